@@ -12,7 +12,6 @@ const INITIAL_WIDTH: u32 = 100;
 const INITIAL_HEIGHT: u32 = 100;
 const SCALE_FACTOR: f32 = 6.0;
 
-#[path = "game.rs"] mod new_game;
 #[path = "0old_game.rs"] mod game;
 use game::*;
 
@@ -0,0 +1,25 @@
+use bevy::prelude::*;
+use bevy_egui::EguiPlugin;
+#[cfg(feature = "dev-tools")]
+use bevy_inspector_egui::quick::WorldInspectorPlugin;
+
+use rusteroids::client::ClientPlugin;
+
+fn main(){
+    let mut app = App::new();
+    app.add_plugins((DefaultPlugins.set(
+        ImagePlugin::default_nearest()
+        ).set(WindowPlugin {
+            primary_window: Some(Window {
+                title: "RUSTEROIDS".into(),
+                ..default()
+            }),
+            ..default()
+        }),
+        EguiPlugin,
+    ));
+    #[cfg(feature = "dev-tools")]
+    app.add_plugins(WorldInspectorPlugin::new());
+    app.add_plugins(ClientPlugin);
+    app.run()
+}
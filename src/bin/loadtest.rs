@@ -0,0 +1,256 @@
+use std::{net::{SocketAddr, UdpSocket}, process::ExitCode, time::{Duration, Instant, SystemTime}};
+
+use bevy::prelude::{Color, Vec2};
+use bevy_renet::renet::{RenetClient, transport::{ClientAuthentication, NetcodeClientTransport}};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+use rusteroids::shared::*;
+
+/*
+    LOAD TEST
+    opens N headless clients to running server, prints stats and fails if limits are exceeded
+
+    cargo run --bin loadtest -- --clients 32 --secs 30
+    --addr 127.0.0.1:8567     server (start it with --max-clients if N > 16 and --no-profiles)
+    --clients 16              number of simulated clients
+    --secs 30                 test duration
+    --inputs random           random | circle | idle
+    --input-rate 30           inputs per second per client
+    --max-tick-ms 16          limit for average server tick
+    --max-bytes 100000        limit for bytes per client per second
+    --max-message 8000        limit for update message size
+    --max-latency-ms 100      limit for p99 snapshot latency, server send to receive, clocks must agree (same machine)
+*/
+
+const GAME_PROTOCOL_ID: u64 = 0;
+const FRAME: Duration = Duration::from_micros(16_667);
+const WARMUP_SECS: f32 = 2.; // connecting and registration are not measured
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum InputScript{
+    Random,
+    Circle,
+    Idle,
+}
+
+struct SimClient{
+    client: RenetClient,
+    transport: NetcodeClientTransport,
+    rng: ChaCha8Rng,
+    registered: bool,
+    direction: Vec2, // for random inputs
+    last_input: f32,
+    last_snapshot: Option<Instant>,
+    last_tick: u64,
+    snapshot_intervals_ms: Vec<f32>,
+    latencies_ms: Vec<f32>,
+    message_sizes: Vec<f32>,
+    bytes_per_sec: Vec<f32>,
+    error: Option<String>,
+}
+
+impl SimClient{
+    fn new(server_addr: SocketAddr, n: u64) -> Self{
+        let current_time = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).unwrap();
+        let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let transport = NetcodeClientTransport::new(
+            current_time,
+            ClientAuthentication::Unsecure {
+                protocol_id: GAME_PROTOCOL_ID,
+                client_id: current_time.as_millis() as u64 * 1000 + n,
+                server_addr: server_addr,
+                user_data: None
+            },
+            socket
+        ).unwrap();
+        SimClient{
+            client: RenetClient::new(connection_config()),
+            transport: transport,
+            rng: ChaCha8Rng::seed_from_u64(n),
+            registered: false,
+            direction: Vec2::ZERO,
+            last_input: 0.,
+            last_snapshot: None,
+            last_tick: 0,
+            snapshot_intervals_ms: vec![],
+            latencies_ms: vec![],
+            message_sizes: vec![],
+            bytes_per_sec: vec![],
+            error: None,
+        }
+    }
+
+    fn send(&mut self, channel: ClientChannel, message: &Message){
+        self.client.send_message(channel, bincode::serialize(message).unwrap());
+    }
+
+    fn inputs(&mut self, script: InputScript, elapsed: f32) -> InputKeys{
+        let mut inputs = InputKeys::default();
+        inputs.acked_tick = self.last_tick;
+        match script {
+            InputScript::Random => {
+                if self.direction == Vec2::ZERO || self.rng.gen_bool(0.05){
+                    self.direction = Vec2::from_angle(self.rng.gen::<f32>() * std::f32::consts::PI * 2.);
+                }
+                inputs.input_vector = self.direction;
+                inputs.rotation_target = Vec2::from_angle(self.rng.gen::<f32>() * std::f32::consts::PI * 2.);
+                inputs.shoot = self.rng.gen_bool(0.3);
+                inputs.dash = self.rng.gen_bool(0.01);
+            }
+            InputScript::Circle => {
+                inputs.input_vector = Vec2::from_angle(elapsed);
+                inputs.rotation_target = Vec2::from_angle(elapsed * 2.);
+                inputs.shoot = true;
+            }
+            InputScript::Idle => {}
+        }
+        inputs
+    }
+}
+
+fn arg<T: std::str::FromStr>(name: &str, default: T) -> T{
+    parse_arg(name).and_then(|value| value.parse().ok()).unwrap_or(default)
+}
+
+fn stats(values: &[f32]) -> (f32, f32, f32){ // avg, p99, max
+    if values.is_empty() {return (0., 0., 0.);}
+    let mut sorted = values.to_vec();
+    sorted.sort_by(|a, b| a.total_cmp(b));
+    let avg = sorted.iter().sum::<f32>() / sorted.len() as f32;
+    let p99 = sorted[((sorted.len() - 1) as f32 * 0.99) as usize];
+    (avg, p99, *sorted.last().unwrap())
+}
+
+fn main() -> ExitCode{
+    let server_addr: SocketAddr = arg("--addr", "127.0.0.1:8567".parse().unwrap());
+    let clients_number: u64 = arg("--clients", 16);
+    let secs: f32 = arg("--secs", 30.);
+    let input_rate: f32 = arg("--input-rate", 30.);
+    let script = match parse_arg("--inputs").as_deref() {
+        Some("circle") => InputScript::Circle,
+        Some("idle") => InputScript::Idle,
+        _ => InputScript::Random,
+    };
+    let max_tick_ms: f32 = arg("--max-tick-ms", 16.);
+    let max_bytes: f32 = arg("--max-bytes", 100000.);
+    let max_message: f32 = arg("--max-message", 8000.);
+    let max_latency_ms: f32 = arg("--max-latency-ms", 100.);
+
+    println!("LOAD TEST: {} clients -> {} for {}s, {:?} inputs at {}/s", clients_number, server_addr, secs, script, input_rate);
+    let mut clients: Vec<SimClient> = (0..clients_number).map(|n| SimClient::new(server_addr, n)).collect();
+    let mut server_ticks: Vec<(f32, f32)> = vec![]; // avg, max
+    let start = Instant::now();
+    let mut last_frame = Instant::now();
+    let mut last_sample = 0.;
+
+    while start.elapsed().as_secs_f32() < secs {
+        let dt = last_frame.elapsed();
+        last_frame = Instant::now();
+        let elapsed = start.elapsed().as_secs_f32();
+        let measuring = elapsed > WARMUP_SECS;
+        for (i, sim) in clients.iter_mut().enumerate(){
+            if sim.error.is_some() {continue;}
+            sim.client.update(dt);
+            if let Err(e) = sim.transport.update(dt, &mut sim.client){
+                sim.error = Some(e.to_string());
+                continue;
+            }
+            if sim.client.is_connected(){
+                while let Some(message) = sim.client.receive_message(ServerChannel::Garanteed){
+                    match bincode::deserialize::<Message>(&message) {
+                        Ok(Message::Greeteng {  }) => {
+                            sim.send(ClientChannel::Garanteed, &Message::RegisterClient{
+                                style: (i % 256) as u8,
+                                color: Color::rgb(0.5, 0.8, 1.),
+                                name: format!("LOAD{}", i),
+                                credentials: None, // new profile every run, server with --no-profiles doesn't save them
+                            });
+                        }
+                        Ok(Message::OnConnect { .. }) => {
+                            sim.registered = true;
+                        }
+                        Ok(Message::ServerStats { avg_tick_ms, max_tick_ms, .. }) => {
+                            if i == 0 && measuring {server_ticks.push((avg_tick_ms, max_tick_ms));} // same for everyone
+                        }
+                        Ok(Message::Kick { reason }) => {
+                            sim.error = Some(format!("kicked: {}", reason));
+                        }
+                        _ => {}
+                    }
+                }
+                while let Some(message) = sim.client.receive_message(ServerChannel::Fast){
+                    let Ok(Message::Update { tick, sent_ms, .. }) = bincode::deserialize::<Message>(&message) else {continue;};
+                    sim.last_tick = sim.last_tick.max(tick);
+                    let now = Instant::now();
+                    if measuring {
+                        sim.message_sizes.push(message.len() as f32);
+                        let received_ms = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).unwrap().as_millis() as u64;
+                        sim.latencies_ms.push(received_ms.saturating_sub(sent_ms) as f32); // includes time in send queue
+                        if let Some(previous) = sim.last_snapshot {
+                            sim.snapshot_intervals_ms.push((now - previous).as_secs_f32() * 1000.);
+                        }
+                    }
+                    sim.last_snapshot = Some(now);
+                }
+                if sim.registered && elapsed - sim.last_input >= 1. / input_rate {
+                    sim.last_input = elapsed;
+                    let inputs = sim.inputs(script, elapsed);
+                    sim.send(ClientChannel::Fast, &Message::Inputs { inputs });
+                }
+            }
+            if let Err(e) = sim.transport.send_packets(&mut sim.client){
+                sim.error = Some(e.to_string());
+            }
+        }
+        if measuring && elapsed - last_sample >= 1. {
+            last_sample = elapsed;
+            for sim in clients.iter_mut().filter(|sim| sim.registered && sim.error.is_none()){
+                sim.bytes_per_sec.push(sim.client.bytes_received_per_sec() as f32);
+            }
+        }
+        std::thread::sleep(FRAME.saturating_sub(last_frame.elapsed()));
+    }
+    for sim in clients.iter_mut(){
+        sim.transport.disconnect();
+    }
+
+    // REPORT
+    let mut failures: Vec<String> = vec![];
+    let registered = clients.iter().filter(|sim| sim.registered && sim.error.is_none()).count();
+    for (i, sim) in clients.iter().enumerate(){
+        if let Some(e) = &sim.error {failures.push(format!("client {}: {}", i, e));}
+    }
+    if registered < clients.len() {failures.push(format!("only {}/{} clients joined", registered, clients.len()));}
+
+    let collect = |f: fn(&SimClient) -> &Vec<f32>| clients.iter().flat_map(|sim| f(sim).iter().copied()).collect::<Vec<f32>>();
+    let tick = stats(&server_ticks.iter().map(|t| t.0).collect::<Vec<f32>>());
+    let tick_max = server_ticks.iter().map(|t| t.1).fold(0., f32::max);
+    let bytes = stats(&collect(|sim| &sim.bytes_per_sec));
+    let sizes = stats(&collect(|sim| &sim.message_sizes));
+    let intervals = stats(&collect(|sim| &sim.snapshot_intervals_ms));
+    let latency = stats(&collect(|sim| &sim.latencies_ms));
+
+    println!("clients joined:          {}/{}", registered, clients.len());
+    println!("server tick ms:          avg {:.2}  p99 {:.2}  max {:.2}", tick.0, tick.1, tick_max);
+    println!("bytes per client per s:  avg {:.0}  p99 {:.0}  max {:.0}", bytes.0, bytes.1, bytes.2);
+    println!("update message bytes:    avg {:.0}  p99 {:.0}  max {:.0}", sizes.0, sizes.1, sizes.2);
+    println!("snapshot interval ms:    avg {:.1}  p99 {:.1}  max {:.1}", intervals.0, intervals.1, intervals.2);
+    println!("snapshot latency ms:     avg {:.1}  p99 {:.1}  max {:.1}", latency.0, latency.1, latency.2);
+
+    if server_ticks.is_empty() {failures.push("no server stats received".into());}
+    if tick.0 > max_tick_ms {failures.push(format!("server tick {:.2}ms > {}ms", tick.0, max_tick_ms));}
+    if bytes.2 > max_bytes {failures.push(format!("{:.0} bytes per client per second > {}", bytes.2, max_bytes));}
+    if sizes.2 > max_message {failures.push(format!("update message {:.0} bytes > {}", sizes.2, max_message));}
+    if latency.1 > max_latency_ms {failures.push(format!("snapshot latency p99 {:.1}ms > {}ms", latency.1, max_latency_ms));}
+
+    if failures.is_empty(){
+        println!("OK");
+        ExitCode::SUCCESS
+    } else {
+        for failure in failures.iter(){
+            eprintln!("FAILED: {}", failure);
+        }
+        ExitCode::FAILURE
+    }
+}
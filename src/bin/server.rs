@@ -0,0 +1,106 @@
+use bevy::prelude::*;
+#[cfg(not(feature = "client"))]
+use bevy::{app::ScheduleRunnerPlugin, log::LogPlugin};
+#[cfg(feature = "client")]
+use bevy_egui::EguiPlugin;
+#[cfg(feature = "dev-tools")]
+use bevy_inspector_egui::quick::WorldInspectorPlugin;
+
+use rusteroids::{server::*, shared::*};
+
+fn main(){
+    let mut app = App::new();
+    #[cfg(feature = "client")]
+    app.add_plugins((
+        DefaultPlugins.set(WindowPlugin {
+            primary_window: Some(Window {
+                title: "RUSTEROIDS server".into(),
+                ..default()
+            }),
+            ..default()
+        }),
+        EguiPlugin,
+    ));
+    #[cfg(feature = "dev-tools")]
+    app.add_plugins(WorldInspectorPlugin::new());
+
+    let mut cfg = GlobalConfig{
+        map_size_chunks: Vec2{
+            x: 3.,
+            y: 2.
+        },
+        single_chunk_size: Vec2{
+            x: 1000.,
+            y: 1000.,
+        },
+        powerups: match load_power_up_definitions("assets/powerups.ron") { // editable without rebuild, clients get them with config
+            Ok(powerups) => powerups,
+            Err(e) => {
+                warn!("Can't load powerups, using built-in ones: {}", e);
+                parse_power_up_definitions(DEFAULT_POWERUPS).unwrap()
+            }
+        },
+        world_seed: parse_arg("--seed").and_then(|seed| seed.parse().ok()).unwrap_or_else(rand::random), // `--seed N` replays the same world
+        tick_rate: parse_rate("--tick-rate", 60.),
+        snapshot_rate: parse_rate("--snapshot-rate", 30.),
+        max_rewind_ms: parse_arg("--max-rewind-ms").and_then(|ms| ms.parse().ok()).unwrap_or(200.), // 0 disables lag compensation
+        ..default()
+    };
+    if let Some(path) = parse_arg("--map"){ // map from file instead of random asteroids
+        match load_map(&path) {
+            Ok(map) => {
+                println!("LOADED MAP {}", map.name);
+                cfg.apply_map(&map);
+            }
+            Err(e) => {
+                eprintln!("Can't load map {}: {}", path, e);
+                std::process::exit(1);
+            }
+        }
+    }
+    let profiles_path = parse_arg("--profiles").unwrap_or("profiles.json".into()); // lifetime stats by client identity
+    if std::env::args().any(|arg| arg == "--no-profiles"){ // load tests, nothing is written
+        app.insert_resource(profiles::ProfileStore::default());
+    } else {
+        match profiles::ProfileStore::load(&profiles_path) {
+            Ok(store) => {app.insert_resource(store);}
+            Err(e) => {
+                eprintln!("Can't load profiles {}: {}", profiles_path, e); // don't overwrite it with empty one
+                std::process::exit(1);
+            }
+        }
+    }
+    if let Some(bind) = parse_arg("--http"){ // read-only stats JSON, e.g. 127.0.0.1:8568
+        app.insert_resource(http::HttpSettings{bind});
+    }
+    app.insert_resource(ServerSettings{
+        port: parse_arg("--port").and_then(|port| port.parse().ok()).unwrap_or(8567),
+        max_clients: parse_arg("--max-clients").and_then(|n| n.parse().ok()).unwrap_or(16), // renet limit is 1024
+    });
+    #[cfg(not(feature = "client"))] // headless, loop is slept to the tick rate instead of vsync
+    app.add_plugins((
+        MinimalPlugins.set(ScheduleRunnerPlugin::run_loop(std::time::Duration::from_secs_f32(1. / cfg.tick_rate))),
+        LogPlugin::default(),
+        HeadlessPlugin,
+    ));
+    app.add_plugins(ServerPlugin{config: cfg});
+    #[cfg(feature = "client")]
+    app.add_plugins(ServerWindowPlugin);
+    app.add_systems(OnEnter(ServerState::Running), spawn_test_bots);
+    console::init_shutdown_signals(&mut app); // countdown, MatchEnded and profile save like /shutdown
+    #[cfg(not(feature = "client"))] // window has its own console
+    console::init_stdin_commands(&mut app);
+    app.run();
+}
+
+/// Per second rate, anything but a positive number stops the server like a broken map does
+fn parse_rate(name: &str, default: f32) -> f32{
+    let Some(arg) = parse_arg(name) else {return default;};
+    match arg.parse::<f32>() {
+        Ok(rate) if rate.is_finite() && rate > 0. => rate,
+        _ => {
+            eprintln!("Bad {} {}: must be a positive number", name, arg);
+            std::process::exit(1);
+        }
+    }
+}
@@ -1,9 +1,9 @@
-use std::{cmp::Ordering, ops::Mul};
+use std::ops::Mul;
 
-use bevy::{app::App, ecs::system::{Res, ResMut, Resource}, math::Vec2, time::Time, utils::hashbrown::{HashMap, HashSet}};
+use bevy::{app::{App, Plugin, Update}, ecs::{schedule::{IntoSystemConfigs, common_conditions::resource_exists}, system::{Res, ResMut, Resource}}, math::Vec2, time::Time, utils::hashbrown::{HashMap, HashSet}};
 use json::object;
 
-use crate::{ClientsData, GlobalConfig, InputKeys, ObjectData};
+use crate::shared::{ClientsData, GlobalConfig, InputKeys, ObjectData, ObjectType, SafeZone, SpatialIndex, SpatialKind};
 
 /*
 self -> movemvents 
@@ -107,36 +107,22 @@ impl BotList{
 }
 
 
-fn world_wrapped_vec(obj1: Vec2, obj2: Vec2, world_size: Vec2) -> Vec2 { // todo: move to cfg and use in stars/dust layers
-    let vector_without_looping = obj2 - obj1;
-    return Vec2::from((
-        [vector_without_looping.x, vector_without_looping.x - world_size.x, vector_without_looping.x + world_size.x]
-            .iter()
-            .min_by(|&x, &y| {
-                x.abs().partial_cmp(&y.abs()).unwrap_or(Ordering::Equal)
-            })
-            .unwrap()
-            .clone(),
-        [vector_without_looping.y, vector_without_looping.y - world_size.y, vector_without_looping.y + world_size.y]
-            .iter()
-            .min_by(|&x, &y| {
-                x.abs().partial_cmp(&y.abs()).unwrap_or(Ordering::Equal)
-            })
-            .unwrap()
-            .clone(),
-    ));
-}
+/// Bots think every frame, server applies their responses like inputs of players
+pub struct BotPlugin;
 
-pub fn init_bots_ai(
-    app: &mut App,
-){
-    app.insert_resource(BotList::default());
+impl Plugin for BotPlugin{
+    fn build(&self, app: &mut App){
+        app.insert_resource(BotList::default());
+        app.add_systems(Update, calculate_bots_response.run_if(resource_exists::<SpatialIndex>()));
+    }
 }
 
 pub fn calculate_bots_response(
     mut bots: ResMut<BotList>,
     clients_data: Res<ClientsData>,
     cfg: Res<GlobalConfig>,
+    safe_zone: Res<SafeZone>,
+    index: Res<SpatialIndex>,
     time: Res<Time>,
 ){
     for bot_id in bots.get_bots_client_ids().iter(){
@@ -146,8 +132,6 @@ pub fn calculate_bots_response(
         let mut shooting_target : Option<&ObjectData> = None;
         let mut self_data : Option<&ObjectData> = None;
 
-        let world_size = cfg.map_size_chunks * cfg.single_chunk_size;
-
         // target is 
         //                         ?
         // n. powerup -> n. player / lowest hp player -> n. asteroid
@@ -157,19 +141,25 @@ pub fn calculate_bots_response(
             let objects = bots.get_bot_world_state(bot_id);
             if objects.is_some(){
                 let objects = objects.unwrap();
+                let mut visible_ships = HashMap::new(); // object_id -> data, hidden ones are not sent to bots
                 for object_data in objects.iter(){
                     match object_data.object.object_type{
-                        crate::ObjectType::Ship { style, color, shields, hp } => {
+                        ObjectType::Ship { style, color, shields, hp } => {
                             //fly_target = 
                             if (object_data.object.id == bot_object_id){ // define self position
                                 self_data = Some(object_data);
                             } else {
-                                shooting_target = Some(object_data);
+                                visible_ships.insert(object_data.object.id, object_data);
                             }
                         }
                         _ => {}
                     }
                 }
+                if let Some(self_data) = self_data{ // nearest visible ship
+                    shooting_target = index.nearest(&cfg, self_data.translation.truncate(), cfg.interest.max_radius, |entry| {
+                        entry.kind == SpatialKind::Ship && visible_ships.contains_key(&entry.object_id)
+                    }).and_then(|(entry, _)| visible_ships.get(&entry.object_id).copied());
+                }
             }
         }
 
@@ -180,7 +170,7 @@ pub fn calculate_bots_response(
         if self_data.is_some() {
             let self_data = self_data.unwrap();
             if shooting_target.is_some(){
-                let target_vector = world_wrapped_vec(self_data.translation.truncate(), shooting_target.unwrap().translation.truncate(), world_size);
+                let target_vector = cfg.world_wrapped_vec(self_data.translation.truncate(), shooting_target.unwrap().translation.truncate());
                 let target_distance_squared = target_vector.length_squared();
                 let direction = target_vector.normalize();
 
@@ -246,6 +236,9 @@ pub fn calculate_bots_response(
                 let velocity_difference = desired_velocity - self_data.linear_velocity;
                 */
             }
+            if !safe_zone.contains(self_data.translation.truncate()){ // get back to zone before anything else
+                bot_inputs.input_vector = (safe_zone.center - self_data.translation.truncate()).normalize_or_zero();
+            }
         }
         
         
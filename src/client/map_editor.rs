@@ -0,0 +1,398 @@
+use std::f32::consts::PI;
+use bevy::{prelude::*, input::mouse::MouseWheel, window::PrimaryWindow};
+use bevy_egui::{egui::{self, Style, Visuals, epaint::Shadow, Color32, Rounding, Stroke}, EguiContexts};
+
+use crate::shared::*;
+
+/*
+    MAP EDITOR
+    LMB    - place object with selected tool (wall needs two clicks)
+    RMB    - erase nearest object
+    WASD   - move camera
+    SCROLL - zoom
+*/
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum EditorTool{
+    Asteroid,
+    Wall,
+    SpawnPoint,
+    PowerUPSpawner,
+    GravityWell,
+}
+
+#[derive(Resource)]
+pub struct MapEditor{
+    pub map: MapFile,
+    pub path: String,
+    pub tool: EditorTool,
+    pub wall_start: Option<Vec2>,
+    pub wall_thickness: f32,
+    pub well: GravityWell, // template for new wells
+    pub powerup: String,
+    pub respawn_secs: f32,
+    pub status: String,
+}
+
+impl Default for MapEditor{
+    fn default() -> Self {
+        MapEditor{
+            map: MapFile::default(),
+            path: "assets/maps/new_map.ron".into(),
+            tool: EditorTool::Asteroid,
+            wall_start: None,
+            wall_thickness: 20.,
+            well: GravityWell{kind: GravityWellKind::Star, position: Vec2::ZERO, strength: 40., radius: 800., core_radius: 40.},
+            powerup: "repair".into(),
+            respawn_secs: 20.,
+            status: "".into(),
+        }
+    }
+}
+
+pub fn init_map_editor(app: &mut App){
+    app.init_resource::<MapEditor>();
+    app.add_systems(OnEnter(ClientState::Editor), setup_map_editor);
+    app.add_systems(Update, (map_editor_ui, map_editor_input, map_editor_render).chain().run_if(in_state(ClientState::Editor)));
+    app.add_systems(OnExit(ClientState::Editor), exit_map_editor);
+}
+
+fn setup_map_editor(
+    editor: Res<MapEditor>,
+    mut camera_q: Query<(&Camera, &mut Transform), With<PixelCamera>>,
+){
+    let world_size = editor.map.map_size_chunks * editor.map.single_chunk_size;
+    for (camera, mut transform) in camera_q.iter_mut(){
+        let viewport = camera.logical_viewport_size().unwrap_or(Vec2::new(256., 180.));
+        transform.translation = (world_size / 2.).extend(0.);
+        transform.scale = Vec3::splat((world_size.x / viewport.x).max(world_size.y / viewport.y) * 1.1);
+    }
+}
+
+fn exit_map_editor(
+    mut camera_q: Query<&mut Transform, With<PixelCamera>>,
+){
+    for mut transform in camera_q.iter_mut(){
+        *transform = Transform::default();
+    }
+}
+
+fn map_editor_ui(
+    mut egui_context: EguiContexts,
+    mut editor: ResMut<MapEditor>,
+    mut next_state: ResMut<NextState<ClientState>>,
+    cfg: Res<GlobalConfig>,
+){
+    let ctx: &mut egui::Context = egui_context.ctx_mut();
+    let style = Style{ // todo: unificate styles and fonts!
+        visuals: Visuals{
+            window_rounding: Rounding::ZERO,
+            window_shadow: Shadow::NONE,
+            window_fill: Color32::from_rgba_unmultiplied(0, 0, 0, 230),
+            window_stroke: Stroke{
+                width: 1.,
+                color: Color32::from_rgba_unmultiplied(255, 255, 255, 255)
+            },
+            ..default()
+        },
+        animation_time: 0.,
+        ..default()
+    };
+    let editor = &mut *editor;
+    egui::Window::new("MAP EDITOR")
+        .anchor(egui::Align2::LEFT_TOP, [10., 10.])
+        .resizable(false)
+        .collapsible(true)
+        .vscroll(true)
+        .show(ctx, |ui|{
+            ui.set_style(style.clone());
+            // FILE
+            ui.horizontal(|ui|{
+                ui.label("name");
+                ui.text_edit_singleline(&mut editor.map.name);
+            });
+            ui.horizontal(|ui|{
+                ui.label("file");
+                ui.text_edit_singleline(&mut editor.path);
+            });
+            ui.horizontal(|ui|{
+                if ui.button("SAVE").clicked(){
+                    if let Some(dir) = std::path::Path::new(&editor.path).parent(){
+                        let _ = std::fs::create_dir_all(dir);
+                    }
+                    editor.status = match save_map(&editor.path, &editor.map) {
+                        Ok(()) => format!("saved to {}", editor.path),
+                        Err(e) => format!("can't save: {}", e),
+                    };
+                }
+                if ui.button("LOAD").clicked(){
+                    editor.status = match load_map(&editor.path) {
+                        Ok(map) => {
+                            editor.map = map;
+                            format!("loaded {}", editor.path)
+                        }
+                        Err(e) => format!("can't load: {}", e),
+                    };
+                }
+                if ui.button("CLEAR").clicked(){
+                    editor.map = MapFile{name: editor.map.name.clone(), ..default()};
+                    editor.status = "cleared".into();
+                }
+            });
+            if !editor.status.is_empty(){
+                ui.label(&editor.status);
+            }
+            ui.separator();
+            // MAP
+            ui.horizontal(|ui|{
+                ui.label("chunks");
+                ui.add(egui::DragValue::new(&mut editor.map.map_size_chunks.x).clamp_range(1..=50).speed(0.1));
+                ui.add(egui::DragValue::new(&mut editor.map.map_size_chunks.y).clamp_range(1..=50).speed(0.1));
+            });
+            ui.horizontal(|ui|{
+                ui.label("chunk size");
+                ui.add(egui::DragValue::new(&mut editor.map.single_chunk_size.x).clamp_range(100..=5000).speed(10.));
+                ui.add(egui::DragValue::new(&mut editor.map.single_chunk_size.y).clamp_range(100..=5000).speed(10.));
+            });
+            editor.map.map_size_chunks = editor.map.map_size_chunks.round(); // MUST BE INTEGER
+            editor.map.single_chunk_size = editor.map.single_chunk_size.round();
+            ui.checkbox(&mut editor.map.fill_asteroids, "fill with random asteroids");
+            ui.horizontal_wrapped(|ui|{
+                ui.label("borders");
+                let topology = &mut editor.map.topology;
+                let zone = MapTopology::ShrinkingZone { start_delay_secs: 60., shrink_secs: 240., min_radius: 300., damage_per_sec: 10. };
+                if ui.selectable_label(*topology == MapTopology::Torus, "TORUS").clicked() {*topology = MapTopology::Torus;}
+                if ui.selectable_label(*topology == MapTopology::Bounded { walls: BoundaryWalls::Solid }, "SOLID").clicked() {*topology = MapTopology::Bounded { walls: BoundaryWalls::Solid };}
+                if ui.selectable_label(*topology == MapTopology::Bounded { walls: BoundaryWalls::Bouncy }, "BOUNCY").clicked() {*topology = MapTopology::Bounded { walls: BoundaryWalls::Bouncy };}
+                if ui.selectable_label(matches!(topology, MapTopology::ShrinkingZone { .. }), "SHRINKING ZONE").clicked() && !matches!(topology, MapTopology::ShrinkingZone { .. }) {*topology = zone;}
+            });
+            if let MapTopology::ShrinkingZone { start_delay_secs, shrink_secs, min_radius, damage_per_sec } = &mut editor.map.topology{
+                ui.horizontal_wrapped(|ui|{
+                    ui.label("delay");
+                    ui.add(egui::DragValue::new(start_delay_secs).clamp_range(0..=3600));
+                    ui.label("shrink secs");
+                    ui.add(egui::DragValue::new(shrink_secs).clamp_range(1..=3600));
+                    ui.label("min radius");
+                    ui.add(egui::DragValue::new(min_radius).clamp_range(0..=10000).speed(5.));
+                    ui.label("damage/sec");
+                    ui.add(egui::DragValue::new(damage_per_sec).clamp_range(0..=1000));
+                });
+            }
+            ui.horizontal(|ui|{
+                ui.label("background");
+                let mut color = editor.map.background.color.as_rgba_f32();
+                let mut rgb = [color[0], color[1], color[2]];
+                if ui.color_edit_button_rgb(&mut rgb).changed(){
+                    color[0..3].copy_from_slice(&rgb);
+                    editor.map.background.color = Color::rgba(color[0], color[1], color[2], color[3]);
+                }
+                ui.label("dust");
+                ui.add(egui::DragValue::new(&mut editor.map.background.dust).clamp_range(0..=5000));
+            });
+            ui.separator();
+            // TOOLS
+            ui.horizontal_wrapped(|ui|{
+                ui.selectable_value(&mut editor.tool, EditorTool::Asteroid, "ASTEROID");
+                ui.selectable_value(&mut editor.tool, EditorTool::Wall, "WALL");
+                ui.selectable_value(&mut editor.tool, EditorTool::SpawnPoint, "SPAWN");
+                ui.selectable_value(&mut editor.tool, EditorTool::PowerUPSpawner, "POWERUP");
+                ui.selectable_value(&mut editor.tool, EditorTool::GravityWell, "GRAVITY WELL");
+            });
+            match editor.tool {
+                EditorTool::Wall => {
+                    ui.horizontal(|ui|{
+                        ui.label("thickness");
+                        ui.add(egui::DragValue::new(&mut editor.wall_thickness).clamp_range(2..=200));
+                    });
+                }
+                EditorTool::PowerUPSpawner => {
+                    egui::ComboBox::from_label("powerup")
+                        .selected_text(editor.powerup.clone())
+                        .show_ui(ui, |ui|{
+                            for def in cfg.powerups.iter(){
+                                ui.selectable_value(&mut editor.powerup, def.id.clone(), &def.id);
+                            }
+                        });
+                    ui.horizontal(|ui|{
+                        ui.label("respawn secs");
+                        ui.add(egui::DragValue::new(&mut editor.respawn_secs).clamp_range(1..=600));
+                    });
+                }
+                EditorTool::GravityWell => {
+                    ui.horizontal(|ui|{
+                        ui.selectable_value(&mut editor.well.kind, GravityWellKind::Star, "STAR");
+                        ui.selectable_value(&mut editor.well.kind, GravityWellKind::BlackHole, "BLACK HOLE");
+                    });
+                    ui.horizontal(|ui|{
+                        ui.label("strength");
+                        ui.add(egui::DragValue::new(&mut editor.well.strength).clamp_range(0..=1000));
+                        ui.label("radius");
+                        ui.add(egui::DragValue::new(&mut editor.well.radius).clamp_range(50..=5000).speed(5.));
+                        ui.label("core");
+                        ui.add(egui::DragValue::new(&mut editor.well.core_radius).clamp_range(5..=500));
+                    });
+                }
+                _ => {}
+            }
+            ui.label("LMB - place, RMB - erase, WASD - move, SCROLL - zoom");
+            ui.label(format!(
+                "obstacles: {}  spawns: {}  powerups: {}  wells: {}",
+                editor.map.obstacles.len(), editor.map.spawn_points.len(), editor.map.powerup_spawn_points.len(), editor.map.gravity_wells.len()
+            ));
+            ui.separator();
+            if ui.button("BACK TO MENU").clicked(){
+                next_state.set(ClientState::Menu);
+            }
+        });
+}
+
+fn map_editor_input(
+    mut egui_context: EguiContexts,
+    mut editor: ResMut<MapEditor>,
+    mut camera_q: Query<(&Camera, &GlobalTransform, &mut Transform), With<PixelCamera>>,
+    canvas_q: Query<&Transform, (With<CameraCanvas>, Without<PixelCamera>)>,
+    window_q: Query<&Window, With<PrimaryWindow>>,
+    buttons: Res<Input<MouseButton>>,
+    keys: Res<Input<KeyCode>>,
+    mut wheel: EventReader<MouseWheel>,
+    time: Res<Time>,
+){
+    let Ok((camera, camera_global_transform, mut camera_transform)) = camera_q.get_single_mut() else {return;};
+    // CAMERA
+    let mut dir = Vec2::ZERO;
+    if keys.pressed(KeyCode::W) || keys.pressed(KeyCode::Up) {dir.y += 1.}
+    if keys.pressed(KeyCode::S) || keys.pressed(KeyCode::Down) {dir.y -= 1.}
+    if keys.pressed(KeyCode::A) || keys.pressed(KeyCode::Left) {dir.x -= 1.}
+    if keys.pressed(KeyCode::D) || keys.pressed(KeyCode::Right) {dir.x += 1.}
+    let scale = camera_transform.scale.x;
+    camera_transform.translation += (dir * 300. * scale * time.delta_seconds()).extend(0.);
+    let ctx = egui_context.ctx_mut();
+    let over_ui = ctx.wants_pointer_input() || ctx.is_pointer_over_area();
+    for e in wheel.read(){
+        if over_ui {continue;}
+        let zoom = if e.y > 0. {0.9} else {1.1};
+        camera_transform.scale = Vec3::splat((scale * zoom).clamp(0.2, 50.));
+    }
+    if over_ui {return;}
+
+    // CURSOR -> WORLD (camera renders to image, which is stretched over window by canvas)
+    let Ok(window) = window_q.get_single() else {return;};
+    let Some(cursor) = window.cursor_position() else {return;};
+    let canvas_scale = canvas_q.get_single().map(|t| t.scale.x).unwrap_or(1.);
+    let Some(pos) = camera.viewport_to_world_2d(camera_global_transform, cursor / canvas_scale) else {return;};
+
+    let editor = &mut *editor;
+    if buttons.just_pressed(MouseButton::Left){
+        match editor.tool {
+            EditorTool::Asteroid => {
+                editor.map.obstacles.push(MapObstacle::Asteroid { seed: rand::random(), position: pos, rotation: rand::random::<f32>() * PI * 2. });
+            }
+            EditorTool::Wall => {
+                match editor.wall_start.take() {
+                    Some(from) => editor.map.obstacles.push(MapObstacle::Wall { from, to: pos, thickness: editor.wall_thickness }),
+                    None => editor.wall_start = Some(pos),
+                }
+            }
+            EditorTool::SpawnPoint => {
+                editor.map.spawn_points.push(pos);
+            }
+            EditorTool::PowerUPSpawner => {
+                editor.map.powerup_spawn_points.push(PowerUPSpawnPoint { position: pos, powerup: editor.powerup.clone(), respawn_secs: editor.respawn_secs });
+            }
+            EditorTool::GravityWell => {
+                editor.map.gravity_wells.push(GravityWell { position: pos, ..editor.well });
+            }
+        }
+    }
+    if buttons.just_pressed(MouseButton::Right){
+        if editor.wall_start.take().is_some() {return;} // cancel wall first
+        // find nearest object of any kind
+        let mut nearest: Option<(f32, usize, usize)> = None; // distance, list, index
+        let mut check = |list: usize, i: usize, p: Vec2| {
+            let d = p.distance(pos);
+            if nearest.map_or(true, |(best, _, _)| d < best) {nearest = Some((d, list, i));}
+        };
+        for (i, obstacle) in editor.map.obstacles.iter().enumerate(){
+            match obstacle {
+                MapObstacle::Asteroid { position, .. } => check(0, i, *position),
+                MapObstacle::Wall { from, to, .. } => check(0, i, (*from + *to) / 2.),
+            }
+        }
+        for (i, p) in editor.map.spawn_points.iter().enumerate(){check(1, i, *p);}
+        for (i, p) in editor.map.powerup_spawn_points.iter().enumerate(){check(2, i, p.position);}
+        for (i, w) in editor.map.gravity_wells.iter().enumerate(){check(3, i, w.position);}
+        if let Some((d, list, i)) = nearest{
+            if d < 50. * scale{
+                match list {
+                    0 => {editor.map.obstacles.remove(i);}
+                    1 => {editor.map.spawn_points.remove(i);}
+                    2 => {editor.map.powerup_spawn_points.remove(i);}
+                    _ => {editor.map.gravity_wells.remove(i);}
+                }
+            }
+        }
+    }
+}
+
+fn map_editor_render(
+    editor: Res<MapEditor>,
+    cfg: Res<GlobalConfig>,
+    camera_q: Query<(&Camera, &GlobalTransform), With<PixelCamera>>,
+    canvas_q: Query<&Transform, With<CameraCanvas>>,
+    window_q: Query<&Window, With<PrimaryWindow>>,
+    mut gizmos: Gizmos,
+){
+    let map = &editor.map;
+    let world_size = map.map_size_chunks * map.single_chunk_size;
+    // GRID
+    for x in 1..(map.map_size_chunks.x as i32){
+        let x = x as f32 * map.single_chunk_size.x;
+        gizmos.line_2d(Vec2::new(x, 0.), Vec2::new(x, world_size.y), Color::DARK_GRAY);
+    }
+    for y in 1..(map.map_size_chunks.y as i32){
+        let y = y as f32 * map.single_chunk_size.y;
+        gizmos.line_2d(Vec2::new(0., y), Vec2::new(world_size.x, y), Color::DARK_GRAY);
+    }
+    gizmos.rect_2d(world_size / 2., 0., world_size, if map.topology.is_wrapped() {Color::WHITE} else {Color::ORANGE_RED});
+    if let MapTopology::ShrinkingZone { min_radius, .. } = map.topology{
+        gizmos.circle_2d(world_size / 2., min_radius, Color::RED).segments(128);
+    }
+    // OBJECTS
+    for obstacle in map.obstacles.iter(){
+        match *obstacle {
+            MapObstacle::Asteroid { seed, position, rotation } => {
+                let (vec, _) = generate_asteroid_vertices(seed, &cfg);
+                let points: Vec<Vec2> = vec.iter().chain(vec.first()).map(|v| position + Vec2::from_angle(rotation).rotate(Vec2::new(v[0], v[1]))).collect();
+                gizmos.linestrip_2d(points, Color::GRAY);
+            }
+            MapObstacle::Wall { from, to, thickness } => {
+                let angle = Vec2::X.angle_between(to - from);
+                gizmos.rect_2d((from + to) / 2., if angle.is_nan() {0.} else {angle}, Vec2::new((to - from).length(), thickness), Color::GRAY);
+            }
+        }
+    }
+    for p in map.spawn_points.iter(){
+        gizmos.circle_2d(*p, 20., Color::GREEN);
+        gizmos.line_2d(*p - Vec2::X * 20., *p + Vec2::X * 20., Color::GREEN);
+        gizmos.line_2d(*p - Vec2::Y * 20., *p + Vec2::Y * 20., Color::GREEN);
+    }
+    for p in map.powerup_spawn_points.iter(){
+        gizmos.rect_2d(p.position, PI / 4., Vec2::splat(24.), Color::YELLOW);
+    }
+    for w in map.gravity_wells.iter(){
+        let color = match w.kind {
+            GravityWellKind::Star => Color::ORANGE,
+            GravityWellKind::BlackHole => Color::PURPLE,
+        };
+        gizmos.circle_2d(w.position, w.core_radius, color);
+        gizmos.circle_2d(w.position, w.radius, color.with_a(0.3));
+    }
+    // WALL IN PROGRESS
+    if let Some(from) = editor.wall_start{
+        let (Ok((camera, camera_global_transform)), Ok(window)) = (camera_q.get_single(), window_q.get_single()) else {return;};
+        let canvas_scale = canvas_q.get_single().map(|t| t.scale.x).unwrap_or(1.);
+        if let Some(pos) = window.cursor_position().and_then(|cursor| camera.viewport_to_world_2d(camera_global_transform, cursor / canvas_scale)){
+            gizmos.line_2d(from, pos, Color::WHITE);
+        }
+    }
+}
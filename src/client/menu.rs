@@ -3,7 +3,7 @@ use bevy::{app::AppExit, core_pipeline::{tonemapping::{Tonemapping, DebandDither
 use bevy_egui::{egui::{self, Style, Visuals, epaint::{Shadow, CircleShape}, Color32, Rounding, Align, Stroke, FontId, load::SizedTexture, Slider, TextureId, ComboBox}, EguiContexts, EguiUserTextures};
 use rand::{random, Rng};
 
-use crate::{game::*, game::components::{ConnectProperties, ClientState}};
+use crate::shared::*;
 
 #[derive(Component)]
 pub struct LabelAnimation;
@@ -79,6 +79,8 @@ pub fn setup_preview_camera(
         entity: Entity::PLACEHOLDER,
         color: Color::WHITE,
         name: "".into(),
+        kills: 0,
+        deaths: 0,
     });
 
     let size = Extent3d {
@@ -161,7 +163,7 @@ pub fn egui_based_menu(
    mut writer: EventWriter<ApplyCameraSettings>,
    //mut writer_init: EventWriter<InitClient>,
    //mut ship_preview: ResMut<ShipPreviewImage>,
-   mut connect_properties: ResMut<ConnectProperties>,
+   (mut connect_properties, mut profile_screen): (ResMut<ConnectProperties>, ResMut<super::profile::ProfileScreen>),
    mut next_state: ResMut<NextState<ClientState>>,
 
    ship_preview_image: Res<ShipPreviewImage>,
@@ -173,16 +175,16 @@ pub fn egui_based_menu(
 
     let mut fonts = egui::FontDefinitions::default();
         fonts.font_data.insert("Font".to_owned(),
-        //egui::FontData::from_static(include_bytes!("../assets/fonts/F77Font-0VYv.ttf") )
-        //egui::FontData::from_static(include_bytes!("../assets/fonts/TerminusTTF-4.49.3.ttf") )
-        //egui::FontData::from_static(include_bytes!("../assets/fonts/unifont-15.1.04.otf") )
-        //egui::FontData::from_static(include_bytes!("../assets/fonts/VCR OSD Mono Cyr.ttf") )
-        //egui::FontData::from_static(include_bytes!("../assets/fonts/pixelplay.ttf") )
-        //egui::FontData::from_static(include_bytes!("../assets/fonts/monocraft.ttf") )
-        egui::FontData::from_static(include_bytes!("../assets/fonts/VecTerminus12Medium.otf") )
-        //egui::FontData::from_static(include_bytes!("../assets/fonts/rzpix.ttf") )
-        //egui::FontData::from_static(include_bytes!("../assets/fonts/CozetteVector.ttf") )
-        //egui::FontData::from_static(include_bytes!("../assets/fonts/bf-mnemonika-regular-regular1.ttf") )
+        //egui::FontData::from_static(include_bytes!("../../assets/fonts/F77Font-0VYv.ttf") )
+        //egui::FontData::from_static(include_bytes!("../../assets/fonts/TerminusTTF-4.49.3.ttf") )
+        //egui::FontData::from_static(include_bytes!("../../assets/fonts/unifont-15.1.04.otf") )
+        //egui::FontData::from_static(include_bytes!("../../assets/fonts/VCR OSD Mono Cyr.ttf") )
+        //egui::FontData::from_static(include_bytes!("../../assets/fonts/pixelplay.ttf") )
+        //egui::FontData::from_static(include_bytes!("../../assets/fonts/monocraft.ttf") )
+        egui::FontData::from_static(include_bytes!("../../assets/fonts/VecTerminus12Medium.otf") )
+        //egui::FontData::from_static(include_bytes!("../../assets/fonts/rzpix.ttf") )
+        //egui::FontData::from_static(include_bytes!("../../assets/fonts/CozetteVector.ttf") )
+        //egui::FontData::from_static(include_bytes!("../../assets/fonts/bf-mnemonika-regular-regular1.ttf") )
         
     );
     
@@ -268,6 +270,8 @@ pub fn egui_based_menu(
                 let play_btn = ui.add_sized(     [300., 40.], egui::Button::new("⚔PLAY⚔")).clicked();
                 let customize_btn = ui.add_sized([300., 40.], egui::Button::new("✱CUSTOMIZE✱")).clicked();
                 let settings_btn = ui.add_sized( [300., 40.], egui::Button::new("⛭SETTINGS⛭")).clicked();
+                let profile_btn = ui.add_sized(  [300., 40.], egui::Button::new("☺PROFILE☺")).clicked();
+                let editor_btn = ui.add_sized(   [300., 40.], egui::Button::new("✎EDITOR✎")).clicked();
                 let exit_btn = ui.add_sized(     [300., 40.], egui::Button::new("xEXITx")).clicked();
                 if exit_btn{
                     exit.send(AppExit);
@@ -281,6 +285,12 @@ pub fn egui_based_menu(
                 if customize_btn{
                     *customize_open = !*customize_open;
                 }
+                if profile_btn{
+                    profile_screen.show = !profile_screen.show;
+                }
+                if editor_btn{
+                    next_state.set(ClientState::Editor);
+                }
             });
             //ui.allocate_space(egui::Vec2::new(1.0, 10.0));
             
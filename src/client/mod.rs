@@ -1,105 +1,115 @@
 use std::{net::UdpSocket, time::SystemTime, f32::consts::PI};
 
-use bevy::{prelude::*, render::{mesh::Indices, render_resource::PrimitiveTopology}, sprite::{MaterialMesh2dBundle, Mesh2dBindGroup, Mesh2dHandle}, transform, utils::{HashMap, HashSet}, window::WindowResized, DefaultPlugins};
+use bevy::{prelude::*, core_pipeline::clear_color::ClearColorConfig, render::{mesh::Indices, render_resource::PrimitiveTopology}, sprite::{MaterialMesh2dBundle, Mesh2dBindGroup, Mesh2dHandle}, transform, utils::{HashMap, HashSet}, window::WindowResized};
 
-use bevy_inspector_egui::{quick::WorldInspectorPlugin, bevy_egui::EguiPlugin};
-use bevy_rapier2d::{na::Translation, plugin::{NoUserData, RapierPhysicsPlugin}, prelude::Velocity, render::{DebugRenderContext, RapierDebugRenderPlugin}};
+use bevy_rapier2d::{na::Translation, plugin::{NoUserData, RapierPhysicsPlugin}, prelude::Velocity};
+#[cfg(feature = "dev-tools")]
+use bevy_rapier2d::render::RapierDebugRenderPlugin;
 use bevy_renet::{renet::{*, transport::*}, transport::NetcodeClientPlugin, RenetClientPlugin};
+use bevy_egui::{egui, EguiContexts};
 use rand::Rng;
-use renet_visualizer::RenetServerVisualizer;
 
-#[path = "client_menu.rs"] mod client_menu;
-use client_menu::*;
-#[path = "game.rs"] mod game;
-use game::*;
-use game::components::*;
+pub mod menu;
+use menu::*;
+pub mod map_editor;
+pub mod net_stats;
+use net_stats::NetStats;
+pub mod profile;
+use profile::{ClientCredentials, ProfileScreen};
+use crate::shared::*;
 use serde::de::value;
 use weighted_rand::builder::*;
 
-fn main(){
-    let mut app = App::new();
-
-    //let default_settings = settings::GameSettings::init();
-
-    //app.insert_resource(default_settings);
-    app.init_resource::<GameSettings>();
-    app.add_state::<ClientState>();
-    // todo: USE RAPIER PHYSICS ON CLIENT???
-    app.add_plugins(RenetClientPlugin);
-    app.add_plugins(RapierPhysicsPlugin::<NoUserData>::pixels_per_meter(100.0));
-    app.add_plugins(RapierDebugRenderPlugin::default());
-    app.add_plugins(NetcodeClientPlugin);
-    app.insert_resource(RenetServerVisualizer::<200>::default());          
-    app.insert_resource(RenetClient::new(ConnectionConfig::default()));
-    app.insert_resource(GlobalConfig::default());
-    app.insert_resource(ClientsData::default());
-    app.insert_resource(LoadedChunks{chunks: vec![]});
-    app.add_plugins((DefaultPlugins.set(
-        ImagePlugin::default_nearest()
-        ).set(WindowPlugin {
-            primary_window: Some(Window {
-                title: "RUSTEROIDS".into(),
-                ..default()
-            }),
-            ..default()
-        }),
-        EguiPlugin,
-        WorldInspectorPlugin::new()
-    ));
-
-
-    app.add_systems(
-        OnEnter(ClientState::Menu), 
-        (
-            setup_splash,
-            setup_preview_camera
-    ));
-    app.add_systems(
-        Update, 
-        (
-            update_menu,
-            update_beams,
-            egui_based_menu,
-            update_preview_ship,
-    ).run_if(in_state(ClientState::Menu)));
-    app.add_systems(
-        OnExit(ClientState::Menu), 
-        (
-            despawn_menu,
-    ));
-
-
-    app.add_systems(
-        OnEnter(ClientState::InGame), 
-        (
-            init_client,
-    ));
-    app.add_systems(
-        Update, 
-        (
-            debug_chunk_render,
-            update_powerups_animation,
-            (receive_message_system, snap_objects, update_chunks_around, starfield_update, camera_follow, ship_labels).chain(),
+/// Menu, map editor and game with connection to server. Needs DefaultPlugins and EguiPlugin
+pub struct ClientPlugin;
+
+impl Plugin for ClientPlugin{
+    fn build(&self, app: &mut App){
+        //let default_settings = settings::GameSettings::init();
+
+        //app.insert_resource(default_settings);
+        app.init_resource::<GameSettings>();
+        app.add_state::<ClientState>();
+        // todo: USE RAPIER PHYSICS ON CLIENT???
+        app.add_plugins(RenetClientPlugin);
+        app.add_plugins(RapierPhysicsPlugin::<NoUserData>::pixels_per_meter(100.0));
+        #[cfg(feature = "dev-tools")]
+        app.add_plugins(RapierDebugRenderPlugin::default());
+        app.add_plugins(NetcodeClientPlugin);
+        app.insert_resource(RenetClient::new(ConnectionConfig::default()));
+        app.insert_resource(GlobalConfig::default());
+        app.insert_resource(ClientsData::default());
+        app.init_resource::<SafeZone>();
+        app.init_resource::<SpatialIndex>();
+        app.init_resource::<LastSnapshotTick>();
+        app.add_systems(Startup, setup_immunity_shield_assets);
+        app.insert_resource(LoadedChunks{chunks: vec![]});
+
+
+        app.add_systems(
+            OnEnter(ClientState::Menu), 
+            (
+                setup_splash,
+                setup_preview_camera
+        ));
+        app.add_systems(
+            Update, 
+            (
+                update_menu,
+                update_beams,
+                egui_based_menu,
+                update_preview_ship,
+        ).run_if(in_state(ClientState::Menu)));
+        app.add_systems(
+            OnExit(ClientState::Menu), 
+            (
+                despawn_menu,
+        ));
+
+
+        app.add_systems(
+            OnEnter(ClientState::InGame), 
+            (
+                init_client,
+        ));
+        app.add_systems(
+            Update, 
+            (
+                debug_chunk_render,
+                update_powerups_animation,
+                blink_powerups,
+                render_gravity_wells,
+                update_map_obstacles,
+                update_map_bounds,
+                render_safe_zone,
+                apply_map_background,
+                (receive_message_system, snap_objects, update_spatial_index, update_chunks_around, starfield_update, camera_follow, ship_labels, spawn_immunity_shields, render_beams, render_tracers).chain(),
             
-            handle_inputs_system,
-            tab_menu, // todo
-            esc_menu
+                handle_inputs_system,
+                send_viewport_system,
+                tab_menu, // todo
+                esc_menu
             
-    ).run_if(in_state(ClientState::InGame)));
-    app.add_systems(
-        OnExit(ClientState::InGame), 
-        (
-            on_ingame_exit,
-    ));
-
+        ).run_if(in_state(ClientState::InGame)));
+        app.add_systems(
+            OnExit(ClientState::InGame), 
+            (
+                on_ingame_exit,
+        ));
 
 
-    app.insert_resource(ConnectProperties{adress: "".into()});
 
+        app.insert_resource(ConnectProperties{adress: "".into()});
+        app.init_resource::<ServerNotice>();
+        app.add_systems(Update, server_notice_overlay.run_if(not(in_state(ClientState::Editor))));
+        app.add_systems(Update, auto_reconnect.run_if(in_state(ClientState::Menu)));
 
-    game::init_pixel_camera(&mut app);
 
-    app.run()
+        init_pixel_camera(app);
+        map_editor::init_map_editor(app);
+        net_stats::init_net_stats(app);
+        profile::init_profile(app);
+    }
 }
 
 fn on_ingame_exit(
@@ -107,10 +117,16 @@ fn on_ingame_exit(
     ship_labels_q: Query<Entity, With<ShipLabel>>,
     objects_q: Query<Entity, With<Object>>,
     debug_chuncs_q: Query<Entity, With<Chunk>>,
+    gravity_wells_q: Query<Entity, With<GravityWellSprite>>,
+    obstacles_q: Query<Entity, (With<Obstacle>, Without<MapBound>)>,
+    bounds_q: Query<Entity, With<MapBound>>,
+    tracers_q: Query<Entity, With<TracerSprite>>,
+    mut safe_zone: ResMut<SafeZone>,
     mut clients_data: ResMut<ClientsData>,
     mut star_layer_q: Query<Entity, With<StarsLayer>>,
     mut renet_client: ResMut<RenetClient>,
     mut camera_translation: Query<&mut Transform, (With<Camera>, With<PixelCamera>, Without<Object>)>,
+    mut pixel_camera_q: Query<&mut Camera2d, (With<PixelCamera>, Without<ShipPreviewCamera>)>,
 ){
     // todo: respawn everything
     for e in objects_q.iter(){
@@ -122,8 +138,21 @@ fn on_ingame_exit(
     for e in ship_labels_q.iter(){
         commands.entity(e).despawn();
     }
+    for e in tracers_q.iter(){
+        commands.entity(e).despawn();
+    }
+    for e in gravity_wells_q.iter(){
+        commands.entity(e).despawn_recursive();
+    }
+    for e in obstacles_q.iter().chain(bounds_q.iter()){
+        commands.entity(e).despawn_recursive();
+    }
+    *safe_zone = SafeZone::default();
     star_layer_q.get_single().is_ok().then(|| { commands.entity(star_layer_q.single()).despawn_recursive();});
     camera_translation.single_mut().translation = Vec3::ZERO;
+    for mut camera in pixel_camera_q.iter_mut(){
+        camera.clear_color = ClearColorConfig::Custom(Color::BLACK);
+    }
     renet_client.disconnect();
     //clients_data.clean_exclude_me();
 }
@@ -164,6 +193,7 @@ fn init_client(
     ).unwrap();
     commands.insert_resource(RenetClient::new(connection_config()));
     commands.insert_resource(transport);
+    commands.insert_resource(LastSnapshotTick(0));
     
     
     //let for_spawn_cl_data = ClientData::for_spawn(e.style, color, 0);
@@ -180,17 +210,87 @@ fn init_client(
     
 }
 
+fn apply_map_background(
+    cfg: Res<GlobalConfig>,
+    mut pixel_camera_q: Query<&mut Camera2d, (With<PixelCamera>, Without<ShipPreviewCamera>)>,
+){
+    if !cfg.is_changed() {return;}
+    for mut camera in pixel_camera_q.iter_mut(){
+        camera.clear_color = ClearColorConfig::Custom(cfg.background.color);
+    }
+}
+
 fn send_message(
     renet_client: &mut ResMut<RenetClient>,
+    net_stats: &mut NetStats,
     chanel: ClientChannel,
     message: Message
 ){
     let encoded_message: Vec<u8> = bincode::serialize(&message).unwrap();
+    net_stats.record_sent(&message, encoded_message.len());
     renet_client.send_message(chanel, encoded_message);
 }
 
+/// Last message from server (countdowns, kick reason), shown in game and in menu
+#[derive(Resource, Default)]
+struct ServerNotice{
+    text: Option<String>,
+    shown_at: f32,
+    reconnect_at: Option<f32>, // after MapChange, to the same address
+}
+
+impl ServerNotice{
+    fn show(&mut self, text: String, now: f32){
+        self.text = Some(text);
+        self.shown_at = now;
+    }
+}
+
+fn server_notice_overlay(
+    mut egui_context: EguiContexts,
+    mut notice: ResMut<ServerNotice>,
+    state: Res<State<ClientState>>,
+    time: Res<Time>,
+){
+    let Some(text) = notice.text.clone() else {return;};
+    let in_game = *state.get() == ClientState::InGame;
+    if in_game && time.elapsed_seconds() - notice.shown_at > 5. { // in menu it stays until closed
+        notice.text = None;
+        return;
+    }
+    egui::Window::new("SERVER")
+        .anchor(egui::Align2::CENTER_TOP, [0., 40.])
+        .resizable(false)
+        .collapsible(false)
+        .title_bar(false)
+        .show(egui_context.ctx_mut(), |ui|{
+            ui.label(egui::RichText::new(text).color(egui::Color32::from_rgb(255, 120, 0)));
+            if !in_game && notice.reconnect_at.is_none() && ui.button("OK").clicked(){
+                notice.text = None;
+            }
+        });
+}
+
+fn auto_reconnect(
+    mut notice: ResMut<ServerNotice>,
+    mut next_state: ResMut<NextState<ClientState>>,
+    time: Res<Time>,
+){
+    let Some(at) = notice.reconnect_at else {return;};
+    if time.elapsed_seconds() < at {return;}
+    notice.reconnect_at = None;
+    notice.text = None;
+    next_state.set(ClientState::InGame); // init_client connects to the same ConnectProperties.adress
+}
+
+/// Tick of the newest snapshot, sent back with inputs so server can rewind to it
+#[derive(Resource, Default)]
+struct LastSnapshotTick(u64);
+
 fn handle_inputs_system(
     mut renet_client: ResMut<RenetClient>,
+    mut net_stats: ResMut<NetStats>,
+    last_tick: Res<LastSnapshotTick>,
     mut player_data: Query<(&mut Velocity, &Transform, &Object), With<CameraFollow>>,
     keys: Res<Input<KeyCode>>,
     buttons: Res<Input<MouseButton>>,
@@ -198,6 +298,7 @@ fn handle_inputs_system(
     camera_q: Query<(&Camera, &GlobalTransform), (With<Camera>, Without<PixelCamera>)>,
 ){
     let mut inp = InputKeys::default();
+    inp.acked_tick = last_tick.0;
     /*inp.up = false;
     inp.down = false;
     inp.left = false;
@@ -233,11 +334,26 @@ fn handle_inputs_system(
         }
     }
     
-    send_message(&mut renet_client, ClientChannel::Fast, Message::Inputs { inputs: inp });
+    send_message(&mut renet_client, &mut net_stats, ClientChannel::Fast, Message::Inputs { inputs: inp });
 
     //println!("{:?}", (up, down, right, left));
 }
 
+/// Server sends objects around in radius based on what we can see
+fn send_viewport_system(
+    mut renet_client: ResMut<RenetClient>,
+    mut net_stats: ResMut<NetStats>,
+    projection_q: Query<&OrthographicProjection, With<PixelCamera>>,
+    mut last_sent: Local<(Vec2, f32)>, // size, time
+    time: Res<Time>,
+){
+    let Ok(projection) = projection_q.get_single() else {return;};
+    let size = projection.area.size();
+    if size == last_sent.0 && time.elapsed_seconds() - last_sent.1 < 2. {return;} // resend sometimes, it may be lost
+    *last_sent = (size, time.elapsed_seconds());
+    send_message(&mut renet_client, &mut net_stats, ClientChannel::Fast, Message::Viewport { size });
+}
+
 #[derive(Component)]
 struct CameraFollow;
 
@@ -274,6 +390,7 @@ fn receive_message_system(
     time: Res<Time>,
     mut death_label_q: Query<(Entity, &mut Text), With<DeathLabel>>,
     mut is_dead: Local<(bool, bool, Vec3, f32)>, // first is current, second is previous val, third is pos
+    (mut safe_zone, mut last_tick, mut net_stats, mut notice, mut credentials, mut profile_screen, connect_properties): (ResMut<SafeZone>, ResMut<LastSnapshotTick>, ResMut<NetStats>, ResMut<ServerNotice>, ResMut<ClientCredentials>, ResMut<ProfileScreen>, Res<ConnectProperties>),
 ){
     if client.is_disconnected(){
         next_state.set(ClientState::Menu);
@@ -284,20 +401,35 @@ fn receive_message_system(
     }
     let mut entities_to_keep = vec![];
     let mut data_to_update = vec![];
+    let mut data_to_keep = vec![];
+    let mut snapshots_this_frame = 0;
     while let Some(message) = client.receive_message(ServerChannel::Fast) {
         let msg: Message = bincode::deserialize::<Message>(&message).unwrap();
+        net_stats.record_received(&msg, message.len());
         match msg {
-            Message::Update { data } => {
+            Message::Update { tick, data, keep, .. } => {
+                last_tick.0 = last_tick.0.max(tick); // fast channel is unordered
+                snapshots_this_frame += 1;
                 data_to_update = data;
+                data_to_keep = keep;
+            }
+            Message::SafeZone { zone } => {
+                *safe_zone = zone;
             }
             msg_type => {
                 warn!("Unhandled message recived on client!");
             }
         }
     }
+    net_stats.record_snapshots_per_frame(snapshots_this_frame);
 
     // UPDATE OBJECTS
-    if data_to_update.len() != 0{
+    if data_to_update.len() != 0 || data_to_keep.len() != 0{
+        for id in data_to_keep.iter(){ // not updated this time, but still around
+            if let Some(e) = existing_objects.get(id){
+                entities_to_keep.push(*e);
+            }
+        }
         for object_data in data_to_update.iter(){
             if existing_objects.contains_key(&object_data.object.id){
                 // UPDATE ENTITY
@@ -326,6 +458,12 @@ fn receive_message_system(
                             }
                         }
                         commands.entity(e).insert(states_and_statuses);
+                        if object_data.weapon.is_some(){
+                            commands.entity(e).insert(object_data.weapon.unwrap());
+                        }
+                    }
+                    ObjectType::PickUP { .. } => { // lifetime for blinking
+                        commands.entity(e).insert(object_data.object);
                     }
                     _ => {}
                 }
@@ -343,13 +481,15 @@ fn receive_message_system(
                             &mut commands,
                             object_data.object.id,
                             cfg.get_asteroid_hp(seed),
+                            &cfg,
                         );
                         Some((e, object_data.object.id))
                     },
-                    ObjectType::Bullet { previous_position: _, spawn_time, owner, extra_damage } => {
+                    ObjectType::Bullet { previous_position: _, spawn_time, owner, extra_damage, weapon } => {
                         let e = spawn_bullet(
                             object_data.linear_velocity,
                             extra_damage,
+                            weapon,
                             Transform::from_translation(object_data.translation).with_rotation(object_data.rotation), 
                             object_data.object.id, 
                             owner, 
@@ -387,8 +527,8 @@ fn receive_message_system(
                             None
                         }
                     },
-                    ObjectType::PickUP{pickup_type} => {
-                        let e = spawn_powerup(pickup_type, object_data.translation, &mut commands, &mut meshes, &mut materials, &asset_server, object_data.object.id);
+                    ObjectType::PickUP{pickup_type, lifetime} => {
+                        let e = spawn_powerup(pickup_type, lifetime, object_data.translation, &mut commands, &mut meshes, &mut materials, &asset_server, &cfg, object_data.object.id);
                         Some((e, object_data.object.id))
                     }
                 };
@@ -438,6 +578,7 @@ fn receive_message_system(
 
     while let Some(message) = client.receive_message(ServerChannel::Garanteed) {
         let msg: Message = bincode::deserialize::<Message>(&message).unwrap();
+        net_stats.record_received(&msg, message.len());
         match msg {
             Message::OnConnect{clients_data, config, ship_object_id} => {
                 *local_clients_data = clients_data;
@@ -454,14 +595,33 @@ fn receive_message_system(
             Message::NewDisconnection { id } => {
                 local_clients_data.remove_by_client_id(id)
             }
+            Message::ServerStats { .. } => {}
+            Message::ChatMessage { sender_id: _, message } => { // only server sends them for now
+                notice.show(message, time.elapsed_seconds());
+            }
+            Message::Kick { reason } => {
+                notice.show(format!("DISCONNECTED: {}", reason), time.elapsed_seconds());
+            }
+            Message::MapChange { reason } => {
+                notice.show(format!("RECONNECTING: {}", reason), time.elapsed_seconds());
+                notice.reconnect_at = Some(time.elapsed_seconds() + 2.);
+            }
+            Message::Credentials { credentials: new_credentials } => {
+                credentials.set(&connect_properties.adress, new_credentials);
+            }
+            Message::Profile { profile } => {
+                profile::receive_profile(&mut profile_screen, profile, &connect_properties.adress);
+            }
             Message::Greeteng {  } => {
                 send_message(
                     &mut client, 
+                    &mut net_stats,
                     ClientChannel::Garanteed, 
                     Message::RegisterClient {
                         style: settings.style,
                         color: Color::from(settings.color),
-                        name: settings.name.clone() as String
+                        name: settings.name.clone() as String,
+                        credentials: credentials.get(&connect_properties.adress),
                     }
                 );
             },
@@ -477,7 +637,6 @@ fn receive_message_system(
 
 
 const STARFIELD_STARS : usize = 5000;
-const DUST : usize = 1000;
 
 fn distance_distribution(x: f32) -> f32{
     if x < 0.5{
@@ -759,7 +918,7 @@ fn starfield_update(
     
 
 
-    if curr_stars_count < DUST{ // todo: move to init and add varables to settings
+    if curr_stars_count < cfg.background.dust{ // todo: move to init and add varables to settings
         let layer = star_layer_q.get_single();
         let layer = if layer.is_ok(){
             layer.unwrap().1
@@ -772,7 +931,7 @@ fn starfield_update(
             )).id()
         };
         
-        let diff = DUST - curr_stars_count;
+        let diff = cfg.background.dust - curr_stars_count;
         let init_spawn = curr_stars_count == 0;
         for _ in 0..diff{
             let depth_range = (-3., 3.);
@@ -854,8 +1013,8 @@ struct ShipData{
 fn ship_labels( // todo: maybe add it as childs to ships? // add handle for every puppet
     cfg: Res<GlobalConfig>,
     mut commands: Commands,
-    ships_q: Query<(&Object, &mut Transform, &ShipState, &ShipStatuses, Entity), (With<Ship>, Without<ShipLabel>, Without<Puppet>, Without<HPBar>, Without<ShieldBar>)>,
-    ships_puppets_q: Query<(&Object, &mut Transform, &ShipState, &ShipStatuses, Entity), (With<Ship>, Without<ShipLabel>, With<Puppet>, Without<HPBar>, Without<ShieldBar>)>,
+    ships_q: Query<(&Object, &mut Transform, &ShipState, &ShipStatuses, Entity, Option<&ShipWeapon>), (With<Ship>, Without<ShipLabel>, Without<Puppet>, Without<HPBar>, Without<ShieldBar>)>,
+    ships_puppets_q: Query<(&Object, &mut Transform, &ShipState, &ShipStatuses, Entity, Option<&ShipWeapon>), (With<Ship>, Without<ShipLabel>, With<Puppet>, Without<HPBar>, Without<ShieldBar>)>,
     mut labels_q: Query<(&ShipLabel, &mut Transform, Entity, &Children), (With<ShipLabel>, Without<Ship>, Without<HPBar>, Without<ShieldBar>)>,  
     mut hpbar_q: Query<&mut Transform, (With<HPBar>, Without<ShipLabel>, Without<Ship>, Without<ShieldBar>)>,  
     mut shieldbar_q: Query<&mut Transform, (With<ShieldBar>, Without<ShipLabel>, Without<Ship>, Without<HPBar>)>,
@@ -921,7 +1080,7 @@ fn ship_labels( // todo: maybe add it as childs to ships? // add handle for ever
     let mut used_labels = HashSet::new();
     // iterating trough ships, collect data about name, hp and shields; and after iter trough puppets. because puppets doesnt update their hp and shields, but we need to update its labels
     let mut data_about_ships: HashMap<u64, ShipData> = HashMap::new();
-    for (object, transform, state, statuses, e) in ships_q.iter().chain(ships_puppets_q.iter()){ 
+    for (object, transform, state, statuses, e, weapon) in ships_q.iter().chain(ships_puppets_q.iter()){ 
         match state {ShipState::Dead { time: _ } => {continue;} _ => {}}; // labels only for alive!
 
         let id = e.to_bits();
@@ -937,6 +1096,17 @@ fn ship_labels( // todo: maybe add it as childs to ships? // add handle for ever
                         }
                         res.unwrap().clone()
                     } else {
+                        let mut statuses = statuses.clone();
+                        let weapon_powerup = weapon.and_then(|weapon| cfg.find_power_up(PowerUPEffectKind::Weapon(weapon.weapon_type)));
+                        if weapon_powerup.is_some() { // picked weapon shown in statusbar with ammo or heat left
+                            let weapon = weapon.unwrap();
+                            let is_beam = cfg.get_weapon_stats(weapon.weapon_type).beam_range.is_some();
+                            statuses.current.insert(weapon_powerup.unwrap(), PowerUPEffect{
+                                seconds: 0.,
+                                value: if is_beam {weapon.heat * 100.} else {weapon.ammo.map_or(f32::INFINITY, |ammo| ammo as f32)}, // heat in percents for beams
+                                kind: PowerUPEffectKind::Weapon(weapon.weapon_type),
+                            });
+                        }
                         data_about_ships.insert(object.id, 
                             ShipData{
                                 name: data.name.clone(),
@@ -948,7 +1118,7 @@ fn ship_labels( // todo: maybe add it as childs to ships? // add handle for ever
                             name: data.name.clone(),
                             shields,
                             hp,
-                            statuses: statuses.clone()
+                            statuses: statuses
                         }
                     };
                     if labels.contains_key(&id){
@@ -1001,7 +1171,7 @@ fn ship_labels( // todo: maybe add it as childs to ships? // add handle for ever
                                         // spawn
                                         let new_e = commands.spawn((
                                             SpriteBundle {
-                                                texture: asset_server.load(status_type.texture_path()),
+                                                texture: asset_server.load(status_type.texture_path(&cfg)),
                                                 transform: Transform::from_scale(Vec3::splat(scale)),
                                                 ..default()
                                             },
@@ -1013,7 +1183,7 @@ fn ship_labels( // todo: maybe add it as childs to ships? // add handle for ever
 
                                         commands.spawn(Text2dBundle{
                                             text: Text::from_section(
-                                            format!("{}", effect.get_val_to_show(status_type)),
+                                            format!("{}", effect.get_val_to_show()),
                                             TextStyle {
                                                 font: font.clone(),
                                                 font_size: 10.0,
@@ -1025,18 +1195,16 @@ fn ship_labels( // todo: maybe add it as childs to ships? // add handle for ever
                                     }
                                 }
 
-                                let max_len = 
-                                    (format!("{}", cfg.effects_extradamage_secs).len()).max(
-                                    (format!("{}", cfg.effects_haste_secs).len()).max(
-                                    (format!("{}", cfg.effects_invisibility_secs).len()).max(
-                                     format!("{}" , cfg.effects_supershield_amount).len()))) as f32;
+                                let max_len = cfg.powerups.iter()
+                                    .map(|def| format!("{}", PowerUPEffect{seconds: def.duration_secs, value: def.magnitude, kind: def.effect}.get_val_to_show()).len())
+                                    .max().unwrap_or(1) as f32;
                                 
                                 let margin = 2. * max_len + 2.; // 2 * len + 2
                                 let single_size = 12.;
                                 let half_size = (margin + single_size) / 2. * (iterated.len() as f32 - 1.);
                                 let mut i = 0;
                                 for (status_type, effect) in ship_data.statuses.current.iter(){ // update pos and value
-                                    let val = effect.get_val_to_show(status_type);
+                                    let val = effect.get_val_to_show();
                                     let icon_entity = status_bar.binds.get(status_type).unwrap();
                                     commands.entity(*icon_entity).insert(Transform::from_translation(Vec3::X * (i as f32 * (single_size + margin) - half_size + 0.5)).with_scale(Vec3::splat(scale)));
 
@@ -1140,4 +1308,168 @@ fn ship_labels( // todo: maybe add it as childs to ships? // add handle for ever
     for (id, (_, _, e, _)) in labels.iter(){
         if !used_labels.contains(id){commands.entity(*e).despawn_recursive()};
     }
-}
\ No newline at end of file
+}
+
+
+#[derive(Component)]
+struct SpawnImmunityShield;
+
+/// Shared by all shields, so spawning one doesn't add assets
+#[derive(Resource)]
+struct ImmunityShieldAssets{
+    mesh: Handle<Mesh>,
+    material: Handle<ColorMaterial>,
+}
+
+fn setup_immunity_shield_assets(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+){
+    let segments = 24;
+    let radius = 28.;
+    let mut mesh = Mesh::new(PrimitiveTopology::LineList);
+    let vertices = (0..segments).map(|i| (Vec2::from_angle(i as f32 / segments as f32 * PI * 2.) * radius).extend(1.)).collect::<Vec<Vec3>>();
+    let indices = (0..segments).flat_map(|i| [i, (i + 1) % segments]).collect::<Vec<u32>>();
+    mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, vec![(Color::CYAN * 2.).as_rgba_f32(); vertices.len()]);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, vertices);
+    mesh.set_indices(Some(Indices::U32(indices)));
+    commands.insert_resource(ImmunityShieldAssets{
+        mesh: meshes.add(mesh),
+        material: materials.add(ColorMaterial::default()),
+    });
+}
+
+fn spawn_immunity_shields( // flashing circle around ships with spawn protection
+    mut commands: Commands,
+    ships_q: Query<(&Object, &ShipStatuses), (With<Ship>, Without<Puppet>)>,
+    all_ships_q: Query<(Entity, &Object, Option<&Children>), With<Ship>>, // with puppets
+    mut shields_q: Query<&mut Visibility, With<SpawnImmunityShield>>,
+    shield_assets: Res<ImmunityShieldAssets>,
+    time: Res<Time>,
+){
+    // puppets doesnt receive statuses, so take them from real ships
+    let mut protected: HashMap<u64, f32> = HashMap::new(); // object id -> seconds left
+    for (object, statuses) in ships_q.iter(){
+        let effect = statuses.current.values().find(|effect| effect.kind == PowerUPEffectKind::SpawnImmunity);
+        if effect.is_some(){
+            protected.insert(object.id, effect.unwrap().seconds);
+        }
+    }
+    for (e, object, children) in all_ships_q.iter(){
+        let shield = children.and_then(|children| children.iter().find(|c| shields_q.contains(**c)).cloned());
+        let seconds_left = protected.get(&object.id);
+        if seconds_left.is_some(){
+            let frequency = if *seconds_left.unwrap() < 0.5 {24.} else {12.}; // flash faster before protection ends
+            let visibility = if (time.elapsed_seconds() * frequency).sin() > 0. {Visibility::Inherited} else {Visibility::Hidden};
+            if shield.is_some(){
+                *shields_q.get_mut(shield.unwrap()).unwrap() = visibility;
+            } else {
+                commands.spawn((
+                    MaterialMesh2dBundle {
+                        mesh: Mesh2dHandle(shield_assets.mesh.clone()),
+                        material: shield_assets.material.clone(),
+                        visibility: visibility,
+                        ..default()
+                    },
+                    SpawnImmunityShield,
+                    Name::new("SPAWN IMMUNITY SHIELD"),
+                )).set_parent(e);
+            }
+        } else if shield.is_some(){
+            commands.entity(shield.unwrap()).despawn_recursive();
+        }
+    }
+}
+
+
+#[derive(Component)]
+struct BeamSprite;
+
+fn render_beams( // beam is child of ship and its puppets, so it is drawn through map borders
+    mut commands: Commands,
+    ships_q: Query<(&Object, &ShipWeapon), (With<Ship>, Without<Puppet>)>,
+    all_ships_q: Query<(Entity, &Object, &Transform, Option<&Children>), (With<Ship>, Without<BeamSprite>)>, // with puppets
+    mut beams_q: Query<&mut Transform, (With<BeamSprite>, Without<Ship>)>,
+    asset_server: Res<AssetServer>,
+){
+    let texture_width = 8.; // beam.png
+    // puppets doesnt receive weapons, so take them from real ships
+    let mut beams: HashMap<u64, Vec2> = HashMap::new(); // object id -> beam vector
+    for (object, weapon) in ships_q.iter(){
+        if weapon.beam.is_some(){
+            let (start, end) = weapon.beam.unwrap();
+            beams.insert(object.id, end - start);
+        }
+    }
+    for (e, object, transform, children) in all_ships_q.iter(){
+        let beam_e = children.and_then(|children| children.iter().find(|c| beams_q.contains(**c)).cloned());
+        let beam = beams.get(&object.id);
+        if beam.is_some(){
+            let local = (transform.rotation.inverse() * beam.unwrap().extend(0.)).truncate();
+            let beam_transform = Transform::from_translation((local / 2.).extend(-1.))
+                .with_rotation(Quat::from_rotation_z(Vec2::X.angle_between(local)))
+                .with_scale(Vec3::new(local.length() / texture_width, 2., 1.));
+            if beam_e.is_some(){
+                *beams_q.get_mut(beam_e.unwrap()).unwrap() = beam_transform;
+            } else {
+                commands.spawn((
+                    SpriteBundle {
+                        texture: asset_server.load("beam.png"),
+                        transform: beam_transform,
+                        sprite: Sprite { color: WeaponType::Laser.projectile_color(), ..default() },
+                        ..default()
+                    },
+                    BeamSprite,
+                    Name::new("BEAM"),
+                )).set_parent(e);
+            }
+        } else if beam_e.is_some(){
+            commands.entity(beam_e.unwrap()).despawn_recursive();
+        }
+    }
+}
+
+#[derive(Component)]
+struct TracerSprite(u64); // object id of shooter
+
+/// Hitscan rays stay where they were fired, they don't follow the ship
+fn render_tracers(
+    mut commands: Commands,
+    ships_q: Query<(&Object, &ShipWeapon), (With<Ship>, Without<Puppet>)>,
+    mut tracers_q: Query<(Entity, &TracerSprite, &mut Transform, &mut Sprite)>,
+    asset_server: Res<AssetServer>,
+){
+    let texture_width = 8.; // beam.png
+    let ray_transform = |(start, end, _): (Vec2, Vec2, WeaponType)| {
+        let ray = end - start;
+        let angle = Vec2::X.angle_between(ray);
+        Transform::from_translation(((start + end) / 2.).extend(-1.))
+            .with_rotation(Quat::from_rotation_z(if angle.is_nan() {0.} else {angle}))
+            .with_scale(Vec3::new(ray.length() / texture_width, 1., 1.))
+    };
+    let mut tracers: HashMap<u64, (Vec2, Vec2, WeaponType)> = ships_q.iter()
+        .filter_map(|(object, weapon)| weapon.tracer.map(|tracer| (object.id, tracer)))
+        .collect();
+    for (e, tracer, mut transform, mut sprite) in tracers_q.iter_mut(){
+        match tracers.remove(&tracer.0) {
+            Some(ray) => {
+                *transform = ray_transform(ray);
+                sprite.color = ray.2.projectile_color();
+            }
+            None => {commands.entity(e).despawn_recursive();}
+        }
+    }
+    for (id, ray) in tracers{
+        commands.spawn((
+            SpriteBundle {
+                texture: asset_server.load("beam.png"),
+                transform: ray_transform(ray),
+                sprite: Sprite { color: ray.2.projectile_color(), ..default() },
+                ..default()
+            },
+            TracerSprite(id),
+            Name::new("TRACER"),
+        ));
+    }
+}
@@ -0,0 +1,214 @@
+use std::{fs::File, io::{BufWriter, Write}, time::SystemTime};
+use bevy::prelude::*;
+use bevy_egui::{egui::{self, Style, Visuals, epaint::Shadow, Color32, Rounding, Stroke}, EguiContexts};
+use bevy_renet::renet::RenetClient;
+
+use crate::shared::*;
+
+/*
+    NET GRAPH
+    F2 - show/hide overlay
+    F4 - start/stop writing one CSV row per second
+    --net-log path.csv - start writing from the beginning of the game
+*/
+
+/// Traffic counters for one second, per message type in MESSAGE_KINDS order
+#[derive(Clone, Default)]
+pub struct NetSecond{
+    pub rtt_ms: f32,
+    pub packet_loss: f32,
+    pub bytes_in: f32, // per second, from renet, includes headers and resends
+    pub bytes_out: f32,
+    pub snapshots: u32,
+    pub max_snapshots_per_frame: u32, // most snapshots read in one frame, only the newest is applied, there is no interpolation buffer
+    pub server_tick_ms: f32,
+    pub in_count: [u32; MESSAGE_KINDS.len()],
+    pub in_bytes: [u32; MESSAGE_KINDS.len()],
+    pub out_count: [u32; MESSAGE_KINDS.len()],
+    pub out_bytes: [u32; MESSAGE_KINDS.len()],
+}
+
+#[derive(Resource, Default)]
+pub struct NetStats{
+    pub show: bool,
+    pub current: NetSecond,
+    pub last: NetSecond, // shown and logged
+    second_start: f32,
+    csv: Option<BufWriter<File>>,
+    csv_path: Option<String>,
+}
+
+impl NetStats{
+    pub fn record_received(&mut self, message: &Message, size: usize){
+        let kind = message.kind();
+        self.current.in_count[kind] += 1;
+        self.current.in_bytes[kind] += size as u32;
+        match message {
+            Message::Update{..} => {self.current.snapshots += 1}
+            Message::ServerStats{avg_tick_ms, ..} => {self.current.server_tick_ms = *avg_tick_ms}
+            _ => {}
+        }
+    }
+
+    pub fn record_sent(&mut self, message: &Message, size: usize){
+        let kind = message.kind();
+        self.current.out_count[kind] += 1;
+        self.current.out_bytes[kind] += size as u32;
+    }
+
+    pub fn record_snapshots_per_frame(&mut self, snapshots: u32){
+        self.current.max_snapshots_per_frame = self.current.max_snapshots_per_frame.max(snapshots);
+    }
+
+    fn start_csv(&mut self, path: &str) -> std::io::Result<()>{
+        let mut file = BufWriter::new(File::create(path)?);
+        let mut header = "time,rtt_ms,packet_loss,bytes_in,bytes_out,snapshots,max_snapshots_per_frame_no_buffer,server_tick_ms".to_string();
+        for kind in MESSAGE_KINDS.iter(){
+            header += &format!(",{kind}_in_count,{kind}_in_bytes,{kind}_out_count,{kind}_out_bytes");
+        }
+        writeln!(file, "{}", header)?;
+        self.csv = Some(file);
+        Ok(())
+    }
+
+    fn write_csv_row(&mut self, time: f32) -> std::io::Result<()>{
+        let Some(file) = self.csv.as_mut() else {return Ok(());};
+        let s = &self.last;
+        let mut row = format!("{:.1},{:.1},{:.4},{:.0},{:.0},{},{},{:.2}", time, s.rtt_ms, s.packet_loss, s.bytes_in, s.bytes_out, s.snapshots, s.max_snapshots_per_frame, s.server_tick_ms);
+        for i in 0..MESSAGE_KINDS.len(){
+            row += &format!(",{},{},{},{}", s.in_count[i], s.in_bytes[i], s.out_count[i], s.out_bytes[i]);
+        }
+        writeln!(file, "{}", row)?;
+        file.flush()
+    }
+}
+
+pub fn init_net_stats(app: &mut App){
+    app.insert_resource(NetStats{csv_path: parse_arg("--net-log"), ..default()});
+    app.add_systems(OnEnter(ClientState::InGame), start_net_log);
+    app.add_systems(Update, (net_stats_input, update_net_stats, net_stats_overlay).chain().run_if(in_state(ClientState::InGame)));
+    app.add_systems(OnExit(ClientState::InGame), stop_net_log);
+}
+
+fn start_net_log(
+    mut stats: ResMut<NetStats>,
+    time: Res<Time>,
+){
+    stats.current = NetSecond::default();
+    stats.last = NetSecond::default();
+    stats.second_start = time.elapsed_seconds();
+    if let Some(path) = stats.csv_path.clone(){
+        if let Err(e) = stats.start_csv(&path){
+            warn!("Can't write net log {}: {}", path, e);
+        }
+    }
+}
+
+fn stop_net_log(
+    mut stats: ResMut<NetStats>,
+){
+    stats.csv = None; // BufWriter flushes on drop
+}
+
+fn net_stats_input(
+    keys: Res<Input<KeyCode>>,
+    mut stats: ResMut<NetStats>,
+){
+    if keys.just_pressed(KeyCode::F2){
+        stats.show = !stats.show;
+    }
+    if keys.just_pressed(KeyCode::F4){
+        if stats.csv.is_some(){
+            stats.csv = None;
+            info!("Net log stopped");
+        } else {
+            let secs = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).unwrap().as_secs();
+            let path = stats.csv_path.clone().unwrap_or(format!("net_stats_{}.csv", secs));
+            match stats.start_csv(&path) {
+                Ok(()) => info!("Net log started: {}", path),
+                Err(e) => warn!("Can't write net log {}: {}", path, e),
+            }
+        }
+    }
+}
+
+fn update_net_stats(
+    client: Res<RenetClient>,
+    mut stats: ResMut<NetStats>,
+    time: Res<Time>,
+){
+    if time.elapsed_seconds() - stats.second_start < 1. {return;}
+    let info = client.network_info();
+    let mut second = std::mem::take(&mut stats.current);
+    second.rtt_ms = info.rtt as f32 * 1000.; // renet gives seconds
+    second.packet_loss = info.packet_loss as f32;
+    second.bytes_in = info.bytes_received_per_second as f32;
+    second.bytes_out = info.bytes_sent_per_second as f32;
+    if second.server_tick_ms == 0. {second.server_tick_ms = stats.last.server_tick_ms} // sent once per second, may come late
+    stats.last = second;
+    stats.second_start = time.elapsed_seconds();
+    if let Err(e) = stats.write_csv_row(time.elapsed_seconds()){
+        warn!("Can't write net log: {}", e);
+        stats.csv = None;
+    }
+}
+
+fn net_stats_overlay(
+    mut egui_context: EguiContexts,
+    stats: Res<NetStats>,
+){
+    if !stats.show {return;}
+    let ctx: &mut egui::Context = egui_context.ctx_mut();
+    let style = Style{
+        visuals: Visuals{
+            window_rounding: Rounding::ZERO,
+            window_shadow: Shadow::NONE,
+            window_fill: Color32::from_rgba_unmultiplied(0, 0, 0, 200),
+            window_stroke: Stroke{
+                width: 1.,
+                color: Color32::from_rgba_unmultiplied(255, 255, 255, 255)
+            },
+            override_text_color: Some(Color32::WHITE),
+            ..default()
+        },
+        animation_time: 0.,
+        ..default()
+    };
+    let s = &stats.last;
+    egui::Window::new("NET")
+        .anchor(egui::Align2::RIGHT_TOP, [-10., 10.])
+        .resizable(false)
+        .collapsible(false)
+        .title_bar(false)
+        .show(ctx, |ui|{
+            ui.set_style(style.clone());
+            ui.monospace(format!("rtt       {:>8.1} ms", s.rtt_ms));
+            ui.monospace(format!("loss      {:>8.1} %", s.packet_loss * 100.));
+            ui.monospace(format!("in        {:>8.1} KB/s", s.bytes_in / 1024.));
+            ui.monospace(format!("out       {:>8.1} KB/s", s.bytes_out / 1024.));
+            ui.monospace(format!("snapshots {:>8} /s", s.snapshots));
+            ui.monospace(format!("no buffer {:>8} max/frame", s.max_snapshots_per_frame)); // not interpolation depth, client has no buffer
+            ui.monospace(format!("srv tick  {:>8.2} ms", s.server_tick_ms));
+            if stats.csv.is_some(){
+                ui.colored_label(Color32::RED, "logging to csv");
+            }
+            ui.separator();
+            egui::Grid::new("net_messages").striped(false).show(ui, |ui|{
+                ui.monospace("message");
+                ui.monospace("in/s");
+                ui.monospace("in B/s");
+                ui.monospace("out/s");
+                ui.monospace("out B/s");
+                ui.end_row();
+                for (i, kind) in MESSAGE_KINDS.iter().enumerate(){
+                    if s.in_count[i] == 0 && s.out_count[i] == 0 {continue;}
+                    ui.monospace(*kind);
+                    ui.monospace(format!("{}", s.in_count[i]));
+                    ui.monospace(format!("{}", s.in_bytes[i]));
+                    ui.monospace(format!("{}", s.out_count[i]));
+                    ui.monospace(format!("{}", s.out_bytes[i]));
+                    ui.end_row();
+                }
+            });
+        });
+}
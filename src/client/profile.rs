@@ -0,0 +1,140 @@
+use bevy::{prelude::*, utils::HashMap};
+use bevy_egui::{egui::{self, Style, Visuals, epaint::Shadow, Color32, Rounding, Stroke}, EguiContexts};
+use bevy_renet::renet::RenetClient;
+
+use crate::shared::*;
+use super::send_message;
+use super::net_stats::NetStats;
+
+/*
+    PLAYER PROFILE
+    every server gives its own identity and secret on first connect, they are kept in identity.txt
+    one line per server: address identity secret
+    server sends profile after registering, client asks again every REQUEST_SECS
+    PROFILE in main menu shows the last one received
+*/
+
+const IDENTITY_PATH: &str = "identity.txt";
+const REQUEST_SECS: f32 = 30.;
+
+/// Credentials sent with RegisterClient, by server address
+#[derive(Resource, Default)]
+pub struct ClientCredentials{
+    path: String,
+    by_server: HashMap<String, Credentials>,
+}
+
+impl ClientCredentials{
+    /// Broken lines are skipped, server gives new identity then
+    pub fn load(path: &str) -> Self{
+        let mut by_server = HashMap::new();
+        for line in std::fs::read_to_string(path).unwrap_or_default().lines(){
+            let parts: Vec<&str> = line.split_whitespace().collect();
+            let [address, identity, secret] = parts[..] else {continue;};
+            let (Ok(identity), Ok(secret)) = (identity.parse::<u64>(), secret.parse::<u64>()) else {continue;};
+            by_server.insert(address.to_string(), Credentials{identity, secret});
+        }
+        ClientCredentials{path: path.into(), by_server}
+    }
+
+    pub fn get(&self, server: &str) -> Option<Credentials>{
+        self.by_server.get(server).copied()
+    }
+
+    pub fn set(&mut self, server: &str, credentials: Credentials){
+        self.by_server.insert(server.into(), credentials);
+        let data: String = self.by_server.iter().map(|(address, c)| format!("{} {} {}\n", address, c.identity, c.secret)).collect();
+        if let Err(e) = std::fs::write(&self.path, data){
+            warn!("Can't save identity {}: {}", self.path, e); // works until restart
+        }
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct ProfileScreen{
+    pub show: bool,
+    pub profile: Option<PlayerProfile>,
+    pub server: String, // where profile came from
+    last_request: f32,
+}
+
+pub fn init_profile(app: &mut App){
+    app.insert_resource(ClientCredentials::load(IDENTITY_PATH));
+    app.init_resource::<ProfileScreen>();
+    app.add_systems(Update, request_profile.run_if(in_state(ClientState::InGame)));
+    app.add_systems(Update, profile_window.run_if(in_state(ClientState::Menu)));
+}
+
+pub fn receive_profile(screen: &mut ProfileScreen, profile: PlayerProfile, server: &str){
+    screen.profile = Some(profile);
+    screen.server = server.into();
+}
+
+fn request_profile(
+    mut client: ResMut<RenetClient>,
+    mut net_stats: ResMut<NetStats>,
+    mut screen: ResMut<ProfileScreen>,
+    time: Res<Time>,
+){
+    if !client.is_connected() || time.elapsed_seconds() - screen.last_request < REQUEST_SECS {return;}
+    screen.last_request = time.elapsed_seconds();
+    send_message(&mut client, &mut net_stats, ClientChannel::Garanteed, Message::ProfileRequest{});
+}
+
+fn profile_window(
+    mut egui_context: EguiContexts,
+    mut screen: ResMut<ProfileScreen>,
+){
+    if !screen.show {return;}
+    let ctx: &mut egui::Context = egui_context.ctx_mut();
+    let style = Style{
+        visuals: Visuals{
+            window_rounding: Rounding::ZERO,
+            window_shadow: Shadow::NONE,
+            window_fill: Color32::from_rgba_unmultiplied(0, 0, 0, 230),
+            window_stroke: Stroke{
+                width: 1.,
+                color: Color32::from_rgba_unmultiplied(255, 255, 255, 255)
+            },
+            override_text_color: Some(Color32::WHITE),
+            ..default()
+        },
+        animation_time: 0.,
+        ..default()
+    };
+    let screen = &mut *screen;
+    egui::Window::new("☺PROFILE☺")
+        .open(&mut screen.show)
+        .collapsible(false)
+        .resizable(false)
+        .default_pos(ctx.screen_rect().center())
+        .show(ctx, |ui|{
+            ui.set_style(style.clone());
+            let Some(profile) = screen.profile.as_ref() else {
+                ui.label("No profile yet, play on a server to get one");
+                return;
+            };
+            ui.label(format!("from {}", screen.server));
+            let hours = profile.playtime_secs / 3600.;
+            let favorite = match profile.favorite_style() {
+                Some(style) => format!("{}", style),
+                None => "-".into(),
+            };
+            egui::Grid::new("profile").show(ui, |ui|{
+                for (name, value) in [
+                    ("name", profile.name.clone()),
+                    ("kills", format!("{}", profile.kills)),
+                    ("deaths", format!("{}", profile.deaths)),
+                    ("k/d", format!("{:.2}", profile.kills as f32 / profile.deaths.max(1) as f32)),
+                    ("shots", format!("{}", profile.shots)),
+                    ("accuracy", format!("{:.1} %", profile.accuracy() * 100.)),
+                    ("playtime", format!("{:.1} h", hours)),
+                    ("favorite ship", favorite),
+                ]{
+                    ui.label(name);
+                    ui.label(value);
+                    ui.end_row();
+                }
+            });
+        });
+}
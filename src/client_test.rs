@@ -13,7 +13,7 @@ use rand::Rng;
 
 
 
-#[path = "game.rs"] mod game;
+use rusteroids::shared as game;
 use game::*;
 //use game::components::*;
 
@@ -82,7 +82,7 @@ fn setup(
 
     commands.entity(e).insert(CameraFollow);
     let mut seed = rand::random();
-    while crate::game::get_asteroid_size(seed) != 3 {
+    while cfg.get_asteroid_size(seed) != 3 {
         seed = rand::random();
     }
     //game::spawn_asteroid(seed, Velocity::zero(), Transform::from_translation(Vec3::splat(3.)), &mut meshes, &mut materials, &mut commands, cfg.new_id(), cfg.get_asteroid_hp(seed));
@@ -343,7 +343,7 @@ fn update(
     }
 
     if inp.shoot{
-        spawn_bullet(transform.up().truncate() * 1000. + velocity.linvel, false, *transform, cfg.new_id(), cfg.new_id(), 3000., &asset_server, &mut commands);
+        spawn_bullet(transform.up().truncate() * 1000. + velocity.linvel, 0., WeaponType::Blaster, *transform, cfg.new_id(), cfg.new_id(), 3000., &asset_server, &mut commands);
     }
     
     let max_linvel = 700.;
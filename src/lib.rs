@@ -0,0 +1,18 @@
+/*
+    RUSTEROIDS
+    shared    components, protocol, config and world math used by everyone
+    server    authoritative simulation, ServerPlugin
+    client    menu, rendering and prediction, ClientPlugin
+    bots      bot inputs, BotPlugin
+    binaries in src/bin only add plugins and read arguments
+
+    features: client, server, dev-tools, dynamic (see Cargo.toml)
+    shared and bots are always built, server window needs client feature
+*/
+
+pub mod shared;
+#[cfg(feature = "server")]
+pub mod server;
+#[cfg(feature = "client")]
+pub mod client;
+pub mod bots;
@@ -1,11 +0,0 @@
-
-
-fn main() {
-
-}
-
-
-
-
-
-// C:\Users\Morphin\.cargo\registry\src\index.crates.io-6f17d22bba15001f\bevy_asset-0.11.2\src\debug_asset_server.rs СМЕРТЬ
\ No newline at end of file
@@ -1,14 +1,15 @@
+use std::{io::BufRead, sync::{atomic::{AtomicU32, Ordering}, mpsc::{self, Receiver}, Arc, Mutex}};
 use bevy::{input::{keyboard::KeyCode, Input}, ecs::system::{Local, Res, Resource}, prelude::*, utils::hashbrown::HashMap};
+#[cfg(feature = "client")]
 use bevy_egui::{egui::{epaint::Shadow, self}, EguiContexts};
 use bevy_rapier2d::rapier::crossbeam::epoch::Pointable;
 use bevy_renet::{renet::{*, transport::*}, RenetServerPlugin, transport::NetcodeServerPlugin};
-use renet_visualizer::RenetServerVisualizer;
-use rand::random;
+use rand::Rng;
 
-use crate::{get_pos_to_spawn, spawn_ship, ClientData, ClientsData, GlobalConfig, Message, ObjectsDistribution, ServerChannel};
+use crate::shared::{get_ship_spawn_pos, load_map, spawn_ship, ClientData, ClientsData, GameRng, GlobalConfig, Message, SafeZone, SpatialIndex, ServerChannel};
+use crate::bots::*;
+use super::{AdminCommand, PendingServerAction, ServerAction, ServerState};
 
-#[path = "bot_ai.rs"] pub mod bot_ai;
-pub use bot_ai::*;
 
 
 
@@ -35,7 +36,7 @@ pub fn command_executer(
     mut server: ResMut<RenetServer>,
     mut reader: EventReader<CommandEvent>,
     mut clients_data: ResMut<ClientsData>,
-    mut objects_distribution: ResMut<ObjectsDistribution>,
+    index: Res<SpatialIndex>,
     mut chat_history: ResMut<ChatHistory>,
     mut cfg: ResMut<GlobalConfig>,
     mut meshes: ResMut<Assets<Mesh>>,
@@ -43,6 +44,10 @@ pub fn command_executer(
     time: Res<Time>,
     mut commands: Commands,
     mut botlist: ResMut<BotList>,
+    mut rng: ResMut<GameRng>,
+    safe_zone: Res<SafeZone>,
+    mut admin_commands: EventWriter<AdminCommand>,
+    mut pending_action: ResMut<PendingServerAction>,
 ){
     
     let mut log = |text: String|{
@@ -77,12 +82,12 @@ pub fn command_executer(
                     match thing {
                         "spawn" => {
                             let object_id = cfg.new_id();
-                            let style = rand::random::<u8>();
-                            let color = Color::Hsla { hue: rand::random::<f32>(), saturation: rand::random::<f32>(), lightness: rand::random::<f32>(), alpha: 1. } * 2.;
+                            let style = rng.0.gen::<u8>();
+                            let color = Color::Hsla { hue: rng.0.gen::<f32>(), saturation: rng.0.gen::<f32>(), lightness: rng.0.gen::<f32>(), alpha: 1. } * 2.;
                             let name = "BEBROBOT";
-                            let id = rand::random::<u64>();
+                            let id = rng.0.gen::<u64>();
                             let for_spawn_cl_data = ClientData::for_spawn(style, color, object_id);
-                            let pos = get_pos_to_spawn(&mut objects_distribution, &mut cfg).extend(0.);
+                            let pos = get_ship_spawn_pos(&index, &mut cfg, &safe_zone, &mut rng.0).extend(0.);
                             let entity = spawn_ship(false, &mut meshes, &mut materials, &mut commands, &for_spawn_cl_data, &mut cfg, &time);
                             commands.entity(entity).insert(Transform::from_translation(pos));
                             let new_client_data = ClientData { 
@@ -91,7 +96,9 @@ pub fn command_executer(
                                 entity: entity,
                                 style: style,
                                 color: color, 
-                                name: name.to_string() 
+                                name: name.to_string(),
+                                kills: 0,
+                                deaths: 0,
                             };
                             clients_data.add(new_client_data.clone());
                             println!("register new BOT with id {}", id);
@@ -137,8 +144,55 @@ pub fn command_executer(
                 "kill" => {
                     log("< kill command executed!".into())
                 }
-                "kick" => {
-                    log("< kick command executed!".into())
+                "kick" | "ban" => { // /kick <client id>
+                    let Some(client_id) = splitted.get(1).and_then(|id| id.parse::<u64>().ok()) else {
+                        log(format!("< Using: /{} [client id]", head_command));
+                        continue;
+                    };
+                    if clients_data.get_option_by_client_id(&client_id).is_none(){
+                        log(format!("< There is no client {}", client_id));
+                        continue;
+                    }
+                    if head_command == "kick"{
+                        admin_commands.send(AdminCommand::Kick{client_id});
+                    } else {
+                        admin_commands.send(AdminCommand::Ban{client_id});
+                    }
+                    log(format!("< {} {}", head_command, client_id));
+                }
+                "shutdown" | "restart" => { // /shutdown [delay secs] [reason]
+                    let delay = splitted.get(1).and_then(|secs| secs.parse::<f32>().ok()).unwrap_or(10.);
+                    let reason_start = if splitted.get(1).and_then(|secs| secs.parse::<f32>().ok()).is_some() {2} else {1};
+                    let reason = if splitted.len() > reason_start {splitted[reason_start..].join(" ")} else {format!("server {}", head_command)};
+                    let action = if head_command == "shutdown" {ServerAction::Shutdown} else {ServerAction::Restart};
+                    pending_action.schedule(action, delay, reason, time.elapsed_seconds());
+                    log(format!("< {} in {}s, /cancel to abort", head_command, delay));
+                }
+                "changemap" => { // /changemap <map file> [delay secs]
+                    let Some(path) = splitted.get(1) else {
+                        log("< Using: /changemap [map file] [delay secs]".into());
+                        continue;
+                    };
+                    let map = match load_map(path) {
+                        Ok(map) => map,
+                        Err(e) => {
+                            log(format!("< Can't load map {}: {}", path, e));
+                            continue;
+                        }
+                    };
+                    let delay = splitted.get(2).and_then(|secs| secs.parse::<f32>().ok()).unwrap_or(10.);
+                    let reason = format!("next map is {}", map.name);
+                    pending_action.schedule(ServerAction::ChangeMap(map), delay, reason, time.elapsed_seconds());
+                    log(format!("< map change in {}s, /cancel to abort", delay));
+                }
+                "cancel" => {
+                    if pending_action.0.take().is_some(){
+                        log("< cancelled".into());
+                        let message = "SERVER: cancelled".to_string();
+                        server.broadcast_message(ServerChannel::Garanteed, bincode::serialize(&Message::ChatMessage{sender_id: 0, message}).unwrap());
+                    } else {
+                        log("< Nothing to cancel".into());
+                    }
                 }
                 "say" => {
                     log("< say command executed!".into())
@@ -161,9 +215,95 @@ pub struct ChatHistory{
     data: Vec<String>
 }
 
+/*
+    TERMINAL
+    headless server reads console commands from stdin, one per line: /restart 30, /kick 1234
+    Ctrl-C or SIGTERM is /shutdown with short countdown, second one stops right away
+*/
+
+const SIGNAL_SHUTDOWN_SECS: f32 = 3.;
+
+/// Lines from stdin, thread sends them here
+#[derive(Resource)]
+struct StdinCommands(Mutex<Receiver<String>>);
+
+/// How many SIGINT/SIGTERM came
+#[derive(Resource)]
+struct ShutdownSignals(Arc<AtomicU32>);
+
+pub fn init_stdin_commands(app: &mut App){
+    let (sender, receiver) = mpsc::channel();
+    std::thread::spawn(move ||{
+        for line in std::io::stdin().lock().lines(){
+            let Ok(line) = line else {break;};
+            if sender.send(line).is_err() {break;}
+        }
+    });
+    app.insert_resource(StdinCommands(Mutex::new(receiver)));
+    app.add_systems(Update, (read_stdin_commands, print_console_log).chain().before(command_executer));
+}
+
+/// Only once per process, tests make many apps and don't call it
+pub fn init_shutdown_signals(app: &mut App){
+    let signals = Arc::new(AtomicU32::new(0));
+    let handler_signals = signals.clone();
+    let result = ctrlc::set_handler(move ||{
+        if handler_signals.fetch_add(1, Ordering::SeqCst) > 0 {
+            std::process::exit(130); // asked twice, don't wait for countdown
+        }
+    });
+    if let Err(e) = result {
+        warn!("Can't handle shutdown signals: {}", e);
+        return;
+    }
+    app.insert_resource(ShutdownSignals(signals));
+    app.add_systems(Update, shutdown_on_signal);
+}
+
+fn read_stdin_commands(
+    stdin: Res<StdinCommands>,
+    mut writer: EventWriter<CommandEvent>,
+){
+    let receiver = stdin.0.lock().unwrap();
+    while let Ok(command) = receiver.try_recv(){
+        writer.send(CommandEvent{command});
+    }
+}
+
+/// Console answers go to chat history, terminal gets them too
+fn print_console_log(
+    chat_history: Res<ChatHistory>,
+    mut printed: Local<usize>,
+){
+    let new = chat_history.data.len().saturating_sub(*printed);
+    for line in chat_history.data[..new].iter().rev(){ // newest first
+        println!("{}", line);
+    }
+    *printed = chat_history.data.len();
+}
+
+fn shutdown_on_signal(
+    signals: Res<ShutdownSignals>,
+    mut pending_action: ResMut<PendingServerAction>,
+    state: Res<State<ServerState>>,
+    mut exit: EventWriter<bevy::app::AppExit>,
+    mut scheduled: Local<bool>,
+    time: Res<Time>,
+){
+    if *scheduled || signals.0.load(Ordering::SeqCst) == 0 {return;}
+    *scheduled = true;
+    if *state.get() != ServerState::Running { // no match, nothing to save
+        exit.send(bevy::app::AppExit);
+        return;
+    }
+    info!("Shutdown signal, stopping in {}s", SIGNAL_SHUTDOWN_SECS);
+    pending_action.schedule(ServerAction::Shutdown, SIGNAL_SHUTDOWN_SECS, "server shutdown".into(), time.elapsed_seconds());
+}
+
 
 
 
+#[cfg(feature = "client")]
 pub fn chat_renderer(
     keys: Res<Input<KeyCode>>,
     mut egui_context: EguiContexts,
@@ -178,7 +318,7 @@ pub fn chat_renderer(
     let ctx: &mut egui::Context = egui_context.ctx_mut();
     let mut fonts = egui::FontDefinitions::default();
         fonts.font_data.insert("Font".to_owned(),
-        egui::FontData::from_static(include_bytes!("../assets/fonts/VecTerminus12Medium.otf") )
+        egui::FontData::from_static(include_bytes!("../../assets/fonts/VecTerminus12Medium.otf") )
     );
     fonts.families.insert(egui::FontFamily::Name("Font".into()), vec!["Font".to_owned()]);
     fonts.families.get_mut(&egui::FontFamily::Proportional).unwrap()
@@ -256,6 +396,7 @@ pub fn chat_renderer(
 
 
 
+#[cfg(feature = "client")]
 pub fn console_renderer(
     keys: Res<Input<KeyCode>>,
     mut egui_context: EguiContexts,
@@ -270,7 +411,7 @@ pub fn console_renderer(
     let ctx: &mut egui::Context = egui_context.ctx_mut();
     let mut fonts = egui::FontDefinitions::default();
         fonts.font_data.insert("Font".to_owned(),
-        egui::FontData::from_static(include_bytes!("../assets/fonts/VecTerminus12Medium.otf") )
+        egui::FontData::from_static(include_bytes!("../../assets/fonts/VecTerminus12Medium.otf") )
     );
     fonts.families.insert(egui::FontFamily::Name("Font".into()), vec!["Font".to_owned()]);
     fonts.families.get_mut(&egui::FontFamily::Proportional).unwrap()
@@ -301,7 +442,7 @@ pub fn console_renderer(
     let ctx: &mut egui::Context = egui_context.ctx_mut();
     let mut fonts = egui::FontDefinitions::default();
         fonts.font_data.insert("Font".to_owned(),
-        egui::FontData::from_static(include_bytes!("../assets/fonts/VecTerminus12Medium.otf") )
+        egui::FontData::from_static(include_bytes!("../../assets/fonts/VecTerminus12Medium.otf") )
     );
     fonts.families.insert(egui::FontFamily::Name("Font".into()), vec!["Font".to_owned()]);
     fonts.families.get_mut(&egui::FontFamily::Proportional).unwrap()
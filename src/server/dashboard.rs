@@ -0,0 +1,229 @@
+use bevy::{prelude::*, window::PrimaryWindow};
+use bevy_egui::{egui::{self, Style, Visuals, epaint::Shadow, Color32, Rounding, Stroke}, EguiContexts};
+use bevy_renet::renet::{ClientId, RenetServer};
+#[cfg(feature = "dev-tools")]
+use renet_visualizer::RenetServerVisualizer;
+
+use crate::shared::*;
+use crate::bots::BotList;
+use super::{overview_transform, AdminCommand, BanList, ServerState, SystemTimings};
+
+/*
+    ADMIN DASHBOARD
+    F2 - show/hide
+    click on name - server camera follows that ship, OVERVIEW returns to whole map
+*/
+
+/// Client id of the ship server camera follows
+#[derive(Resource, Default)]
+pub struct FollowedClient(pub Option<u64>);
+
+#[derive(Resource)]
+struct Dashboard{
+    show: bool,
+    show_graphs: bool,
+}
+
+pub fn init_dashboard(app: &mut App){
+    app.init_resource::<FollowedClient>();
+    app.insert_resource(Dashboard{show: true, show_graphs: false});
+    app.add_systems(Update, (dashboard_ui, follow_camera).chain().run_if(in_state(ServerState::Running)));
+}
+
+fn dashboard_ui(
+    keys: Res<Input<KeyCode>>,
+    mut egui_context: EguiContexts,
+    mut dashboard: ResMut<Dashboard>,
+    mut followed: ResMut<FollowedClient>,
+    mut admin_commands: EventWriter<AdminCommand>,
+    server: Res<RenetServer>,
+    clients_data: Res<ClientsData>,
+    botlist: Res<BotList>,
+    ban_list: Res<BanList>,
+    timings: Res<SystemTimings>,
+    tick: Res<ServerTick>,
+    objects_q: Query<&Object, Without<Puppet>>,
+    #[cfg(feature = "dev-tools")]
+    mut visualizer: ResMut<RenetServerVisualizer<200>>,
+){
+    if keys.just_pressed(KeyCode::F2){
+        dashboard.show = !dashboard.show;
+    }
+    #[cfg(feature = "dev-tools")]
+    visualizer.update(&server);
+    if !dashboard.show {return;}
+
+    let ctx: &mut egui::Context = egui_context.ctx_mut();
+    let style = Style{
+        visuals: Visuals{
+            window_rounding: Rounding::ZERO,
+            window_shadow: Shadow::NONE,
+            window_fill: Color32::from_rgba_unmultiplied(0, 0, 0, 230),
+            window_stroke: Stroke{
+                width: 1.,
+                color: Color32::from_rgba_unmultiplied(255, 255, 255, 255)
+            },
+            override_text_color: Some(Color32::WHITE),
+            ..default()
+        },
+        animation_time: 0.,
+        ..default()
+    };
+
+    let bots = botlist.get_bots_client_ids();
+    let mut players: Vec<&ClientData> = clients_data.iter().filter(|data| !bots.contains(&data.client_id)).collect();
+    let mut bot_players: Vec<&ClientData> = clients_data.iter().filter(|data| bots.contains(&data.client_id)).collect();
+    players.sort_by_key(|data| data.client_id);
+    bot_players.sort_by_key(|data| data.client_id);
+
+    let (mut asteroids, mut bullets, mut ships, mut pickups) = (0, 0, 0, 0);
+    for object in objects_q.iter(){
+        match object.object_type {
+            ObjectType::Asteroid{..} => asteroids += 1,
+            ObjectType::Bullet{..} => bullets += 1,
+            ObjectType::Ship{..} => ships += 1,
+            ObjectType::PickUP{..} => pickups += 1,
+        }
+    }
+
+    let dashboard = &mut *dashboard;
+    egui::Window::new("DASHBOARD")
+        .anchor(egui::Align2::RIGHT_TOP, [-10., 10.])
+        .resizable(false)
+        .collapsible(true)
+        .vscroll(true)
+        .show(ctx, |ui|{
+            ui.set_style(style.clone());
+            ui.horizontal(|ui|{
+                ui.label(format!("tick {}", tick.0));
+                if ui.button("OVERVIEW").clicked(){
+                    followed.0 = None;
+                }
+            });
+
+            // PLAYERS
+            ui.separator();
+            ui.label(format!("PLAYERS {}", players.len()));
+            egui::Grid::new("players").show(ui, |ui|{
+                for title in ["name", "id", "ping", "loss", "in B/s", "out B/s", "k/d", "", ""]{
+                    ui.label(title);
+                }
+                ui.end_row();
+                for data in players.iter(){
+                    let name = if followed.0 == Some(data.client_id) {format!("> {}", data.name)} else {data.name.clone()};
+                    if ui.selectable_label(followed.0 == Some(data.client_id), name).clicked(){
+                        followed.0 = Some(data.client_id);
+                    }
+                    ui.label(format!("{}", data.client_id));
+                    match server.network_info(ClientId::from_raw(data.client_id)) {
+                        Ok(info) => {
+                            ui.label(format!("{:.0} ms", info.rtt * 1000.)); // renet gives seconds
+                            ui.label(format!("{:.1} %", info.packet_loss * 100.));
+                            ui.label(format!("{:.0}", info.bytes_received_per_second));
+                            ui.label(format!("{:.0}", info.bytes_sent_per_second));
+                        }
+                        Err(_) => {
+                            for _ in 0..4 {ui.label("-");}
+                        }
+                    }
+                    ui.label(format!("{}/{}", data.kills, data.deaths));
+                    if ui.button("KICK").clicked(){
+                        admin_commands.send(AdminCommand::Kick{client_id: data.client_id});
+                    }
+                    if ui.button("BAN").clicked(){
+                        admin_commands.send(AdminCommand::Ban{client_id: data.client_id});
+                    }
+                    ui.end_row();
+                }
+            });
+            if !ban_list.names.is_empty(){
+                ui.label(format!("banned: {}", ban_list.names.join(", ")));
+            }
+
+            // BOTS
+            ui.separator();
+            ui.label(format!("BOTS {}", bot_players.len()));
+            egui::Grid::new("bots").show(ui, |ui|{
+                for data in bot_players.iter(){
+                    if ui.selectable_label(followed.0 == Some(data.client_id), data.name.clone()).clicked(){
+                        followed.0 = Some(data.client_id);
+                    }
+                    ui.label(format!("{}", data.client_id));
+                    ui.label(format!("{}/{}", data.kills, data.deaths));
+                    if ui.button("REMOVE").clicked(){
+                        admin_commands.send(AdminCommand::Kick{client_id: data.client_id});
+                    }
+                    ui.end_row();
+                }
+            });
+
+            // ENTITIES
+            ui.separator();
+            ui.label("ENTITIES");
+            egui::Grid::new("entities").show(ui, |ui|{
+                for (name, count) in [("asteroids", asteroids), ("bullets", bullets), ("ships", ships), ("pickups", pickups)]{
+                    ui.label(name);
+                    ui.label(format!("{}", count));
+                    ui.end_row();
+                }
+            });
+
+            // TIMINGS
+            ui.separator();
+            ui.label("TICK TIME");
+            egui::Grid::new("timings").show(ui, |ui|{
+                for (label, ms) in timings.average_ms.iter(){
+                    ui.label(*label);
+                    ui.label(format!("{:.3} ms", ms));
+                    ui.end_row();
+                }
+                ui.label("total");
+                ui.label(format!("{:.3} ms", timings.average_ms.iter().map(|(_, ms)| ms).sum::<f32>()));
+                ui.end_row();
+            });
+
+            #[cfg(feature = "dev-tools")]
+            {
+                ui.separator();
+                ui.checkbox(&mut dashboard.show_graphs, "renet graphs");
+            }
+        });
+
+    #[cfg(feature = "dev-tools")]
+    if dashboard.show_graphs {
+        visualizer.show_window(ctx);
+    }
+}
+
+fn follow_camera(
+    mut followed: ResMut<FollowedClient>,
+    clients_data: Res<ClientsData>,
+    cfg: Res<GlobalConfig>,
+    ships_q: Query<&Transform, (With<Ship>, Without<Camera2d>)>,
+    window_q: Query<&Window, With<PrimaryWindow>>,
+    mut camera_q: Query<&mut Transform, With<Camera2d>>,
+    mut was_following: Local<bool>,
+){
+    let Ok(mut camera_transform) = camera_q.get_single_mut() else {return;};
+    let target = followed.0
+        .and_then(|client_id| clients_data.get_option_by_client_id(&client_id))
+        .and_then(|data| ships_q.get(data.entity).ok());
+    match target {
+        Some(ship_transform) => {
+            if !*was_following{
+                camera_transform.scale = Vec3::splat(2.);
+            }
+            camera_transform.translation = ship_transform.translation.truncate().extend(camera_transform.translation.z);
+            *was_following = true;
+        }
+        None => {
+            followed.0 = None; // left the game
+            if *was_following{
+                if let Ok(window) = window_q.get_single(){
+                    *camera_transform = overview_transform(&cfg, Vec2::new(window.width(), window.height()));
+                }
+            }
+            *was_following = false;
+        }
+    }
+}
@@ -0,0 +1,228 @@
+use std::{io::{BufRead, BufReader, ErrorKind, Read, Write}, net::{TcpListener, TcpStream}, sync::{Arc, Mutex}, thread, time::{Duration, Instant, SystemTime}};
+use bevy::prelude::*;
+use serde_json::{json, Value};
+
+use crate::shared::*;
+use crate::bots::BotList;
+use super::{profiles::ProfileStore, ServerSettings, SystemTimings};
+
+/*
+    STATS HTTP
+    read-only JSON for dashboards, off by default:
+    server --http 127.0.0.1:8568
+    GET /scoreboard    current match, by kills
+    GET /leaderboard   all-time from profiles, top by kills, accuracy and playtime
+    GET /status        players, bots, uptime, tick rate
+    GET /kills         recent kills, newest first
+    bevy side rebuilds the documents once per second, listener thread only copies them
+*/
+
+const REFRESH_SECS: f32 = 1.;
+const RECENT_KILLS: usize = 50;
+const LEADERBOARD_SIZE: usize = 10;
+const MIN_SHOTS_FOR_ACCURACY: u32 = 100; // few lucky shots don't top the table
+const REQUEST_DEADLINE: Duration = Duration::from_secs(2); // whole request, slow client can't hold the thread
+const MAX_REQUEST_BYTES: u64 = 8192; // request line with headers, body is never read
+
+/// Address to listen on, without it there is no listener
+#[derive(Resource)]
+pub struct HttpSettings{
+    pub bind: String,
+}
+
+#[derive(Default)]
+struct Documents{
+    scoreboard: String,
+    leaderboard: String,
+    status: String,
+    kills: String,
+}
+
+impl Documents{
+    /// Body for GET path, None if there is no such endpoint
+    fn get(&self, path: &str) -> Option<String>{
+        match path {
+            "/scoreboard" => Some(self.scoreboard.clone()),
+            "/leaderboard" => Some(self.leaderboard.clone()),
+            "/status" => Some(self.status.clone()),
+            "/kills" => Some(self.kills.clone()),
+            "/" => Some(json!({"endpoints": ["/scoreboard", "/leaderboard", "/status", "/kills"]}).to_string()),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Resource)]
+pub(super) struct HttpStats{
+    documents: Arc<Mutex<Documents>>, // shared with listener thread
+    kills: Vec<Value>, // newest last
+    started: Instant,
+    last_refresh: f32,
+}
+
+impl HttpStats{
+    /// Documents are empty until the first refresh
+    pub(super) fn new() -> Self{
+        HttpStats{documents: default(), kills: vec![], started: Instant::now(), last_refresh: f32::MIN}
+    }
+
+    #[cfg(test)]
+    pub(super) fn document(&self, path: &str) -> Option<String>{
+        self.documents.lock().unwrap().get(path)
+    }
+}
+
+pub fn init_http(app: &mut App){
+    app.add_systems(Startup, start_http.run_if(resource_exists::<HttpSettings>()));
+    app.add_systems(Update, (record_kills, refresh_documents).chain().run_if(resource_exists::<HttpStats>()));
+}
+
+fn start_http(
+    mut commands: Commands,
+    settings: Res<HttpSettings>,
+){
+    let listener = match TcpListener::bind(&settings.bind) {
+        Ok(listener) => listener,
+        Err(e) => {
+            warn!("Can't start stats http on {}: {}", settings.bind, e); // game works without it
+            return;
+        }
+    };
+    info!("Stats http on {}", settings.bind);
+    let stats = HttpStats::new();
+    let shared = stats.documents.clone();
+    thread::spawn(move ||{
+        for stream in listener.incoming(){
+            let Ok(stream) = stream else {continue;};
+            if let Err(e) = serve(stream, &shared){
+                debug!("Stats http request failed: {}", e);
+            }
+        }
+    });
+    commands.insert_resource(stats);
+}
+
+/// Reads one line, every read waits only for what is left until deadline
+fn read_line_until(reader: &mut impl BufRead, stream: &TcpStream, deadline: Instant, line: &mut String) -> std::io::Result<usize>{
+    let left = deadline.saturating_duration_since(Instant::now());
+    if left.is_zero() {return Err(ErrorKind::TimedOut.into());}
+    stream.set_read_timeout(Some(left))?;
+    reader.read_line(line)
+}
+
+/// One request per connection, only GET
+fn serve(mut stream: TcpStream, documents: &Mutex<Documents>) -> std::io::Result<()>{
+    let deadline = Instant::now() + REQUEST_DEADLINE;
+    stream.set_write_timeout(Some(REQUEST_DEADLINE))?;
+    let mut reader = BufReader::new((&stream).take(MAX_REQUEST_BYTES)); // huge header ends as if request was over
+    let mut request_line = String::new();
+    read_line_until(&mut reader, &stream, deadline, &mut request_line)?;
+    loop { // skip headers
+        let mut line = String::new();
+        if read_line_until(&mut reader, &stream, deadline, &mut line)? == 0 || line.trim().is_empty() {break;}
+    }
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or("");
+    let path = parts.next().unwrap_or("").split('?').next().unwrap_or("");
+
+    let (status, body) = if method != "GET" {
+        ("405 Method Not Allowed", json!({"error": "read-only, use GET"}).to_string())
+    } else {
+        match documents.lock().unwrap().get(path) {
+            Some(body) => ("200 OK", body),
+            None => ("404 Not Found", json!({"error": "unknown path"}).to_string()),
+        }
+    };
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nAccess-Control-Allow-Origin: *\r\nConnection: close\r\n\r\n{}",
+        status, body.len(), body
+    )?;
+    stream.flush()
+}
+
+fn unix_secs() -> u64{
+    SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+fn record_kills(
+    mut stats: ResMut<HttpStats>,
+    mut destroyed_reader: EventReader<ShipDestroyed>,
+    clients_data: Res<ClientsData>,
+    tick: Res<ServerTick>,
+){
+    for event in destroyed_reader.read(){
+        let Some(victim) = clients_data.get_option_by_object_id(event.victim) else {continue;};
+        let killer = event.killer.and_then(|object_id| clients_data.get_option_by_object_id(object_id));
+        stats.kills.push(json!({
+            "tick": tick.0,
+            "time": unix_secs(),
+            "victim": victim.name,
+            "killer": killer.map(|data| data.name.clone()), // null for asteroids and safe zone
+        }));
+    }
+    let extra = stats.kills.len().saturating_sub(RECENT_KILLS);
+    stats.kills.drain(..extra);
+}
+
+fn refresh_documents(
+    mut stats: ResMut<HttpStats>,
+    clients_data: Res<ClientsData>,
+    botlist: Res<BotList>,
+    profiles: Res<ProfileStore>,
+    cfg: Res<GlobalConfig>,
+    settings: Option<Res<ServerSettings>>,
+    timings: Res<SystemTimings>,
+    tick: Res<ServerTick>,
+    time: Res<Time>,
+){
+    if time.elapsed_seconds() - stats.last_refresh < REFRESH_SECS {return;}
+    stats.last_refresh = time.elapsed_seconds();
+
+    let bots = botlist.get_bots_client_ids();
+    let mut players: Vec<&ClientData> = clients_data.iter().collect();
+    players.sort_by(|a, b| b.kills.cmp(&a.kills).then(a.deaths.cmp(&b.deaths)));
+    let scoreboard: Vec<Value> = players.iter().map(|data| json!({
+        "name": data.name,
+        "kills": data.kills,
+        "deaths": data.deaths,
+        "bot": bots.contains(&data.client_id),
+    })).collect();
+    let bot_count = players.iter().filter(|data| bots.contains(&data.client_id)).count();
+
+    let top = |mut profiles: Vec<&PlayerProfile>, key: fn(&PlayerProfile) -> f32| -> Vec<Value>{
+        profiles.sort_by(|a, b| key(b).total_cmp(&key(a)));
+        profiles.iter().take(LEADERBOARD_SIZE).map(|profile| json!({
+            "name": profile.name,
+            "kills": profile.kills,
+            "deaths": profile.deaths,
+            "accuracy": profile.accuracy(),
+            "playtime_secs": profile.playtime_secs,
+        })).collect()
+    };
+    let all: Vec<&PlayerProfile> = profiles.profiles().filter(|profile| profile.has_stats()).collect();
+    let shooters: Vec<&PlayerProfile> = all.iter().copied().filter(|profile| profile.shots >= MIN_SHOTS_FOR_ACCURACY).collect();
+    let leaderboard = json!({
+        "kills": top(all.clone(), |profile| profile.kills as f32),
+        "accuracy": top(shooters, |profile| profile.accuracy()),
+        "playtime": top(all, |profile| profile.playtime_secs),
+    });
+
+    let status = json!({
+        "players": players.len() - bot_count,
+        "bots": bot_count,
+        "max_clients": settings.map(|settings| settings.max_clients),
+        "uptime_secs": stats.started.elapsed().as_secs(),
+        "tick_rate": cfg.tick_rate,
+        "tick": tick.0,
+        "avg_tick_ms": timings.average_ms.iter().map(|(_, ms)| ms).sum::<f32>(),
+    });
+
+    let kills: Vec<&Value> = stats.kills.iter().rev().collect();
+    let kills = json!(kills).to_string();
+    let mut documents = stats.documents.lock().unwrap();
+    documents.scoreboard = json!(scoreboard).to_string();
+    documents.leaderboard = leaderboard.to_string();
+    documents.status = status.to_string();
+    documents.kills = kills;
+}
@@ -0,0 +1,1607 @@
+use std::{net::{UdpSocket, SocketAddr, IpAddr}, time::{SystemTime, Instant}, f32::consts::PI, collections::{HashSet}};
+
+use bevy::{core_pipeline::clear_color::ClearColorConfig, ecs::schedule::ScheduleLabel, prelude::*, scene::ScenePlugin, utils::{hashbrown::HashMap}, window::WindowResized};
+use bevy_rapier2d::{geometry::ColliderDisabled, prelude::{RapierPhysicsPlugin, RapierConfiguration, TimestepMode, PhysicsSet, NoUserData, Velocity}};
+#[cfg(feature = "dev-tools")]
+use bevy_rapier2d::render::RapierDebugRenderPlugin;
+use bevy_renet::{renet::{*, transport::*}, RenetServerPlugin, transport::NetcodeServerPlugin};
+
+#[cfg(feature = "dev-tools")]
+use renet_visualizer::RenetServerVisualizer;
+#[cfg(feature = "client")]
+use bevy_egui::{egui::{self, Style, Visuals, epaint::Shadow, Color32, Rounding, Align, Stroke, FontId}, EguiContexts};
+
+
+pub mod console;
+pub mod profiles;
+pub mod http;
+#[cfg(feature = "client")]
+pub mod dashboard;
+#[cfg(test)]
+mod tests;
+
+use crate::bots::*;
+use crate::shared::*;
+use rand::Rng;
+use rand_chacha::ChaCha8Rng;
+
+
+#[derive(Component)]
+struct GameLabel;
+
+#[derive(Resource)]
+pub struct ServerSettings{
+    pub port: i16,
+    pub max_clients: usize,
+}
+
+
+/*#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Hash, States)]
+enum ServerState {
+    #[default]
+    PreInit,
+    Running
+}
+
+fn main(){
+    let mut app = App::new();
+    app.add_plugins((
+        DefaultPlugins.set(WindowPlugin {
+            primary_window: Some(Window {
+                title: "RUSTEROIDS server".into(),
+                ..default()
+            }),
+            ..default()
+        }),
+        EguiPlugin,
+        WorldInspectorPlugin::new(),
+        RenetServerPlugin,
+        NetcodeServerPlugin,
+        RapierPhysicsPlugin::<NoUserData>::pixels_per_meter(100.0), // ::<NoUserData>::pixels_per_meter(15.0)
+        RapierDebugRenderPlugin{enabled: false, ..default()}
+    ));
+    app.add_state::<ServerState>();
+
+    app.insert_resource(ClientsData::default());
+    app.insert_resource(LoadedChunks{chunks: vec![]});
+    app.insert_resource(GlobalConfig{
+        map_size_chunks: Vec2{
+            x: 3.,
+            y: 1.
+        },
+        single_chunk_size: Vec2{
+            x: 1000.,
+            y: 1000.,
+        },
+        ..default()
+    });
+    app.insert_resource(ServerSettings{
+        port: 8567,
+        max_clients: 16,
+    });
+
+    //app.add_systems(OnEnter(ServerState::PreInit), setup_menu);
+    app.add_systems(Update, menu.run_if(in_state(ServerState::PreInit)));
+    //app.add_systems(OnExit(ServerState::PreInit), cleanup_menu);
+
+    app.add_systems(OnEnter(ServerState::Running), (
+        setup_game,
+    ));
+    app.add_systems(Update, (
+        debug_chunk_render,
+        resize_server_camera,
+
+        state_and_status_checker,
+        check_bullet_collisions_and_lifetime,
+        check_ship_force_events,
+        check_pickups_collisions,
+
+        (asteroids_refiller, snap_objects, update_spatial_index, update_chunks_around, send_message_system).chain(),
+
+        receive_message_system,
+        handle_events_system,
+        
+        calculate_bots_response,
+        
+        console::console_renderer,
+        console::command_executer
+        //check_bullet_collisions_and_lifetime
+    ).run_if(in_state(ServerState::Running)));
+    //app.add_systems(OnExit(ServerState::Running), cleanup_menu)
+
+    app.add_event::<ServerEvent>();
+    init_bots_ai(&mut app);
+    console::setup_commands_executer(&mut app, true);
+    app.run();
+}*/
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+// # [BOTTESTZONE BOTTESTZONE BOTTESTZONE BOTTESTZONE BOTTESTZONE BOTTESTZONE BOTTESTZONE BOTTESTZONE BOTTESTZONE BOTTESTZONE]
+// # [BOTTESTZONE BOTTESTZONE BOTTESTZONE BOTTESTZONE BOTTESTZONE BOTTESTZONE BOTTESTZONE BOTTESTZONE BOTTESTZONE BOTTESTZONE]
+// # [BOTTESTZONE BOTTESTZONE BOTTESTZONE BOTTESTZONE BOTTESTZONE BOTTESTZONE BOTTESTZONE BOTTESTZONE BOTTESTZONE BOTTESTZONE]
+
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Hash, States)]
+pub enum ServerState {
+    PreInit,
+    #[default]
+    Running,
+    Restarting, // one frame between matches, world is cleared and Running is entered again
+}
+
+/// Simulation, networking and bots without window.
+/// Socket is opened when ServerSettings are inserted, otherwise caller inserts RenetServer (tests).
+pub struct ServerPlugin{
+    pub config: GlobalConfig,
+}
+
+impl Plugin for ServerPlugin{
+    fn build(&self, app: &mut App){
+        let cfg = self.config.clone();
+        app.add_plugins((RenetServerPlugin, NetcodeServerPlugin));
+        if !app.is_plugin_added::<BotPlugin>(){
+            app.add_plugins(BotPlugin);
+        }
+        app.add_state::<ServerState>();
+
+        app.insert_resource(ClientsData::default());
+        app.insert_resource(LoadedChunks{chunks: vec![]});
+        #[cfg(feature = "dev-tools")]
+        app.insert_resource(RenetServerVisualizer::<200>::default());
+        app.insert_resource(Time::<Fixed>::from_hz(cfg.tick_rate as f64));
+        app.insert_resource(RapierConfiguration{ // must be before plugin, physics step is one tick
+            timestep_mode: TimestepMode::Fixed { dt: 1. / cfg.tick_rate, substeps: 1 },
+            ..default()
+        });
+        app.add_plugins(RapierPhysicsPlugin::<NoUserData>::pixels_per_meter(100.0).in_fixed_schedule());
+        app.insert_resource(cfg);
+        app.init_resource::<SafeZone>();
+        app.init_resource::<InterestManager>();
+        app.init_resource::<LatestInputs>();
+        app.init_resource::<ServerTick>();
+        app.init_resource::<ShipHistory>();
+        app.init_resource::<SpawnPointsState>();
+
+        app.add_systems(OnEnter(ServerState::Running), (
+            setup_network.run_if(resource_exists::<ServerSettings>()).run_if(not(resource_exists::<NetcodeServerTransport>())),
+            setup_world,
+            update_map_obstacles, // map changes go through Restarting, so entering is enough
+            update_map_bounds,
+        ));
+        app.add_systems(OnEnter(ServerState::Restarting), reset_world);
+        app.add_systems(FixedUpdate, (
+            timing_start,
+            advance_tick,
+            apply_inputs_system,
+            resolve_hitscan_shots, // same tick as fired
+            timing_mark("inputs"),
+            state_and_status_checker,
+            check_bullet_collisions_and_lifetime,
+            check_ship_force_events,
+            check_pickups_collisions,
+            timing_mark("collisions"),
+            (
+                update_homing_missiles,
+                update_beam_weapons,
+                apply_area_powerups,
+                update_powerups_lifetime,
+                apply_ambient_forces,
+                update_safe_zone,
+            ).chain(), // they draw from GameRng, fixed order keeps worlds with same seed same
+            timing_mark("weapons and powerups"),
+            count_kills,
+            (asteroids_refiller, snap_objects, update_spatial_index, update_chunks_around).chain(),
+            timing_mark("world and chunks"),
+        ).chain().before(PhysicsSet::SyncBackend).run_if(in_state(ServerState::Running)));
+        app.add_systems(FixedUpdate, (
+            timing_mark("physics"),
+            record_ship_history,
+            send_message_system.run_if(snapshot_due),
+            timing_mark("snapshots"),
+        ).chain().after(PhysicsSet::Writeback).run_if(in_state(ServerState::Running)));
+        app.add_systems(Update, (
+            receive_message_system,
+            handle_events_system,
+            console::command_executer,
+            admin_commands_system,
+            pending_kicks_system,
+            pending_server_action_system,
+        ).chain().run_if(in_state(ServerState::Running)));
+
+        app.add_event::<ServerEvent>();
+        app.init_resource::<TickTimer>();
+        app.init_resource::<SystemTimings>();
+        app.init_resource::<BanList>();
+        app.init_resource::<PendingKicks>();
+        app.add_event::<AdminCommand>();
+        app.init_resource::<PendingServerAction>();
+        app.add_event::<MatchEnded>();
+        profiles::init_profiles(app);
+        http::init_http(app);
+        app.add_systems(First, tick_timer_start);
+        app.add_systems(Last, tick_timer_end.run_if(resource_exists::<RenetServer>()));
+
+        app.add_event::<ShipDestroyed>();
+        app.add_event::<HitscanFired>();
+        console::setup_commands_executer(app, true);
+    }
+}
+
+/// Plugins that ServerPlugin needs when there is no window and renderer (server-only build, tests)
+pub struct HeadlessPlugin;
+
+impl Plugin for HeadlessPlugin{
+    fn build(&self, app: &mut App){
+        app.add_plugins((TransformPlugin, HierarchyPlugin, AssetPlugin::default(), ScenePlugin));
+        app.init_asset::<Mesh>(); // ships and asteroids still get meshes, nobody draws them
+        app.init_asset::<ColorMaterial>();
+        app.init_asset::<Image>();
+    }
+}
+
+/// Server window: overview camera, map and debug render, console, admin dashboard and start menu
+#[cfg(feature = "client")]
+pub struct ServerWindowPlugin;
+
+#[cfg(feature = "client")]
+impl Plugin for ServerWindowPlugin{
+    fn build(&self, app: &mut App){
+        #[cfg(feature = "dev-tools")]
+        app.add_plugins(RapierDebugRenderPlugin{enabled: false, ..default()});
+
+        //app.add_systems(OnEnter(ServerState::PreInit), setup_menu);
+        app.add_systems(Update, menu.run_if(in_state(ServerState::PreInit)));
+        //app.add_systems(OnExit(ServerState::PreInit), cleanup_menu);
+
+        app.add_systems(OnEnter(ServerState::Running), setup_server_camera);
+        app.add_systems(Update, (
+            debug_chunk_render,
+            resize_server_camera,
+            render_gravity_wells,
+            render_safe_zone,
+            console::console_renderer,
+        ).run_if(in_state(ServerState::Running)));
+        dashboard::init_dashboard(app);
+        //app.add_systems(OnExit(ServerState::Running), cleanup_menu)
+    }
+}
+
+
+
+
+
+
+
+
+
+fn setup_network(
+    mut commands: Commands,
+    settings: Res<ServerSettings>,
+){
+    // INIT SERVER   
+    let server = RenetServer::new(connection_config());
+    commands.insert_resource(server);
+
+    let server_addr = vec![format!("127.0.0.1:{}", settings.port).parse::<SocketAddr>().unwrap()];//format!("127.0.0.1:{}", settings.port).parse().unwrap(); SocketAddr::from("127.0.0.1:{}");
+    
+    let current_time = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).unwrap();
+    let socket = UdpSocket::bind(server_addr[0]).unwrap();
+    const GAME_PROTOCOL_ID: u64 = 0;
+    let server_config = ServerConfig {
+        max_clients: settings.max_clients,
+        protocol_id: GAME_PROTOCOL_ID,
+        public_addresses: server_addr,
+        current_time: current_time,
+        authentication: ServerAuthentication::Unsecure // todo: change to secure
+    };
+    
+    let transport = NetcodeServerTransport::new(server_config, socket).unwrap();
+    commands.insert_resource(transport);
+    println!("SERVER STARTED!!!!");
+}
+
+fn setup_world(
+    mut commands: Commands,
+    cfg: Res<GlobalConfig>,
+    mut loaded_chunks: ResMut<LoadedChunks>, //todo: wtf is that?
+){
+    println!("WORLD SEED: {}", cfg.world_seed);
+    commands.insert_resource(GameRng::from_seed(cfg.world_seed));
+    // INIT CHUNKS
+    for x in -1..(cfg.map_size_chunks.x as i32 + 1){ // include shadow chunks
+        for y in -1..(cfg.map_size_chunks.y as i32 + 1){
+            loaded_chunks.chunks.push(Chunk { pos: Vec2::from((x as f32, y as f32)) });
+        }
+    }
+
+    commands.insert_resource(SpatialIndex::default());
+}
+
+/// Whole map with shadow chunks fitted into window
+#[cfg(feature = "client")]
+fn overview_transform(cfg: &GlobalConfig, window_size: Vec2) -> Transform{
+    let size = (cfg.map_size_chunks  + Vec2::from((2., 2.))) * cfg.single_chunk_size;
+    let mid = cfg.map_size_chunks * cfg.single_chunk_size / 2.;
+    let target_scale = if window_size.x / window_size.y < size.x / size.y{
+        size.x / window_size.x
+    } else {
+        size.y / window_size.y
+    };
+    Transform::from_xyz(mid.x, mid.y, 0.).with_scale(Vec3::splat(target_scale))
+}
+
+#[cfg(feature = "client")]
+fn setup_server_camera(
+    mut commands: Commands,
+    cfg: Res<GlobalConfig>,
+    mut window: Query<&mut Window>,
+    mut camera_q: Query<&mut Transform, With<Camera2d>>,
+){
+    let window_size = Vec2::from((window.single_mut().width(), window.single_mut().height()));
+    if let Ok(mut transform) = camera_q.get_single_mut(){ // after restart or map change
+        *transform = overview_transform(&cfg, window_size);
+        return;
+    }
+    
+    commands.spawn(
+            Camera2dBundle{
+                camera_2d: Camera2d {
+                    clear_color: ClearColorConfig::Custom(Color::Rgba { red: 0., green: 0., blue: 0., alpha: 1. }),
+                    ..default()
+                },
+                camera: Camera{
+                    hdr: true,
+                    ..default()
+                },
+                transform: overview_transform(&cfg, window_size),
+                ..default()
+            },
+    );
+}
+
+pub fn spawn_test_bots( // todo: remove!
+    mut commands: Commands,
+    mut cfg: ResMut<GlobalConfig>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    time: Res<Time>,
+    mut botlist: ResMut<BotList>,
+    mut clients_data: ResMut<ClientsData>,
+    mut rng: ResMut<GameRng>,
+){
+    // SPAWN BOT TARGET
+    let object_id = cfg.new_id();
+    let style = 0;
+    let color = Color::BLUE;
+    let for_spawn_cl_data = ClientData::for_spawn(style, color, object_id);
+    let pos = Vec2{x: 2500., y: 1000.,}.extend(0.);
+    let entity = spawn_ship(false, &mut meshes, &mut materials, &mut commands, &for_spawn_cl_data, &mut cfg, &time);
+    commands.entity(entity).insert(Transform::from_translation(pos));
+
+    // SPAWN BOT
+    let object_id = cfg.new_id();
+    let style = 0;
+    let color = Color::ORANGE_RED;
+    let name = "BOT";
+    let id = rng.0.gen::<u64>();
+    let for_spawn_cl_data = ClientData::for_spawn(style, color, object_id);
+    let pos = Vec2{x: 500., y: 1000.,}.extend(0.);
+    let entity = spawn_ship(false, &mut meshes, &mut materials, &mut commands, &for_spawn_cl_data, &mut cfg, &time);
+    commands.entity(entity).insert(Transform::from_translation(pos));
+    let new_client_data = ClientData { 
+        client_id: id,
+        object_id: object_id,
+        entity: entity,
+        style: style,
+        color: color, 
+        name: name.to_string(),
+        kills: 0,
+        deaths: 0,
+    };
+    clients_data.add(new_client_data.clone());
+    botlist.register_bot(id);
+    
+
+
+
+    
+
+
+    // SPAWN ASTEROIDS
+
+
+
+    /*for x in 0..cfg.map_size_chunks.x as u32{
+        for y in 0..cfg.map_size_chunks.y as u32{
+            let vel = Velocity{
+                linvel: Vec2 { 
+                    x: (rand::random::<f32>() - 0.5), 
+                    y: (rand::random::<f32>() - 0.5) 
+                } * 0./*500.*/, 
+                angvel: (rand::random::<f32>() - 0.5) * 5.
+            };
+            let position = Transform::from_translation(
+                Vec3::from([
+                    (x as f32 + rand::random::<f32>()) * cfg.single_chunk_size.x,
+                    (y as f32 + rand::random::<f32>()) * cfg.single_chunk_size.y,
+                    0.
+                ]));
+            let seed = rand::random::<u64>();
+            spawn_asteroid(seed, vel, position, &mut meshes, &mut materials, &mut commands, cfg.new_id(), cfg.get_asteroid_hp(seed));
+        }
+    }*/
+
+}
+// # [NOBOT NOBOT NOBOT NOBOT NOBOT NOBOT NOBOT NOBOT NOBOT NOBOT NOBOT NOBOT NOBOT NOBOT NOBOT NOBOT NOBOT NOBOT NOBOT NOBOT NOBOT NOBOT NOBOT NOBOT]
+// # [NOBOT NOBOT NOBOT NOBOT NOBOT NOBOT NOBOT NOBOT NOBOT NOBOT NOBOT NOBOT NOBOT NOBOT NOBOT NOBOT NOBOT NOBOT NOBOT NOBOT NOBOT NOBOT NOBOT NOBOT]
+// # [NOBOT NOBOT NOBOT NOBOT NOBOT NOBOT NOBOT NOBOT NOBOT NOBOT NOBOT NOBOT NOBOT NOBOT NOBOT NOBOT NOBOT NOBOT NOBOT NOBOT NOBOT NOBOT NOBOT NOBOT]
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+#[cfg(feature = "client")]
+fn menu(
+    mut egui_context: EguiContexts,
+    mut settings: ResMut<ServerSettings>,
+    mut port_preview: Local<String>,
+    mut next_state: ResMut<NextState<ServerState>>,
+){
+    let ctx = egui_context.ctx_mut();
+    let mut fonts = egui::FontDefinitions::default();
+        fonts.font_data.insert("Font".to_owned(),
+        egui::FontData::from_static(include_bytes!("../../assets/fonts/VecTerminus12Medium.otf") )
+    );
+    
+    fonts.families.insert(egui::FontFamily::Name("Font".into()), vec!["Font".to_owned()]);
+    fonts.families.get_mut(&egui::FontFamily::Proportional).unwrap()
+            .insert(0, "Font".to_owned());
+    fonts.families.get_mut(&egui::FontFamily::Monospace).unwrap()
+        .insert(0, "Font".to_owned());
+
+    ctx.set_fonts(fonts);
+
+    let style = Style{
+        visuals: Visuals{
+            window_rounding: Rounding::ZERO,
+            window_shadow: Shadow::NONE,
+            window_fill: Color32::from_rgba_unmultiplied(0, 0, 0, 230),
+            window_stroke: Stroke{
+                width: 1.,
+                color: Color32::from_rgba_unmultiplied(255, 255, 255, 255)
+            },
+            button_frame: false,
+            ..default()
+        },
+        animation_time: 0.,
+        ..default()
+    };
+    ctx.set_style(style.clone());
+
+
+    egui::Window::new("MENU")
+        .anchor(egui::Align2([Align::Center, Align::Center]), [0., 100.])
+        
+        //.constrain(true)
+        .resizable(false)
+        //.default_height(100.0)
+        .default_width(200.)
+        
+        .title_bar(false)
+        .collapsible(false)
+        
+        .vscroll(false)
+        .hscroll(false)
+        
+        //.fixed_size(bevy_egui::egui::Vec2{x: 100., y: 100.})
+        .show(ctx, |ui|{
+            ui.set_style(style.clone());
+
+            let mut newstyle = (*ctx.style()).clone();
+            newstyle.text_styles = [
+                (egui::TextStyle::Button, FontId::new(34.0, egui::FontFamily::Monospace)),
+                (egui::TextStyle::Body, FontId::new(34.0, egui::FontFamily::Monospace))
+                ].into();
+            ui.style_mut().text_styles = newstyle.text_styles;
+            ui.add(egui::TextEdit::singleline(&mut *port_preview).char_limit(5).hint_text("PORT (8567)"));
+            if let Ok(_) = (*port_preview).parse::<i32>(){
+            } else if port_preview.len() != 0 {
+                ui.add(egui::Label::new(egui::RichText::new("PORT IS INVALID!").color(Color32::RED)));
+            }
+            ui.add(egui::Slider::new(&mut settings.max_clients, 1..=64).suffix(" MAX PLAYERS"));
+            let start_btn = ui.add(egui::Button::new("START SERVER")).clicked();
+            if start_btn{
+                // CHECK ALL, INIT SERVER AND CHANGE STATE
+                next_state.set(ServerState::Running);
+            }
+        });
+}
+
+
+fn setup_game(
+    mut commands: Commands,
+    settings: Res<ServerSettings>,
+    mut cfg: ResMut<GlobalConfig>,
+    mut window: Query<&mut Window>,
+    mut loaded_chunks: ResMut<LoadedChunks>, //todo: wtf is that?
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut time: Res<Time>,
+    
+){
+    // INIT SERVER   
+    let server = RenetServer::new(connection_config());
+    commands.insert_resource(server);
+
+    let server_addr = vec![format!("127.0.0.1:{}", settings.port).parse::<SocketAddr>().unwrap()];//format!("127.0.0.1:{}", settings.port).parse().unwrap(); SocketAddr::from("127.0.0.1:{}");
+    
+    #[cfg(feature = "dev-tools")]
+    commands.insert_resource(RenetServerVisualizer::<200>::default());
+    let current_time = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).unwrap();
+    let socket = UdpSocket::bind(server_addr[0]).unwrap();
+    const GAME_PROTOCOL_ID: u64 = 0;
+    let server_config = ServerConfig {
+        max_clients: settings.max_clients,
+        protocol_id: GAME_PROTOCOL_ID,
+        public_addresses: server_addr,
+        current_time: current_time,
+        authentication: ServerAuthentication::Unsecure // todo: change to secure
+    };
+    
+    let transport = NetcodeServerTransport::new(server_config, socket).unwrap();
+    commands.insert_resource(transport);
+    println!("SERVER STARTED!!!!");
+    println!("WORLD SEED: {}", cfg.world_seed);
+    commands.insert_resource(GameRng::from_seed(cfg.world_seed));
+    let size = (cfg.map_size_chunks  + Vec2::from((2., 2.))) * cfg.single_chunk_size;
+    let mid = cfg.map_size_chunks * cfg.single_chunk_size / 2.;
+    let window_size = Vec2::from((window.single_mut().width(), window.single_mut().height()));
+    let target_scale = if window_size.x / window_size.y < size.x / size.y{
+        size.x / window_size.x
+    } else {
+        size.y / window_size.y
+    };
+    
+    commands.spawn(
+            Camera2dBundle{
+                camera_2d: Camera2d {
+                    clear_color: ClearColorConfig::Custom(Color::Rgba { red: 0., green: 0., blue: 0., alpha: 1. }),
+                    ..default()
+                },
+                camera: Camera{
+                    hdr: true,
+                    ..default()
+                },
+                transform: Transform::from_xyz(mid.x, mid.y, 0.).with_scale(Vec3::splat(target_scale)),
+                ..default()
+            },
+    );
+    // INIT CHUNKS
+    for x in -1..(cfg.map_size_chunks.x as i32 + 1){ // include shadow chunks
+        for y in -1..(cfg.map_size_chunks.y as i32 + 1){
+            loaded_chunks.chunks.push(Chunk { pos: Vec2::from((x as f32, y as f32)) });
+        }
+    }
+
+    commands.insert_resource(SpatialIndex::default());
+
+
+
+    // SPAWN ASTEROIDS
+    /*for x in 0..cfg.map_size_chunks.x as u32{
+        for y in 0..cfg.map_size_chunks.y as u32{
+            let vel = Velocity{
+                linvel: Vec2 { 
+                    x: (rand::random::<f32>() - 0.5), 
+                    y: (rand::random::<f32>() - 0.5) 
+                } * 0./*500.*/, 
+                angvel: (rand::random::<f32>() - 0.5) * 5.
+            };
+            let position = Transform::from_translation(
+                Vec3::from([
+                    (x as f32 + rand::random::<f32>()) * cfg.single_chunk_size.x,
+                    (y as f32 + rand::random::<f32>()) * cfg.single_chunk_size.y,
+                    0.
+                ]));
+            let seed = rand::random::<u64>();
+            spawn_asteroid(seed, vel, position, &mut meshes, &mut materials, &mut commands, cfg.new_id(), cfg.get_asteroid_hp(seed));
+        }
+    }*/
+
+}
+
+#[cfg(feature = "client")]
+fn resize_server_camera(
+    resize_event: Res<Events<WindowResized>>,
+    cfg: ResMut<GlobalConfig>,
+    followed: Res<dashboard::FollowedClient>,
+    mut camera_transform_q: Query<&mut Transform, With<Camera2d>>
+){
+    let mut reader = resize_event.get_reader();
+    for e in reader.read(&resize_event) {
+        if followed.0.is_some() {continue;} // zoom is kept while following
+        let window_size = Vec2::from((e.width, e.height));
+        camera_transform_q.single_mut().scale = overview_transform(&cfg, window_size).scale;
+        //println!("width = {} height = {}", e.width, e.height);
+    }
+}
+
+// Systems
+fn send_message_system(
+    mut server: ResMut<RenetServer>,
+    clients_data: Res<ClientsData>,
+    mut commands: Commands,
+    states_and_statuses_q: Query<(&ShipState, &ShipStatuses), Without<Puppet>>,
+    weapons_q: Query<&ShipWeapon, Without<Puppet>>,
+    mut objects_q: Query<(&Object, &Velocity, &Transform, Entity), (With<Object>, Without<Puppet>)>,
+    index: Res<SpatialIndex>,
+    mut bots: ResMut<BotList>,
+    mut interest: ResMut<InterestManager>,
+    tick: Res<ServerTick>,
+    cfg: ResMut<GlobalConfig>,
+) {
+    /*
+                LastDamageTaken{time: 0.},
+    */
+    let mut objects_data: HashMap<Entity, ObjectData> = HashMap::new(); // built once for objects someone sees
+    let get_object_data = |e: Entity| -> Option<ObjectData> {
+        let (object, velocity, transform, _) = objects_q.get(e).ok()?;
+        let is_player = match object.object_type{
+            ObjectType::Ship { style: _, color: _, shields: _, hp: _} => {true},
+            _ => {false}
+        };
+        Some(ObjectData{
+            object: object.clone(),
+            states_and_statuses: if is_player {
+                let states_and_statuses = states_and_statuses_q.get(e).unwrap();
+                Some((states_and_statuses.0.clone(), states_and_statuses.1.clone()))
+            } else {None},
+            weapon: weapons_q.get(e).ok().copied(),
+            angular_velocity: velocity.angvel,
+            linear_velocity: velocity.linvel,
+            translation: transform.translation,
+            rotation: transform.rotation,
+        })
+    };
+
+    let bot_ids = bots.get_bots_client_ids();
+    let clients_ids = server.clients_id();
+    let mut clients = HashMap::new();
+    for cid in clients_ids{
+        clients.insert(cid.raw(), cid);
+    }
+    for bot_id in bot_ids.iter(){ // todo: find better solution
+        clients.insert(*bot_id, ClientId::from_raw(*bot_id));
+    }
+
+    interest.clients.retain(|id, _| clients.contains_key(id));
+    let sent_ms = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).unwrap().as_millis() as u64;
+    for client_id in clients.keys().collect::<Vec<&u64>>().iter() {
+        let is_bot = bot_ids.contains(*client_id);
+        let clients_data = clients_data.get_option_by_client_id(*client_id);
+        if clients_data.is_some(){
+            let clients_data = clients_data.unwrap();
+            let e = clients_data.entity;
+            let obj = objects_q.get(e);
+            if obj.is_ok(){
+                let (_, _, t, _) = obj.unwrap();
+                let client_interest = interest.clients.entry(**client_id).or_default();
+                let radius = cfg.interest.radius(client_interest.viewport);
+                let player_pos = t.translation.truncate();
+                let near = index.in_radius(&cfg, player_pos, radius);
+                for (entry, _) in near.iter(){
+                    if !objects_data.contains_key(&entry.entity){
+                        if let Some(data) = get_object_data(entry.entity){
+                            objects_data.insert(entry.entity, data);
+                        }
+                    }
+                }
+                let mut candidates: Vec<(&ObjectData, f32)> = vec![]; // data, distance
+                for (entry, distance) in near.iter(){
+                    let Some(object_data) = objects_data.get(&entry.entity) else {continue;};
+                    let is_owner = clients_data.object_id == object_data.object.id;
+                    if let ObjectType::Ship { .. } = object_data.object.object_type{
+                        let (state, statuses) = object_data.states_and_statuses.as_ref().unwrap();
+                        let hidden = match state {
+                            ShipState::Dead { time: _ } => true,
+                            _ => statuses.is_hidden() // revealed ships are sent to everyone
+                        };
+                        if hidden && !is_owner {continue;} // send only to owner
+                    }
+                    candidates.push((object_data, if is_owner {-1.} else {*distance}));
+                }
+                if is_bot{ // bots live on server, no bandwidth to save
+                    bots.set_bot_world_state(**client_id, candidates.iter().map(|(data, _)| (*data).clone()).collect());
+                    continue;
+                }
+
+                // PRIORITY ACCUMULATORS
+                let bandwidth = cfg.interest.bandwidth_bytes_per_sec;
+                let dt = cfg.snapshot_interval();
+                client_interest.budget = (client_interest.budget + bandwidth * dt).min(bandwidth * 0.2);
+                let mut prioritized: Vec<(&ObjectData, f32)> = candidates.iter().map(|(data, distance)| {
+                    let priority = client_interest.priorities.entry(data.object.id).or_insert(0.);
+                    *priority += cfg.interest.priority(distance.max(0.), radius, data.linear_velocity.length()) * dt;
+                    (*data, if *distance < 0. {f32::INFINITY} else {*priority}) // own ship first
+                }).collect();
+                prioritized.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+                let mut personalised_data: Vec<ObjectData> = vec![];
+                let mut keep: Vec<u64> = vec![];
+                for (object_data, priority) in prioritized.into_iter(){
+                    let id = object_data.object.id;
+                    let size = bincode::serialized_size(object_data).unwrap_or(0) as f32;
+                    if priority.is_infinite() || client_interest.budget >= size{
+                        client_interest.budget -= size;
+                        client_interest.priorities.insert(id, 0.);
+                        personalised_data.push(object_data.clone());
+                    } else if client_interest.known.contains(&id){
+                        client_interest.budget -= 8.;
+                        keep.push(id);
+                    }
+                }
+                client_interest.known = personalised_data.iter().map(|data| data.object.id).chain(keep.iter().copied()).collect();
+                let in_radius: HashSet<u64> = candidates.iter().map(|(data, _)| data.object.id).collect();
+                client_interest.priorities.retain(|id, _| in_radius.contains(id));
+
+                let msg = Message::Update {
+                    tick: tick.0,
+                    sent_ms,
+                    data: personalised_data,
+                    keep: keep,
+                };
+                let encoded: Vec<u8> = bincode::serialize(&msg).unwrap();
+                server.send_message(*clients.get(*client_id).unwrap(), ServerChannel::Fast, encoded);
+            }
+        }
+    }
+
+}
+
+
+fn advance_tick(
+    mut tick: ResMut<ServerTick>,
+){
+    tick.0 += 1;
+}
+
+fn snapshot_due(
+    tick: Res<ServerTick>,
+    cfg: Res<GlobalConfig>,
+) -> bool {
+    tick.0 % cfg.ticks_per_snapshot() == 0
+}
+
+/// Per client state of interest management
+struct ClientInterest{
+    viewport: Vec2, // reported by client, world units
+    priorities: HashMap<u64, f32>, // object_id -> accumulated priority, reset when sent
+    known: HashSet<u64>, // objects client has and keeps until they leave radius
+    budget: f32, // bytes allowed to send now
+}
+
+impl Default for ClientInterest{
+    fn default() -> Self {
+        ClientInterest{
+            viewport: DEFAULT_VIEWPORT,
+            priorities: HashMap::new(),
+            known: HashSet::new(),
+            budget: 0.,
+        }
+    }
+}
+
+#[derive(Resource, Default)]
+struct InterestManager{
+    clients: HashMap<u64, ClientInterest>, // client_id -> interest
+}
+
+
+struct ServerSideVarables{
+    shooting_cds: HashMap<u64, f32>, // client_id -> latest shoot time
+    dash_cds: HashMap<u64, f32>, // client_id -> latest shoot time
+}
+
+impl Default for ServerSideVarables{
+    fn default() -> Self {
+        ServerSideVarables{
+            shooting_cds: HashMap::new(),
+            dash_cds: HashMap::new(),
+        }
+    }
+}
+
+/// Fires current weapon of the ship: cooldowns, charging, pellets spread and ammo.
+/// Charged weapons accumulate charge while shoot is held and fire on release.
+fn handle_shooting(
+    shoot: bool,
+    shooter_id: u64, // client_id, key for cooldowns
+    owner: u64, // object_id of ship
+    rewind_ticks: u64, // how old world shooter sees, bullets and beams hit ships there
+    velocity: &Velocity,
+    transform: &Transform,
+    statuses: &mut ShipStatuses,
+    weapon: &mut ShipWeapon,
+    server_side_varables: &mut ServerSideVarables,
+    cfg: &mut ResMut<GlobalConfig>,
+    rng: &mut ChaCha8Rng,
+    time: &Time,
+    asset_server: &Res<AssetServer>,
+    shot_writer: &mut EventWriter<ShotFired>,
+    hitscan_writer: &mut EventWriter<HitscanFired>,
+    commands: &mut Commands,
+){
+    if shoot{
+        statuses.remove(PowerUPEffectKind::SpawnImmunity); // shooting ends protection
+    }
+    let stats = cfg.get_weapon_stats(weapon.weapon_type).clone();
+    if stats.beam_range.is_some(){ // beam is handled every tick in update_beam_weapons
+        weapon.firing = shoot;
+        weapon.rewind_ticks = rewind_ticks;
+        return;
+    }
+    let current_time = time.elapsed().as_secs_f32();
+    let last_time = server_side_varables.shooting_cds.get(&shooter_id);
+    if last_time.is_some() && current_time - *last_time.unwrap() <= stats.fire_cd_secs{
+        weapon.charge = 0.;
+        return;
+    }
+    if stats.charge_secs > 0. {
+        if shoot {
+            weapon.charge += time.delta_seconds();
+            return;
+        }
+        let charged = weapon.charge >= stats.charge_secs;
+        weapon.charge = 0.;
+        if !charged {return;}
+    } else if !shoot {
+        return;
+    }
+
+    // FIRE
+    let forward = transform.up().truncate();
+    let multishot = statuses.get_value(PowerUPEffectKind::Multishot).unwrap_or(1.).max(1.) as u32;
+    for n in 0..stats.pellets * multishot{
+        let (n, copy) = (n / multishot, n % multishot);
+        let mut angle = if stats.pellets > 1 {
+            -stats.spread / 2. + stats.spread * n as f32 / (stats.pellets - 1) as f32
+        } else {
+            (rng.gen::<f32>() - 0.5) * stats.spread
+        };
+        angle += (copy as f32 - (multishot - 1) as f32 / 2.) * 0.15; // multishot fan
+        let dir = Vec2::from_angle(angle).rotate(forward);
+        if let Some(range) = stats.hitscan_range{ // ray is resolved this tick, nothing flies
+            hitscan_writer.send(HitscanFired{
+                owner,
+                origin: transform.translation.truncate(),
+                dir,
+                range,
+                damage: stats.damage * (1. + statuses.get_value(PowerUPEffectKind::ExtraDamage).unwrap_or(0.)),
+                weapon: weapon.weapon_type,
+                rewind_ticks,
+            });
+            shot_writer.send(ShotFired{owner});
+            continue;
+        }
+        let object_id = cfg.new_id();
+        let entity = spawn_bullet(
+            velocity.linvel + dir * stats.projectile_speed, 
+            statuses.get_value(PowerUPEffectKind::ExtraDamage).unwrap_or(0.),
+            weapon.weapon_type,
+            *transform, 
+            object_id, 
+            owner, 
+            current_time, 
+            asset_server, 
+            commands
+        );
+        if rewind_ticks > 0 {
+            commands.entity(entity).insert(LagCompensation{rewind_ticks});
+        }
+        shot_writer.send(ShotFired{owner});
+    }
+    server_side_varables.shooting_cds.insert(shooter_id, current_time);
+
+    // AMMO
+    if weapon.ammo.is_some(){
+        let ammo = weapon.ammo.unwrap().saturating_sub(1);
+        if ammo == 0 {
+            *weapon = ShipWeapon::new(WeaponType::Blaster, cfg);
+        } else {
+            weapon.ammo = Some(ammo);
+        }
+    }
+}
+
+#[derive(Resource, Default)]
+struct LatestInputs(HashMap<u64, InputKeys>); // client_id -> inputs, used every tick until new ones come
+
+/// Applies latest inputs of players and bots to their ships, once per tick
+fn apply_inputs_system(
+    mut latest_inputs: ResMut<LatestInputs>,
+    bots: Res<BotList>,
+    clients_data: Res<ClientsData>,
+    mut ships_q: Query<(&mut Velocity, &Transform, &Object, &mut ShipState, &mut ShipStatuses, &mut ShipWeapon), (With<Ship>, Without<Puppet>)>,
+    mut server_side_varables: Local<ServerSideVarables>,
+    mut cfg: ResMut<GlobalConfig>,
+    mut rng: ResMut<GameRng>,
+    tick: Res<ServerTick>,
+    time: Res<Time>,
+    asset_server: Res<AssetServer>,
+    (mut shot_writer, mut hitscan_writer): (EventWriter<ShotFired>, EventWriter<HitscanFired>),
+    mut commands: Commands,
+){
+    latest_inputs.0.retain(|client_id, _| clients_data.get_option_by_client_id(client_id).is_some());
+    let bot_ids = bots.get_bots_client_ids();
+    let bot_inputs = bot_ids.iter().filter_map(|bot_id| bots.get_bot_response(bot_id).map(|inputs| (bot_id, inputs)));
+    for (client_id, inputs) in latest_inputs.0.iter().chain(bot_inputs){
+        let client_id = *client_id;
+        let Some(client_data) = clients_data.get_option_by_client_id(&client_id) else {continue;};
+        let Ok((mut velocity, transform, object, mut state, mut statuses, mut weapon)) = ships_q.get_mut(client_data.entity) else {continue;};
+        //todo: add dash
+        match *state{
+            ShipState::Dead { time: _ } => {} // cant move!
+            ShipState::Dash { start_time, mut init_velocity } => {
+
+                //*velocity.linvel = direction.normalize_or_zero() * 400. * (0.5 + 1. - ((time.elapsed_seconds() - start_time) / cfg.dash_time).powi(3));
+
+                //*state = ShipState::Dash { start_time: time.elapsed_seconds(), direction: target_direction };
+
+                // let it be...
+                velocity.linvel = init_velocity + init_velocity.normalize_or_zero() * cfg.dash_impulse;
+                /*velocity.linvel = init_velocity.normalize_or_zero() * 400. * (0.5 + 1. - ((time.elapsed_seconds() - start_time) / cfg.dash_time).powi(3));
+                *state = ShipState::Dash { start_time: start_time, init_velocity: init_velocity };*/
+
+                if start_time + cfg.dash_time < time.elapsed_seconds(){
+                    *state = ShipState::Regular;
+                    velocity.linvel = if init_velocity.length_squared() > 300_f32.powi(2) {init_velocity} else {init_velocity.normalize() * 300.}
+                }
+            }
+            ShipState::Regular => {
+                // MOVES
+                let mut target_direction = inputs.input_vector;
+                if target_direction.length_squared() > 1. {
+                    target_direction = target_direction.normalize();
+                }
+
+                if statuses.has_haste(){
+                    target_direction *= statuses.get_value(PowerUPEffectKind::Haste).unwrap();
+                } 
+
+                // let it be...
+                // todo: do something! / or no...
+                let target_angle = transform.up().truncate().angle_between(inputs.rotation_target);
+                if !target_angle.is_nan(){
+                    velocity.angvel += ((target_angle * 180. / PI - velocity.angvel) * 1.).clamp(-90., 90.);//.clamp(-1.5, 1.5);
+                }
+                velocity.linvel += target_direction * time.delta_seconds() * 100.;
+
+                // SHOOTING
+                let rewind_ticks = if inputs.acked_tick == 0 {0} else {tick.0.saturating_sub(inputs.acked_tick).min(cfg.max_rewind_ticks())}; // bots don't ack
+                handle_shooting(inputs.shoot, client_id, client_data.object_id, rewind_ticks, &velocity, transform, &mut statuses, &mut weapon, &mut server_side_varables, &mut cfg, &mut rng.0, &time, &asset_server, &mut shot_writer, &mut hitscan_writer, &mut commands);
+                if inputs.dash {
+                    let exist = server_side_varables.dash_cds.contains_key(&client_id);
+                    let current_time = time.elapsed().as_secs_f32();
+                    if exist {
+                        let last_time = server_side_varables.dash_cds.get(&client_id).unwrap().clone();
+                        if time.elapsed().as_secs_f32() - last_time > cfg.dash_cd_secs{
+                            if target_direction == Vec2::ZERO {
+                                target_direction = Vec2::from_angle(transform.rotation.to_euler(EulerRot::XYZ).2 + PI / 2.);
+                            }
+                            *state = ShipState::Dash { start_time: time.elapsed_seconds(), init_velocity: target_direction.normalize() * velocity.linvel.length()};
+
+                            velocity.angvel = 0.;
+
+                            server_side_varables.dash_cds.insert(client_id, current_time);
+                        }
+                    } else {
+                        if target_direction == Vec2::ZERO {
+                            target_direction = Vec2::from_angle(transform.rotation.to_euler(EulerRot::XYZ).2 + PI / 2.);
+                        }
+                        *state = ShipState::Dash { start_time: time.elapsed_seconds(), init_velocity: target_direction };
+
+                        velocity.angvel = 0.;
+                        server_side_varables.dash_cds.insert(client_id, current_time);
+                    }
+                }
+            }
+        }
+    }
+}
+
+
+fn receive_message_system(
+    mut server: ResMut<RenetServer>,
+    mut latest_inputs: ResMut<LatestInputs>,
+    mut clients_data: ResMut<ClientsData>,
+    mut commands: Commands,
+    index: Res<SpatialIndex>,
+    mut cfg: ResMut<GlobalConfig>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut interest: ResMut<InterestManager>,
+    mut rng: ResMut<GameRng>,
+    safe_zone: Res<SafeZone>,
+    time: Res<Time>,
+    (ban_list, mut kicks, transport): (Res<BanList>, ResMut<PendingKicks>, Option<Res<NetcodeServerTransport>>),
+    mut profiles: ResMut<profiles::ProfileStore>,
+) {
+    for client_id in server.clients_id().into_iter() {
+        while let Some(message) = server.receive_message(client_id, ClientChannel::Fast) {
+            let msg: Message = bincode::deserialize::<Message>(&message).unwrap();
+            match msg {
+                Message::Viewport{ size } => {
+                    interest.clients.entry(client_id.raw()).or_default().viewport = size;
+                }
+                Message::Inputs{ inputs } => { // applied in apply_inputs_system every tick
+                    latest_inputs.0.insert(client_id.raw(), inputs);
+                }
+                msg_type => {
+                    warn!("Unhandled message recived on server!");
+                }
+            }
+           // println!("{}", String::from_utf8(message.to_vec()).unwrap());
+        }
+        while let Some(message) = server.receive_message(client_id, ClientChannel::Garanteed) {
+            // println!("{}", String::from_utf8(message.to_vec()).unwrap());
+            let msg: Message = bincode::deserialize::<Message>(&message).unwrap();
+            match msg {
+                Message::RegisterClient { style, color, name, credentials } => {
+                    let (identity, new_credentials) = profiles.login(credentials);
+                    if let Some(new_credentials) = new_credentials{
+                        profiles::send_credentials(&mut server, new_credentials, client_id.raw());
+                    }
+                    let address = transport.as_ref().and_then(|transport| transport.client_addr(client_id)).map(|address| address.ip());
+                    if ban_list.is_banned(address, identity){
+                        info!("Banned client {} ({}, {:?}) tried to join", name, client_id, address);
+                        kick_client(&mut server, &mut kicks, client_id.raw(), "banned", time.elapsed_seconds());
+                        continue;
+                    }
+                    
+                    // todo: check color!
+                    
+                    /* SPAWN */
+                    let object_id = cfg.new_id();
+
+                    let for_spawn_cl_data = ClientData::for_spawn(style, color, object_id);
+
+                    let pos = get_ship_spawn_pos(&index, &mut cfg, &safe_zone, &mut rng.0).extend(0.);
+
+                    let entity = spawn_ship(false, &mut meshes, &mut materials, &mut commands, &for_spawn_cl_data, &mut cfg, &time);
+                    commands.entity(entity).insert(Transform::from_translation(pos));
+                    let new_client_data = ClientData { 
+                        client_id: client_id.raw(),
+                        object_id: object_id,
+                        entity: entity,
+                        style: style,
+                        color: color, 
+                        name: name.to_string(),
+                        kills: 0,
+                        deaths: 0,
+                    };
+                    clients_data.add(new_client_data.clone());
+                    profiles.start_session(client_id.raw(), identity, &name, style, time.elapsed_seconds());
+                    println!("register new client with id {}", client_id);
+
+                    // SEND DATA TO CONNECTED PLAYER
+                    let mut cfg_clone = cfg.clone();
+                    cfg_clone.debug_render = false;
+                    let msg = Message::OnConnect{
+                        clients_data: clients_data.clone(),
+                        ship_object_id: object_id,
+                        config: cfg_clone
+                    };
+                    let encoded: Vec<u8> = bincode::serialize(&msg).unwrap();
+                    server.send_message(client_id, ServerChannel::Garanteed, encoded);
+
+                    // SEND CONNECTION MESSAGE TO ALL
+                    let msg = Message::NewConnection {client_data: new_client_data};
+                    let encoded: Vec<u8> = bincode::serialize(&msg).unwrap();
+                    server.broadcast_message(ServerChannel::Garanteed, encoded);
+                    profiles::send_profile(&mut server, &profiles, client_id.raw());
+                }
+                Message::ProfileRequest {} => {
+                    profiles::send_profile(&mut server, &profiles, client_id.raw());
+                }
+                msg_type => {
+                    warn!("Unhandled message recived on server!");
+                }
+            }
+        }
+    }
+}
+
+
+fn handle_events_system(
+    mut server_events: EventReader<ServerEvent>,
+    mut server: ResMut<RenetServer>,
+    #[cfg(feature = "dev-tools")]
+    mut visualizer: ResMut<RenetServerVisualizer<200>>,
+    mut clients_data: ResMut<ClientsData>,
+    mut commands: Commands,
+    //mut cfg: ResMut<GlobalConfig>,
+    //transport: Res<NetcodeServerTransport>,
+    //mut meshes: ResMut<Assets<Mesh>>,
+    //mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    for event in server_events.read() {
+        //println!("{:?}", event);
+        match event {
+            ServerEvent::ClientConnected { client_id } => {
+                // ADD CLIENT TO SERVER DB
+                #[cfg(feature = "dev-tools")]
+                visualizer.add_client(*client_id);
+                println!("New client with id {} connected", client_id);
+                let encoded: Vec<u8> = bincode::serialize(&Message::Greeteng {}).unwrap();
+                server.send_message(*client_id, ServerChannel::Garanteed, encoded);
+            }
+            ServerEvent::ClientDisconnected { client_id, reason } => {
+                #[cfg(feature = "dev-tools")]
+                visualizer.remove_client(*client_id);
+                println!("Client {client_id} disconnected: {reason}");
+                let data = clients_data.get_option_by_client_id(&client_id.raw());
+                if data.is_some(){
+                    commands.entity(data.unwrap().entity).despawn_recursive();
+                }
+                clients_data.remove_by_client_id(client_id.raw()); // todo: add reconnection (may be hard, but if use unique u64 for every client, possible)
+                let msg = Message::NewDisconnection { id: client_id.raw()};
+                let encoded: Vec<u8> = bincode::serialize(&msg).unwrap();
+                
+                server.broadcast_message_except(*client_id, ServerChannel::Garanteed, encoded);
+            }
+        }
+    }
+}
+
+#[derive(Resource, Default)]
+struct TickTimer{
+    start: Option<Instant>,
+    total_ms: f32,
+    max_ms: f32,
+    ticks: u32,
+    last_report: f32,
+}
+
+fn tick_timer_start(
+    mut timer: ResMut<TickTimer>,
+){
+    timer.start = Some(Instant::now());
+}
+
+/// Measures time of every frame and sends averages to clients once per second
+fn tick_timer_end(
+    mut timer: ResMut<TickTimer>,
+    mut server: ResMut<RenetServer>,
+    objects_q: Query<(), (With<Object>, Without<Puppet>)>,
+    time: Res<Time>,
+){
+    let Some(start) = timer.start else {return;};
+    let tick_ms = start.elapsed().as_secs_f32() * 1000.;
+    timer.total_ms += tick_ms;
+    timer.max_ms = timer.max_ms.max(tick_ms);
+    timer.ticks += 1;
+    if time.elapsed_seconds() - timer.last_report < 1. {return;}
+    let msg = Message::ServerStats{
+        avg_tick_ms: timer.total_ms / timer.ticks as f32,
+        max_tick_ms: timer.max_ms,
+        objects: objects_q.iter().len() as u32,
+        clients: server.clients_id().len() as u32,
+    };
+    let encoded: Vec<u8> = bincode::serialize(&msg).unwrap();
+    server.broadcast_message(ServerChannel::Garanteed, encoded);
+    *timer = TickTimer{last_report: time.elapsed_seconds(), ..default()};
+}
+
+/// Time of system groups in FixedUpdate, averaged per tick over last second (dashboard)
+#[derive(Resource, Default)]
+pub struct SystemTimings{
+    last_mark: Option<Instant>,
+    window_start: Option<Instant>,
+    ticks: u32,
+    total_ms: Vec<(&'static str, f32)>, // in order of marks
+    pub average_ms: Vec<(&'static str, f32)>,
+}
+
+impl SystemTimings{
+    fn mark(&mut self, label: &'static str){ // time since previous mark goes to `label`
+        let Some(last) = self.last_mark else {return;};
+        let ms = last.elapsed().as_secs_f32() * 1000.;
+        match self.total_ms.iter_mut().find(|(l, _)| *l == label) {
+            Some((_, total)) => {*total += ms}
+            None => {self.total_ms.push((label, ms))}
+        }
+        self.last_mark = Some(Instant::now());
+    }
+}
+
+fn timing_start(
+    mut timings: ResMut<SystemTimings>,
+){
+    let window_start = *timings.window_start.get_or_insert_with(Instant::now);
+    if window_start.elapsed().as_secs_f32() >= 1. && timings.ticks > 0{
+        let ticks = timings.ticks as f32;
+        timings.average_ms = timings.total_ms.iter().map(|(label, total)| (*label, total / ticks)).collect();
+        timings.total_ms.clear();
+        timings.ticks = 0;
+        timings.window_start = Some(Instant::now());
+    }
+    timings.ticks += 1;
+    timings.last_mark = Some(Instant::now());
+}
+
+fn timing_mark(label: &'static str) -> impl FnMut(ResMut<SystemTimings>){
+    move |mut timings| timings.mark(label)
+}
+
+/// Addresses and profile identities that are kicked right after registration
+#[derive(Resource, Default)]
+pub struct BanList{
+    pub addresses: HashSet<IpAddr>,
+    pub identities: HashSet<u64>,
+    pub names: Vec<String>, // only shown on dashboard, names are not checked
+}
+
+impl BanList{
+    pub fn is_banned(&self, address: Option<IpAddr>, identity: u64) -> bool{
+        address.is_some_and(|address| self.addresses.contains(&address)) || self.identities.contains(&identity)
+    }
+}
+
+/// From dashboard buttons and console
+#[derive(Event)]
+pub enum AdminCommand{
+    Kick{client_id: u64},
+    Ban{client_id: u64},
+}
+
+fn admin_commands_system(
+    mut reader: EventReader<AdminCommand>,
+    mut server: ResMut<RenetServer>,
+    mut clients_data: ResMut<ClientsData>,
+    mut botlist: ResMut<BotList>,
+    mut ban_list: ResMut<BanList>,
+    mut kicks: ResMut<PendingKicks>,
+    transport: Option<Res<NetcodeServerTransport>>, // tests have no sockets
+    profiles: Res<profiles::ProfileStore>,
+    mut commands: Commands,
+    time: Res<Time>,
+){
+    for command in reader.read(){
+        let client_id = match command {
+            AdminCommand::Kick{client_id} => *client_id,
+            AdminCommand::Ban{client_id} => {
+                if let Some(data) = clients_data.get_option_by_client_id(client_id){
+                    let address = transport.as_ref().and_then(|transport| transport.client_addr(ClientId::from_raw(*client_id)));
+                    info!("Banned {} ({:?})", data.name, address);
+                    ban_list.addresses.extend(address.map(|address| address.ip()));
+                    ban_list.identities.extend(profiles.identity_of(*client_id));
+                    ban_list.names.push(data.name.clone());
+                }
+                *client_id
+            }
+        };
+        if botlist.get_bots_client_ids().contains(&client_id){ // bot has no connection, remove it like disconnected client
+            if let Some(data) = clients_data.get_option_by_client_id(&client_id){
+                commands.entity(data.entity).despawn_recursive();
+            }
+            botlist.unregister_bot(&client_id);
+            clients_data.remove_by_client_id(client_id);
+            let encoded: Vec<u8> = bincode::serialize(&Message::NewDisconnection{id: client_id}).unwrap();
+            server.broadcast_message(ServerChannel::Garanteed, encoded);
+        } else {
+            kick_client(&mut server, &mut kicks, client_id, "kicked by admin", time.elapsed_seconds());
+        }
+    }
+}
+
+/// What happens when countdown of /shutdown, /restart or /changemap ends
+#[derive(Clone)]
+pub enum ServerAction{
+    Shutdown,
+    Restart,
+    ChangeMap(MapFile),
+}
+
+pub struct PendingAction{
+    pub action: ServerAction,
+    pub reason: String,
+    pub at: f32, // elapsed seconds
+    warned_secs: Option<u32>,
+    clients_told_at: Option<f32>,
+}
+
+#[derive(Resource, Default)]
+pub struct PendingServerAction(pub Option<PendingAction>);
+
+impl PendingServerAction{
+    pub fn schedule(&mut self, action: ServerAction, delay_secs: f32, reason: String, now: f32){
+        self.0 = Some(PendingAction{action, reason, at: now + delay_secs, warned_secs: None, clients_told_at: None});
+    }
+}
+
+/// Sent right before clients are dropped by shutdown, restart or map change, persistent stats are saved on it
+#[derive(Event)]
+pub struct MatchEnded;
+
+/// Countdown in chat, then Kick (shutdown) or MapChange (clients reconnect), then connections are dropped
+fn pending_server_action_system(
+    mut pending: ResMut<PendingServerAction>,
+    mut server: ResMut<RenetServer>,
+    mut cfg: ResMut<GlobalConfig>,
+    mut next_state: ResMut<NextState<ServerState>>,
+    mut match_ended: EventWriter<MatchEnded>,
+    mut exit: EventWriter<bevy::app::AppExit>,
+    time: Res<Time>,
+){
+    let Some(current) = pending.0.as_mut() else {return;};
+    let now = time.elapsed_seconds();
+    let verb = match current.action {
+        ServerAction::Shutdown => "SHUTDOWN",
+        ServerAction::Restart => "RESTART",
+        ServerAction::ChangeMap(_) => "MAP CHANGE",
+    };
+    let Some(told_at) = current.clients_told_at else {
+        let left = current.at - now;
+        if left > 0. {
+            let secs = left.ceil() as u32;
+            if current.warned_secs != Some(secs) && (secs <= 5 || secs % 10 == 0 || current.warned_secs.is_none()){
+                current.warned_secs = Some(secs);
+                let message = format!("SERVER {} IN {}s: {}", verb, secs, current.reason);
+                println!("{}", message);
+                let encoded: Vec<u8> = bincode::serialize(&Message::ChatMessage{sender_id: 0, message}).unwrap();
+                server.broadcast_message(ServerChannel::Garanteed, encoded);
+            }
+            return;
+        }
+        let msg = match current.action {
+            ServerAction::Shutdown => Message::Kick{reason: current.reason.clone()},
+            _ => Message::MapChange{reason: current.reason.clone()},
+        };
+        server.broadcast_message(ServerChannel::Garanteed, bincode::serialize(&msg).unwrap());
+        match_ended.send(MatchEnded);
+        current.clients_told_at = Some(now);
+        return;
+    };
+    if now - told_at < DISCONNECT_DELAY_SECS {return;}
+    server.disconnect_all();
+    println!("SERVER {}", verb);
+    match pending.0.take().unwrap().action {
+        ServerAction::Shutdown => {exit.send(bevy::app::AppExit);}
+        ServerAction::Restart => {next_state.set(ServerState::Restarting);}
+        ServerAction::ChangeMap(map) => {
+            println!("LOADED MAP {}", map.name);
+            cfg.apply_map(&map);
+            next_state.set(ServerState::Restarting);
+        }
+    }
+}
+
+/// Removes everything of previous match, setup_world fills it again when Running is entered
+fn reset_world(
+    mut commands: Commands,
+    objects_q: Query<Entity, With<Object>>,
+    mut loaded_chunks: ResMut<LoadedChunks>,
+    mut next_state: ResMut<NextState<ServerState>>,
+){
+    for entity in objects_q.iter(){ // puppets are objects too
+        commands.entity(entity).despawn_recursive();
+    }
+    loaded_chunks.chunks.clear();
+    commands.insert_resource(ClientsData::default());
+    commands.insert_resource(BotList::default());
+    commands.insert_resource(SafeZone::default());
+    commands.insert_resource(InterestManager::default());
+    commands.insert_resource(LatestInputs::default());
+    commands.insert_resource(ShipHistory::default());
+    commands.insert_resource(SpawnPointsState::default()); // old pickup ids and slots of previous map
+    commands.insert_resource(PendingKicks::default()); // everyone is disconnected already
+    next_state.set(ServerState::Running);
+}
+
+/// Time for last messages to leave before connections are dropped
+const DISCONNECT_DELAY_SECS: f32 = 0.5;
+
+/// Kicked clients, client_id -> when to disconnect them
+#[derive(Resource, Default)]
+pub struct PendingKicks(HashMap<u64, f32>);
+
+/// Sends reason now and disconnects a bit later, renet drops messages queued for disconnected client.
+/// ClientDisconnected event cleans up as usual
+fn kick_client(server: &mut RenetServer, kicks: &mut PendingKicks, client_id: u64, reason: &str, now: f32){
+    if kicks.0.contains_key(&client_id) {return;}
+    let encoded: Vec<u8> = bincode::serialize(&Message::Kick{reason: reason.into()}).unwrap();
+    server.send_message(ClientId::from_raw(client_id), ServerChannel::Garanteed, encoded);
+    kicks.0.insert(client_id, now + DISCONNECT_DELAY_SECS);
+}
+
+fn pending_kicks_system(
+    mut kicks: ResMut<PendingKicks>,
+    mut server: ResMut<RenetServer>,
+    time: Res<Time>,
+){
+    let now = time.elapsed_seconds();
+    kicks.0.retain(|client_id, at| {
+        if now < *at {return true;}
+        server.disconnect(ClientId::from_raw(*client_id));
+        false
+    });
+}
+
+/// Shrinks safe zone with time, damages ships outside of it and sends it to clients
+fn update_safe_zone(
+    mut safe_zone: ResMut<SafeZone>,
+    mut ships_q: Query<(Entity, &Object, &Transform, &ShipState, &mut ShipStatuses), (With<Ship>, Without<Puppet>)>,
+    mut destroyed_writer: EventWriter<ShipDestroyed>,
+    mut server: ResMut<RenetServer>,
+    mut last_sent: Local<f32>,
+    cfg: Res<GlobalConfig>,
+    time: Res<Time>,
+    mut commands: Commands,
+){
+    let MapTopology::ShrinkingZone { start_delay_secs, shrink_secs, min_radius, damage_per_sec } = cfg.topology else {
+        *safe_zone = SafeZone::default();
+        return;
+    };
+    let current_time = time.elapsed_seconds();
+    let start_time = *safe_zone.started_at.get_or_insert(current_time); // reset_world inserts new zone
+    let world_size = cfg.map_size_chunks * cfg.single_chunk_size;
+    let max_radius = world_size.length() / 2.;
+    let progress = ((current_time - start_time - start_delay_secs) / shrink_secs.max(0.001)).clamp(0., 1.);
+    *safe_zone = SafeZone{
+        center: world_size / 2.,
+        radius: max_radius + (min_radius.min(max_radius) - max_radius) * progress,
+        started_at: Some(start_time),
+    };
+    for (entity, object, transform, state, mut statuses) in ships_q.iter_mut(){
+        if let ShipState::Dead { .. } = state {continue;}
+        if safe_zone.contains(transform.translation.truncate()) {continue;}
+        if damage_ship(entity, object, &mut statuses, damage_per_sec * time.delta_seconds(), &time, &mut commands){
+            destroyed_writer.send(ShipDestroyed{victim: object.id, killer: None});
+        }
+    }
+    if current_time - *last_sent > 0.25{
+        *last_sent = current_time;
+        let encoded: Vec<u8> = bincode::serialize(&Message::SafeZone{zone: *safe_zone}).unwrap();
+        server.broadcast_message(ServerChannel::Fast, encoded);
+    }
+}
+
+fn state_and_status_checker(
+    mut ships_q: Query<(&mut Object, &ShipState, &LastDamageTaken), (With<Ship>, Without<Puppet>)>,
+    mut status_q: Query<&mut ShipStatuses, Without<Puppet>>,
+    time: Res<Time>,
+    mut commands: Commands,
+    index: Res<SpatialIndex>,
+    cfg: ResMut<GlobalConfig>,
+    clients_data: ResMut<ClientsData>,
+    mut rng: ResMut<GameRng>,
+    safe_zone: Res<SafeZone>,
+){
+    for (mut object, state, last_damage_taken) in ships_q.iter_mut(){
+        match *state{
+            ShipState::Dead { time: death_time } => {
+                if death_time < cfg.respawn_time_secs{
+                    let client_data = clients_data.get_option_by_object_id(object.id);
+                    if client_data.is_some(){
+                        commands.entity(client_data.unwrap().entity).insert(ShipState::Dead { time: death_time + time.delta_seconds() });
+                    }
+                } else { // respawn
+                    let pos = get_ship_spawn_pos(&index, &cfg, &safe_zone, &mut rng.0);
+                    let mut respawned_object = object.clone();
+                    match object.object_type{
+                        ObjectType::Ship { style, color, shields: _, hp: _ } => {
+                            respawned_object.object_type = ObjectType::Ship{
+                                style,
+                                color,
+                                shields: cfg.player_shields,
+                                hp: cfg.player_hp,
+                            };
+                            let client_data = clients_data.get_option_by_object_id(object.id);
+                            client_data.is_some().then(||{
+                                commands.entity(client_data.unwrap().entity).insert((
+                                    ShipState::Regular,
+                                    respawned_object,
+                                    ShipStatuses::on_spawn(&cfg),
+                                    ShipWeapon::new(WeaponType::Blaster, &cfg),
+                                    Transform::from_translation(pos.extend(0.))
+                                ));
+                                commands.entity(client_data.unwrap().entity).remove::<ColliderDisabled>();
+                            });
+                        }
+                        _ => {}
+                    }
+                }
+            },
+            _ => {}
+        }
+        if last_damage_taken.time + cfg.shield_recharge_delay < time.elapsed_seconds(){
+            match object.object_type{
+                ObjectType::Ship { style, color, shields, hp } => {
+                    object.object_type = ObjectType::Ship { style, color, shields: (shields + time.delta_seconds() * cfg.shield_recharge_per_sec).clamp(0., cfg.player_shields), hp };
+                }
+                _ => {}
+            }
+        }
+    }
+    for mut ship_status in status_q.iter_mut(){
+        let current = &mut ship_status.current;
+        let mut to_remove = vec![];
+        for (status_type, status_effect) in current.iter_mut() {
+            status_effect.seconds -= time.delta_seconds();
+            if status_effect.seconds <= 0. {
+                to_remove.push(status_type.clone());
+            };
+            if status_effect.value <= 0. {
+                to_remove.push(status_type.clone());
+            };
+        }
+        for t in to_remove{current.remove(&t);}; 
+    } 
+}
+
+fn count_kills(
+    mut destroyed_reader: EventReader<ShipDestroyed>,
+    mut clients_data: ResMut<ClientsData>,
+){
+    for event in destroyed_reader.read(){
+        let victim = clients_data.get_option_by_object_id(event.victim).map(|data| data.client_id);
+        if victim.is_some(){
+            clients_data.get_mut_by_client_id(victim.unwrap()).deaths += 1;
+        }
+        if event.killer.is_none(){continue;}
+        let killer = clients_data.get_option_by_object_id(event.killer.unwrap()).map(|data| data.client_id);
+        if killer.is_some(){
+            clients_data.get_mut_by_client_id(killer.unwrap()).kills += 1;
+        }
+    }
+}
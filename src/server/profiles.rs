@@ -0,0 +1,200 @@
+use std::time::SystemTime;
+use bevy::{prelude::*, utils::hashbrown::HashMap};
+use bevy_renet::renet::{ClientId, RenetServer, ServerEvent};
+use serde::{Serialize, Deserialize};
+
+use crate::shared::*;
+use super::MatchEnded;
+
+/*
+    PLAYER PROFILES
+    lifetime stats by client identity, one JSON file:
+    server --profiles profiles.json
+    without a file (tests) profiles live until server stops
+    server issues identity with random secret on first connect, client without matching secret gets new profile
+    profile is saved only once it has stats, so reconnects and load tests don't fill the file
+    server --no-profiles keeps everything in memory
+*/
+
+const AUTOSAVE_SECS: f32 = 60.;
+
+/// Connected client whose time is not yet added to profile
+struct Session{
+    identity: u64,
+    style: u8,
+    counted_until: f32, // elapsed seconds
+}
+
+/// Profile with the secret of its identity, secret is never sent with profile
+#[derive(Serialize, Deserialize, Default)]
+struct StoredProfile{
+    secret: u64,
+    #[serde(flatten)]
+    profile: PlayerProfile,
+}
+
+#[derive(Resource, Default)]
+pub struct ProfileStore{
+    pub path: Option<String>,
+    profiles: HashMap<u64, StoredProfile>, // identity -> profile
+    sessions: HashMap<u64, Session>, // client_id -> session
+    dirty: bool,
+    last_save: f32,
+}
+
+impl ProfileStore{
+    /// Empty store if file doesn't exist yet
+    pub fn load(path: &str) -> Result<Self, String>{
+        let profiles = match std::fs::read_to_string(path) {
+            Ok(data) => serde_json::from_str(&data).map_err(|e| e.to_string())?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => HashMap::new(),
+            Err(e) => return Err(e.to_string()),
+        };
+        Ok(ProfileStore{path: Some(path.into()), profiles, ..default()})
+    }
+
+    pub fn save(&mut self) -> Result<(), String>{
+        self.dirty = false;
+        let Some(path) = self.path.as_ref() else {return Ok(());};
+        let kept: HashMap<&u64, &StoredProfile> = self.profiles.iter().filter(|(_, stored)| stored.profile.has_stats()).collect();
+        let data = serde_json::to_string_pretty(&kept).map_err(|e| e.to_string())?;
+        let tmp = format!("{}.tmp", path); // whole file is replaced, so crash while writing keeps old one
+        std::fs::write(&tmp, data).map_err(|e| e.to_string())?;
+        std::fs::rename(&tmp, path).map_err(|e| e.to_string())
+    }
+
+    /// Identity of registering client and new credentials if it has to get them
+    pub fn login(&mut self, credentials: Option<Credentials>) -> (u64, Option<Credentials>){
+        if let Some(credentials) = credentials{
+            if self.profiles.get(&credentials.identity).is_some_and(|stored| stored.secret == credentials.secret){
+                return (credentials.identity, None);
+            }
+        }
+        let mut identity = rand::random::<u64>(); // not GameRng, world seed must not tell secrets
+        while self.profiles.contains_key(&identity) {identity = rand::random::<u64>();}
+        let credentials = Credentials{identity, secret: rand::random::<u64>()};
+        self.profiles.insert(identity, StoredProfile{secret: credentials.secret, ..default()}); // saved with first stats
+        (identity, Some(credentials))
+    }
+
+    pub fn profiles(&self) -> impl Iterator<Item = &PlayerProfile>{
+        self.profiles.values().map(|stored| &stored.profile)
+    }
+
+    /// Identity must come from login
+    pub fn start_session(&mut self, client_id: u64, identity: u64, name: &str, style: u8, now: f32){
+        let profile = &mut self.profiles.entry(identity).or_default().profile;
+        profile.name = name.into();
+        profile.last_seen = unix_secs();
+        self.sessions.insert(client_id, Session{identity, style, counted_until: now});
+        self.dirty = true;
+    }
+
+    pub fn end_session(&mut self, client_id: u64, now: f32){
+        self.count_playtime(now);
+        self.sessions.remove(&client_id);
+    }
+
+    pub fn get_mut(&mut self, client_id: u64) -> Option<&mut PlayerProfile>{
+        let identity = self.sessions.get(&client_id)?.identity;
+        self.dirty = true;
+        self.profiles.get_mut(&identity).map(|stored| &mut stored.profile)
+    }
+
+    pub fn identity_of(&self, client_id: u64) -> Option<u64>{
+        self.sessions.get(&client_id).map(|session| session.identity)
+    }
+
+    pub fn get(&self, client_id: u64) -> Option<&PlayerProfile>{
+        self.profiles.get(&self.sessions.get(&client_id)?.identity).map(|stored| &stored.profile)
+    }
+
+    fn count_playtime(&mut self, now: f32){
+        for session in self.sessions.values_mut(){
+            let secs = now - session.counted_until;
+            session.counted_until = now;
+            let Some(profile) = self.profiles.get_mut(&session.identity).map(|stored| &mut stored.profile) else {continue;};
+            profile.playtime_secs += secs;
+            *profile.style_secs.entry(session.style).or_default() += secs;
+            profile.last_seen = unix_secs();
+            self.dirty = true;
+        }
+    }
+}
+
+fn unix_secs() -> u64{
+    SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+pub fn init_profiles(app: &mut App){
+    app.init_resource::<ProfileStore>(); // server binary inserts one with file before plugin
+    app.add_event::<ShotFired>();
+    app.add_event::<ShotHit>();
+    app.add_systems(Update, (profile_events_system, save_profiles_system).chain().after(super::handle_events_system));
+}
+
+/// Kills, deaths, shots and hits of connected clients, sessions end on disconnect.
+/// Shots and hits are both events sent by the weapon code, so every hit has its shot
+fn profile_events_system(
+    mut store: ResMut<ProfileStore>,
+    mut server_events: EventReader<ServerEvent>,
+    mut destroyed_reader: EventReader<ShipDestroyed>,
+    mut shot_reader: EventReader<ShotFired>,
+    mut hit_reader: EventReader<ShotHit>,
+    clients_data: Res<ClientsData>,
+    time: Res<Time>,
+){
+    let client_of = |object_id: u64| clients_data.get_option_by_object_id(object_id).map(|data| data.client_id);
+    for event in destroyed_reader.read(){
+        if let Some(profile) = client_of(event.victim).and_then(|client_id| store.get_mut(client_id)){
+            profile.deaths += 1;
+        }
+        if let Some(profile) = event.killer.and_then(client_of).and_then(|client_id| store.get_mut(client_id)){
+            profile.kills += 1;
+        }
+    }
+    for event in hit_reader.read(){
+        if let Some(profile) = client_of(event.owner).and_then(|client_id| store.get_mut(client_id)){
+            profile.hits += 1;
+        }
+    }
+    for event in shot_reader.read(){
+        if let Some(profile) = client_of(event.owner).and_then(|client_id| store.get_mut(client_id)){
+            profile.shots += 1;
+        }
+    }
+    for event in server_events.read(){
+        if let ServerEvent::ClientDisconnected{client_id, ..} = event{
+            store.end_session(client_id.raw(), time.elapsed_seconds());
+        }
+    }
+}
+
+fn save_profiles_system(
+    mut store: ResMut<ProfileStore>,
+    mut match_ended: EventReader<MatchEnded>,
+    time: Res<Time>,
+){
+    let now = time.elapsed_seconds();
+    let ended = match_ended.read().count() > 0;
+    if !ended && now - store.last_save < AUTOSAVE_SECS {return;}
+    store.last_save = now;
+    store.count_playtime(now);
+    if !store.dirty {return;}
+    if let Err(e) = store.save(){
+        warn!("Can't save profiles: {}", e);
+    }
+}
+
+/// Sent once, client keeps them for this server address
+pub fn send_credentials(server: &mut RenetServer, credentials: Credentials, client_id: u64){
+    let encoded: Vec<u8> = bincode::serialize(&Message::Credentials{credentials}).unwrap();
+    server.send_message(ClientId::from_raw(client_id), ServerChannel::Garanteed, encoded);
+}
+
+/// Sends lifetime stats to its owner only
+pub fn send_profile(server: &mut RenetServer, store: &ProfileStore, client_id: u64){
+    let Some(profile) = store.get(client_id) else {return;};
+    let encoded: Vec<u8> = bincode::serialize(&Message::Profile{profile: profile.clone()}).unwrap();
+    server.send_message(ClientId::from_raw(client_id), ServerChannel::Garanteed, encoded);
+}
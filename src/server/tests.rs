@@ -0,0 +1,574 @@
+use std::time::Duration;
+
+use bevy::{ecs::system::RunSystemOnce, time::TimeUpdateStrategy};
+use bevy_renet::RenetClientPlugin;
+
+use super::*;
+
+/*
+    INTEGRATION TESTS
+    server App and headless client Apps in one process, packets are passed in memory,
+    every App is stepped with fixed frame time so runs are repeatable
+
+    cargo test --lib
+*/
+
+const SEED: u64 = 42;
+
+fn frame() -> Duration{
+    Duration::from_secs_f64(1. / 60.)
+}
+
+/// What headless client got from server and what it sends back
+#[derive(Resource, Default)]
+struct ClientLog{
+    name: String,
+    ship_object_id: Option<u64>,
+    last_tick: u64,
+    snapshots: u32,
+    objects: HashMap<u64, ObjectData>, // latest known state of replicated objects
+    disconnections: Vec<u64>,
+    notices: Vec<String>, // chat from server, kick reasons and map changes
+    credentials: Option<Credentials>, // given by server on first registration
+    profile: Option<PlayerProfile>,
+    inputs: Option<InputKeys>, // sent every frame after OnConnect
+}
+
+fn test_client_system(
+    mut client: ResMut<RenetClient>,
+    mut log: ResMut<ClientLog>,
+){
+    while let Some(message) = client.receive_message(ServerChannel::Garanteed){
+        match bincode::deserialize::<Message>(&message).unwrap() {
+            Message::Greeteng {} => {
+                let msg = Message::RegisterClient{style: 0, color: Color::WHITE, name: log.name.clone(), credentials: log.credentials};
+                client.send_message(ClientChannel::Garanteed, bincode::serialize(&msg).unwrap());
+            }
+            Message::OnConnect { ship_object_id, .. } => {
+                log.ship_object_id = Some(ship_object_id);
+            }
+            Message::NewDisconnection { id } => {
+                log.disconnections.push(id);
+            }
+            Message::ChatMessage { message, .. } | Message::Kick { reason: message } | Message::MapChange { reason: message } => {
+                log.notices.push(message);
+            }
+            Message::Profile { profile } => {
+                log.profile = Some(profile);
+            }
+            Message::Credentials { credentials } => {
+                log.credentials = Some(credentials);
+            }
+            _ => {}
+        }
+    }
+    while let Some(message) = client.receive_message(ServerChannel::Fast){
+        let Ok(Message::Update { tick, data, keep, .. }) = bincode::deserialize::<Message>(&message) else {continue;};
+        if tick < log.last_tick {continue;} // fast channel is unordered
+        log.last_tick = tick;
+        log.snapshots += 1;
+        let mut objects: HashMap<u64, ObjectData> = keep.iter().filter_map(|id| log.objects.remove(id).map(|data| (*id, data))).collect();
+        for object_data in data{
+            objects.insert(object_data.object.id, object_data);
+        }
+        log.objects = objects;
+    }
+    if log.ship_object_id.is_none() {return;}
+    if let Some(mut inputs) = log.inputs.clone(){
+        inputs.acked_tick = log.last_tick;
+        client.send_message(ClientChannel::Fast, bincode::serialize(&Message::Inputs{inputs}).unwrap());
+    }
+}
+
+/// Server and clients connected with in-memory transport
+struct TestWorld{
+    server: App,
+    clients: Vec<(u64, App)>, // client_id, app
+}
+
+impl TestWorld{
+    fn new() -> Self{
+        Self::with_config(|_| {})
+    }
+
+    fn with_topology(topology: MapTopology) -> Self{
+        Self::with_config(|cfg| cfg.topology = topology)
+    }
+
+    /// Test config changed before server starts
+    fn with_config(edit: impl FnOnce(&mut GlobalConfig)) -> Self{
+        let mut server = App::new();
+        server.add_plugins((MinimalPlugins, HeadlessPlugin));
+        server.insert_resource(TimeUpdateStrategy::ManualDuration(frame()));
+        let mut cfg = GlobalConfig{
+            map_size_chunks: Vec2{x: 3., y: 2.},
+            single_chunk_size: Vec2{x: 1000., y: 1000.},
+            world_seed: SEED,
+            respawn_time_secs: 1.,
+            ..default()
+        };
+        edit(&mut cfg);
+        server.add_plugins(ServerPlugin{config: cfg});
+        server.insert_resource(RenetServer::new(connection_config()));
+        server.finish();
+        server.cleanup();
+        server.update();
+        TestWorld{server, clients: vec![]}
+    }
+
+    /// Adds client, it registers by itself when server greets it
+    fn connect(&mut self, name: &str) -> usize{
+        let client_id = 1000 + self.clients.len() as u64;
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, RenetClientPlugin));
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(frame()));
+        let mut client = RenetClient::new(connection_config());
+        client.set_connected();
+        app.insert_resource(client);
+        app.insert_resource(ClientLog{name: name.into(), ..default()});
+        app.add_systems(Update, test_client_system);
+        app.finish();
+        app.cleanup();
+        self.server.world.resource_mut::<RenetServer>().add_connection(ClientId::from_raw(client_id));
+        self.clients.push((client_id, app));
+        self.clients.len() - 1
+    }
+
+    fn disconnect(&mut self, n: usize){
+        let (client_id, _) = self.clients[n];
+        self.clients[n].1.world.resource_mut::<RenetClient>().disconnect();
+        self.server.world.resource_mut::<RenetServer>().remove_connection(ClientId::from_raw(client_id));
+    }
+
+    fn step(&mut self, frames: u32){
+        for _ in 0..frames{
+            self.server.update();
+            for (client_id, app) in self.clients.iter_mut(){
+                app.update();
+                let client_id = ClientId::from_raw(*client_id);
+                let mut server = self.server.world.resource_mut::<RenetServer>();
+                let mut client = app.world.resource_mut::<RenetClient>();
+                if client.is_disconnected() {continue;}
+                for packet in client.get_packets_to_send(){
+                    server.process_packet_from(&packet, client_id).unwrap();
+                }
+                let Ok(packets) = server.get_packets_to_send(client_id) else { // dropped by server
+                    client.disconnect();
+                    continue;
+                };
+                for packet in packets{
+                    client.process_packet(&packet);
+                }
+            }
+        }
+    }
+
+    /// Steps until condition is true, false if it didn't happen in time
+    fn step_until(&mut self, max_frames: u32, condition: impl Fn(&TestWorld) -> bool) -> bool{
+        for _ in 0..max_frames{
+            if condition(self) {return true;}
+            self.step(1);
+        }
+        condition(self)
+    }
+
+    fn log(&self, n: usize) -> &ClientLog{
+        self.clients[n].1.world.resource::<ClientLog>()
+    }
+
+    fn set_inputs(&mut self, n: usize, inputs: InputKeys){
+        self.clients[n].1.world.resource_mut::<ClientLog>().inputs = Some(inputs);
+    }
+
+    fn client_data(&self, n: usize) -> Option<&ClientData>{
+        self.server.world.resource::<ClientsData>().get_option_by_client_id(&self.clients[n].0)
+    }
+
+    fn connect_and_register(&mut self, name: &str) -> usize{
+        let n = self.connect(name);
+        assert!(self.step_until(120, |world| world.log(n).ship_object_id.is_some()), "{} didn't get OnConnect", name);
+        n
+    }
+
+    fn server_objects(&mut self) -> Vec<(Entity, Object, Vec2)>{
+        let mut objects_q = self.server.world.query_filtered::<(Entity, &Object, &Transform), Without<Puppet>>();
+        objects_q.iter(&self.server.world).map(|(entity, object, transform)| (entity, object.clone(), transform.translation.truncate())).collect()
+    }
+}
+
+fn aim_up(shoot: bool) -> InputKeys{
+    let mut inputs = InputKeys::default();
+    inputs.rotation_target = Vec2::Y;
+    inputs.shoot = shoot;
+    inputs
+}
+
+#[test]
+fn client_registers_and_gets_on_connect(){
+    let mut world = TestWorld::new();
+    let n = world.connect_and_register("TESTER");
+    let data = world.client_data(n).expect("client is not in ClientsData");
+    assert_eq!(data.name, "TESTER");
+    assert_eq!(Some(data.object_id), world.log(n).ship_object_id);
+    assert!(world.server.world.get_entity(data.entity).is_some());
+}
+
+#[test]
+fn snapshots_replicate_own_ship(){
+    let mut world = TestWorld::new();
+    let n = world.connect_and_register("TESTER");
+    let ship_id = world.log(n).ship_object_id.unwrap();
+    assert!(world.step_until(60, |world| world.log(n).snapshots >= 5));
+    let log = world.log(n);
+    assert!(log.last_tick > 0);
+    let ship = log.objects.get(&ship_id).expect("own ship is not replicated");
+    assert!(matches!(ship.object.object_type, ObjectType::Ship { .. }));
+
+    let entity = world.client_data(n).unwrap().entity;
+    let server_pos = world.server.world.get::<Transform>(entity).unwrap().translation.truncate();
+    assert!(server_pos.distance(ship.translation.truncate()) < 50., "replicated ship is far from server one");
+}
+
+#[test]
+fn shooting_spawns_replicated_bullets(){
+    let mut world = TestWorld::new();
+    let n = world.connect_and_register("TESTER");
+    let ship_id = world.log(n).ship_object_id.unwrap();
+    world.set_inputs(n, aim_up(true));
+    let own_bullet = |object: &Object| matches!(object.object_type, ObjectType::Bullet { owner, .. } if owner == ship_id);
+    assert!(world.step_until(60, |world| world.log(n).objects.values().any(|data| own_bullet(&data.object))), "no bullets in snapshots");
+    assert!(world.server_objects().iter().any(|(_, object, _)| own_bullet(object)));
+}
+
+#[test]
+fn asteroid_splits_when_destroyed(){
+    let mut world = TestWorld::new();
+    let n = world.connect_and_register("TESTER");
+    let ship_entity = world.client_data(n).unwrap().entity;
+    world.set_inputs(n, aim_up(false));
+    world.step(10);
+
+    // asteroid of size 2 without hp right in front of ship
+    let ship_pos = world.server.world.get::<Transform>(ship_entity).unwrap().translation;
+    let asteroid_pos = ship_pos + Vec3::Y * 150.;
+    let asteroid_id = world.server.world.run_system_once(move |
+        mut cfg: ResMut<GlobalConfig>,
+        mut meshes: ResMut<Assets<Mesh>>,
+        mut materials: ResMut<Assets<ColorMaterial>>,
+        mut commands: Commands,
+    | {
+        let seed = (0..).find(|seed| cfg.get_asteroid_size(*seed) == 2).unwrap();
+        let object_id = cfg.new_id();
+        spawn_asteroid(seed, Velocity::zero(), Transform::from_translation(asteroid_pos), &mut meshes, &mut materials, &mut commands, object_id, 0, &cfg);
+        object_id
+    });
+    world.step(2);
+    world.set_inputs(n, aim_up(true));
+
+    let exists = |world: &mut TestWorld, id: u64| world.server_objects().iter().any(|(_, object, _)| object.id == id);
+    let mut destroyed = false;
+    for _ in 0..120{
+        world.step(1);
+        if !exists(&mut world, asteroid_id) {destroyed = true; break;}
+    }
+    assert!(destroyed, "asteroid was not destroyed");
+    world.step(2);
+    let cfg = world.server.world.resource::<GlobalConfig>().clone();
+    let pieces = world.server_objects().iter().filter(|(_, object, pos)| {
+        matches!(object.object_type, ObjectType::Asteroid { seed, .. } if cfg.get_asteroid_size(seed) == 1) && pos.distance(asteroid_pos.truncate()) < 150.
+    }).count();
+    assert!(pieces >= 2, "asteroid split into {} pieces", pieces);
+    assert!(world.step_until(30, |world| !world.log(n).objects.contains_key(&asteroid_id)), "client still has destroyed asteroid");
+}
+
+#[test]
+fn ship_dies_and_respawns(){
+    let mut world = TestWorld::new();
+    let n = world.connect_and_register("TESTER");
+    let ship_id = world.log(n).ship_object_id.unwrap();
+    let entity = world.client_data(n).unwrap().entity;
+    world.step(5);
+
+    for _ in 0..3{ // shields first, then hp
+        world.server.world.run_system_once(move |
+            mut ships_q: Query<(&Object, &ShipState, &mut ShipStatuses)>,
+            mut destroyed_writer: EventWriter<ShipDestroyed>,
+            time: Res<Time>,
+            mut commands: Commands,
+        | {
+            let (object, state, mut statuses) = ships_q.get_mut(entity).unwrap();
+            if matches!(state, ShipState::Dead { .. }) {return;}
+            statuses.remove(PowerUPEffectKind::SpawnImmunity);
+            if damage_ship(entity, object, &mut statuses, 10000., &time, &mut commands){
+                destroyed_writer.send(ShipDestroyed{victim: object.id, killer: None});
+            }
+        });
+    }
+    let state = |world: &TestWorld| world.log(n).objects.get(&ship_id).and_then(|data| data.states_and_statuses.as_ref().map(|s| s.0));
+    assert!(world.step_until(30, |world| matches!(state(world), Some(ShipState::Dead { .. }))), "client didn't see death");
+    assert_eq!(world.client_data(n).unwrap().deaths, 1);
+
+    assert!(world.step_until(180, |world| matches!(state(world), Some(ShipState::Regular))), "ship didn't respawn");
+    let cfg = world.server.world.resource::<GlobalConfig>().clone();
+    match world.log(n).objects[&ship_id].object.object_type {
+        ObjectType::Ship { hp, .. } => assert_eq!(hp, cfg.player_hp),
+        _ => panic!("own object is not a ship"),
+    }
+}
+
+#[test]
+fn ship_stops_at_wall_without_window(){
+    let wall = MapObstacle::Wall{from: Vec2::new(1000., 0.), to: Vec2::new(1000., 2000.), thickness: 40.};
+    let mut world = TestWorld::with_config(|cfg| cfg.obstacles = vec![wall]);
+    let n = world.connect_and_register("TESTER");
+    let entity = world.client_data(n).unwrap().entity;
+    world.step(5);
+    world.server.world.run_system_once(move |mut ships_q: Query<(&mut Transform, &mut Velocity)>| {
+        let (mut transform, mut velocity) = ships_q.get_mut(entity).unwrap();
+        transform.translation = Vec3::new(1300., 1000., transform.translation.z);
+        velocity.linvel = Vec2::new(-1500., 0.); // would be far behind the wall in a second
+    });
+    world.step(60);
+    let x = world.server.world.get::<Transform>(entity).unwrap().translation.x;
+    assert!(x > 1000., "ship went through wall to {}", x);
+}
+
+#[test]
+fn disconnect_removes_client_and_ship(){
+    let mut world = TestWorld::new();
+    let first = world.connect_and_register("FIRST");
+    let second = world.connect_and_register("SECOND");
+    let first_id = world.clients[first].0;
+    let first_entity = world.client_data(first).unwrap().entity;
+    world.set_inputs(first, aim_up(false));
+    world.step(5);
+
+    world.disconnect(first);
+    assert!(world.step_until(30, |world| world.log(second).disconnections.contains(&first_id)), "other client wasn't told about disconnection");
+    assert!(world.client_data(first).is_none());
+    assert!(world.server.world.get_entity(first_entity).is_none(), "ship of disconnected client still exists");
+    assert!(!world.server.world.resource::<LatestInputs>().0.contains_key(&first_id));
+    assert!(world.client_data(second).is_some());
+}
+
+#[test]
+fn kicked_client_gets_reason_before_disconnect(){
+    let mut world = TestWorld::new();
+    let n = world.connect_and_register("TESTER");
+    let client_id = world.clients[n].0;
+    world.server.world.send_event(AdminCommand::Kick{client_id});
+    assert!(world.step_until(60, |world| world.log(n).notices.iter().any(|notice| notice == "kicked by admin")), "kick reason wasn't delivered");
+    assert!(world.step_until(60, |world| world.client_data(n).is_none()), "kicked client is still connected");
+}
+
+#[test]
+fn ban_follows_identity_not_name(){
+    let mut world = TestWorld::new();
+    let n = world.connect_and_register("TESTER");
+    let client_id = world.clients[n].0;
+    let credentials = world.log(n).credentials;
+    assert!(credentials.is_some(), "no credentials after first registration");
+    world.server.world.send_event(AdminCommand::Ban{client_id});
+    assert!(world.step_until(60, |world| world.client_data(n).is_none()), "banned client is still connected");
+
+    let renamed = world.connect("RENAMED");
+    world.clients[renamed].1.world.resource_mut::<ClientLog>().credentials = credentials;
+    assert!(world.step_until(60, |world| world.log(renamed).notices.iter().any(|notice| notice == "banned")), "new name got around the ban");
+    assert!(world.log(renamed).ship_object_id.is_none());
+
+    let namesake = world.connect_and_register("TESTER"); // other player with the same name
+    assert!(world.client_data(namesake).is_some());
+}
+
+#[test]
+fn restart_warns_clients_and_resets_world(){
+    let mut world = TestWorld::new();
+    let n = world.connect_and_register("TESTER");
+    let old_entity = world.client_data(n).unwrap().entity;
+    world.step(5);
+
+    let now = world.server.world.resource::<Time>().elapsed_seconds();
+    world.server.world.resource_mut::<PendingServerAction>().schedule(ServerAction::Restart, 2., "test restart".into(), now);
+    assert!(world.step_until(30, |world| world.log(n).notices.iter().any(|notice| notice.contains("RESTART IN 2s"))), "no countdown");
+    assert!(world.step_until(180, |world| world.log(n).notices.last().map(|notice| notice.as_str()) == Some("test restart")), "no MapChange");
+    assert!(world.step_until(60, |world| world.server.world.get_entity(old_entity).is_none()), "old ship still exists");
+    world.step(2);
+    assert_eq!(*world.server.world.resource::<State<ServerState>>().get(), ServerState::Running);
+    assert!(world.server.world.resource::<ClientsData>().iter().next().is_none());
+
+    let again = world.connect_and_register("AGAIN"); // same server keeps accepting clients
+    assert!(world.client_data(again).is_some());
+}
+
+#[test]
+fn restart_starts_shrinking_zone_again(){
+    let topology = MapTopology::ShrinkingZone{start_delay_secs: 0.5, shrink_secs: 0.5, min_radius: 100., damage_per_sec: 10.};
+    let mut world = TestWorld::with_topology(topology);
+    world.connect_and_register("TESTER");
+    assert!(world.step_until(120, |world| world.server.world.resource::<SafeZone>().radius <= 100.), "zone didn't shrink");
+
+    let now = world.server.world.resource::<Time>().elapsed_seconds();
+    world.server.world.resource_mut::<PendingServerAction>().schedule(ServerAction::Restart, 0., "test restart".into(), now);
+    assert!(world.step_until(120, |world| world.server.world.resource::<ClientsData>().iter().next().is_none()), "server didn't restart");
+    world.step(5);
+    let zone = *world.server.world.resource::<SafeZone>();
+    assert!(zone.radius > 100., "new match starts with shrunk zone");
+    world.step(20); // still in start delay
+    assert!(world.server.world.resource::<SafeZone>().radius > 100.);
+}
+
+#[test]
+fn restart_spawns_arena_pickups_right_away(){
+    let mut world = TestWorld::with_config(|cfg| {
+        let powerup = cfg.powerups[0].id.clone();
+        cfg.powerup_spawn_points = vec![PowerUPSpawnPoint{position: Vec2::new(1500., 1000.), powerup, respawn_secs: 1000.}];
+    });
+    let pickups = |world: &mut TestWorld| world.server_objects().into_iter().filter(|(_, object, _)| matches!(object.object_type, ObjectType::PickUP { .. })).map(|(e, _, _)| e).collect::<Vec<_>>();
+    world.step(5);
+    let old = pickups(&mut world);
+    assert_eq!(old.len(), 1, "no arena pickup at start");
+
+    let now = world.server.world.resource::<Time>().elapsed_seconds();
+    world.server.world.resource_mut::<PendingServerAction>().schedule(ServerAction::Restart, 0., "test restart".into(), now);
+    assert!(world.step_until(120, |world| world.server.world.get_entity(old[0]).is_none()), "server didn't restart");
+    world.step(5); // respawn_secs is far away, only a reset slot spawns this soon
+    assert_eq!(pickups(&mut world).len(), 1, "arena pickup waits for respawn after restart");
+}
+
+#[test]
+fn profile_keeps_stats_between_sessions(){
+    let mut world = TestWorld::new();
+    let n = world.connect_and_register("TESTER");
+    let credentials = world.log(n).credentials;
+    world.set_inputs(n, aim_up(true));
+    world.step(30);
+    world.set_inputs(n, aim_up(false));
+    let request = bincode::serialize(&Message::ProfileRequest{}).unwrap();
+    world.clients[n].1.world.resource_mut::<RenetClient>().send_message(ClientChannel::Garanteed, request);
+    assert!(world.step_until(30, |world| world.log(n).profile.as_ref().is_some_and(|profile| profile.shots > 0)), "shots are not counted");
+    world.disconnect(n);
+    world.step(5);
+
+    let again = world.connect("RENAMED");
+    world.clients[again].1.world.resource_mut::<ClientLog>().credentials = credentials; // same player, new client id
+    assert!(world.step_until(120, |world| world.log(again).profile.is_some()), "no profile after registering");
+    let profile = world.log(again).profile.as_ref().unwrap();
+    assert_eq!(profile.name, "RENAMED");
+    assert!(profile.shots > 0);
+    assert!(profile.playtime_secs > 0.);
+}
+
+#[test]
+fn identity_needs_secret_from_server(){
+    let mut world = TestWorld::new();
+    let n = world.connect_and_register("OWNER");
+    world.set_inputs(n, aim_up(true));
+    world.step(30);
+    world.set_inputs(n, aim_up(false));
+    let stolen = Credentials{secret: 0, ..world.log(n).credentials.unwrap()}; // identity is known, secret is not
+
+    let thief = world.connect("THIEF");
+    world.clients[thief].1.world.resource_mut::<ClientLog>().credentials = Some(stolen);
+    assert!(world.step_until(120, |world| world.log(thief).profile.is_some()), "no profile after registering");
+    assert_eq!(world.log(thief).profile.as_ref().unwrap().shots, 0, "got stats of other identity");
+    assert_ne!(world.log(thief).credentials.map(|c| c.identity), Some(stolen.identity), "kept identity without secret");
+}
+
+#[test]
+fn stats_documents_show_players(){
+    let mut world = TestWorld::new();
+    world.server.insert_resource(http::HttpStats::new()); // documents without a listener
+    world.connect_and_register("TESTER");
+    let document = |world: &TestWorld, path: &str| world.server.world.resource::<http::HttpStats>().document(path);
+    assert!(world.step_until(120, |world| document(world, "/scoreboard").is_some_and(|scoreboard| scoreboard.contains("TESTER"))), "documents are not refreshed");
+    let stats = world.server.world.resource::<http::HttpStats>();
+    let scoreboard: serde_json::Value = serde_json::from_str(&stats.document("/scoreboard").unwrap()).unwrap();
+    assert!(scoreboard.as_array().unwrap().iter().any(|player| player["name"] == "TESTER"), "player is not on scoreboard");
+    let status: serde_json::Value = serde_json::from_str(&stats.document("/status").unwrap()).unwrap();
+    assert_eq!(status["players"], 1);
+    assert!(stats.document("/leaderboard").is_some_and(|leaderboard| leaderboard.contains("\"kills\"")));
+    assert!(stats.document("/nothing").is_none());
+}
+
+#[test]
+fn lag_compensated_beam_hits_once_across_wrap_edge(){
+    let mut world = TestWorld::new();
+    let shooter = world.connect_and_register("SHOOTER");
+    let target = world.connect_and_register("TARGET");
+    let shooter_entity = world.client_data(shooter).unwrap().entity;
+    let target_entity = world.client_data(target).unwrap().entity;
+    world.server.world.run_system_once(move |
+        mut ships_q: Query<(&mut Transform, &mut Velocity, &mut ShipWeapon)>,
+        asteroids_q: Query<Entity, With<Asteroid>>,
+        cfg: Res<GlobalConfig>,
+        mut commands: Commands,
+    | {
+        for entity in asteroids_q.iter(){ // nothing between ships
+            commands.entity(entity).despawn_recursive();
+        }
+        let (mut transform, mut velocity, mut weapon) = ships_q.get_mut(shooter_entity).unwrap();
+        *transform = Transform::from_xyz(1500., 1900., transform.translation.z); // facing up, over the top edge
+        *velocity = Velocity::zero();
+        *weapon = ShipWeapon::new(WeaponType::Laser, &cfg);
+        let (mut transform, mut velocity, _) = ships_q.get_mut(target_entity).unwrap();
+        *transform = Transform::from_xyz(1500., 60., transform.translation.z); // its puppet is right above the edge
+        *velocity = Velocity::zero();
+    });
+    world.step(5); // history has new positions before rewinding into it
+    world.set_inputs(shooter, aim_up(true)); // acked ticks make it lag compensated
+    world.step(20);
+    world.set_inputs(shooter, aim_up(false));
+    world.step(2);
+
+    let client_id = world.clients[shooter].0;
+    let profile = world.server.world.resource::<profiles::ProfileStore>().get(client_id).unwrap().clone();
+    assert!(profile.hits > 0, "beam missed the ship across the edge");
+    assert_eq!(profile.hits, profile.shots, "ship was hit through its puppet and its history");
+}
+
+#[test]
+fn railgun_ray_is_resolved_at_fire_and_stops_at_wall(){
+    let wall = MapObstacle::Wall{from: Vec2::new(1000., 800.), to: Vec2::new(2000., 800.), thickness: 40.};
+    let mut world = TestWorld::with_config(|cfg| cfg.obstacles = vec![wall]);
+    let shooter = world.connect_and_register("SHOOTER");
+    let front = world.connect_and_register("FRONT");
+    let behind = world.connect_and_register("BEHIND");
+    let entities = [shooter, front, behind].map(|n| world.client_data(n).unwrap().entity);
+    world.server.world.run_system_once(move |
+        mut ships_q: Query<(&mut Transform, &mut Velocity, &mut ShipStatuses)>,
+        asteroids_q: Query<Entity, With<Asteroid>>,
+        mut commands: Commands,
+    | {
+        for entity in asteroids_q.iter(){
+            commands.entity(entity).despawn_recursive();
+        }
+        for (entity, y) in entities.into_iter().zip([500., 650., 1100.]){
+            let (mut transform, mut velocity, mut statuses) = ships_q.get_mut(entity).unwrap();
+            *transform = Transform::from_xyz(1500., y, transform.translation.z);
+            *velocity = Velocity::zero();
+            statuses.remove(PowerUPEffectKind::SpawnImmunity);
+        }
+    });
+    world.step(5);
+    let health = |world: &TestWorld, entity: Entity| match world.server.world.get::<Object>(entity).unwrap().object_type {
+        ObjectType::Ship { shields, hp, .. } => shields + hp,
+        _ => panic!("not a ship"),
+    };
+    let (front_before, behind_before) = (health(&world, entities[1]), health(&world, entities[2]));
+    world.server.world.send_event(HitscanFired{
+        owner: world.log(shooter).ship_object_id.unwrap(),
+        origin: Vec2::new(1500., 500.),
+        dir: Vec2::Y,
+        range: 2000.,
+        damage: 50.,
+        weapon: WeaponType::Railgun,
+        rewind_ticks: 0,
+    });
+    world.step(2);
+    assert!(health(&world, entities[1]) < front_before, "ship in front of wall is not hit");
+    assert_eq!(health(&world, entities[2]), behind_before, "ray went through wall");
+    let tracer = world.server.world.get::<ShipWeapon>(entities[0]).unwrap().tracer;
+    let (_, end, _) = tracer.expect("no tracer after shot");
+    assert!((end.y - 780.).abs() < 1., "tracer ends at {} instead of wall", end.y);
+    assert!(world.server_objects().iter().all(|(_, object, _)| !matches!(object.object_type, ObjectType::Bullet { .. })), "hitscan spawned a bullet");
+}
@@ -0,0 +1,1595 @@
+use std::{time::Duration, cmp::Ordering, collections::VecDeque, f32::consts::PI};
+use bevy::{prelude::{Component, Resource, Event, IVec2, Vec2, Vec3, Transform, Entity, Quat, default}, render::color::Color, ecs::schedule::States, utils::{HashMap, HashSet}};
+use bevy_rapier2d::prelude::Velocity;
+use bevy_renet::renet::{ChannelConfig, SendType, ConnectionConfig};
+use rand::{SeedableRng, Rng};
+use rand_chacha::ChaCha8Rng;
+use weighted_rand::builder::*;
+use serde::{Serialize, Deserialize};
+
+
+#[derive(Resource)]
+pub struct ConnectProperties{
+    pub adress: String
+}
+
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Hash, States)]
+pub enum ClientState {
+    #[default]
+    Menu,
+    InGame,
+    Editor,
+}
+
+#[derive(Serialize, Deserialize)]
+pub enum Message{
+    Greeteng{
+
+    },
+    RegisterClient{
+        style: u8,
+        color: Color,
+        name: String,
+        credentials: Option<Credentials>, // issued by this server before, None on first connect
+    },
+    OnConnect{ // MAP AND CLIENT DATA
+        clients_data: ClientsData,
+        ship_object_id: u64,
+        config: GlobalConfig
+    }, 
+    Update{ // SERVER TICK, DATA ABOUT OBJECTS AROUND, IDS OF OBJECTS THAT ARE STILL AROUND BUT SKIPPED THIS TIME
+        tick: u64,
+        sent_ms: u64, // unix millis on server when sent, loadtest measures latency from it
+        data: Vec<ObjectData>,
+        keep: Vec<u64>,
+    }, 
+    Viewport{ // CLIENT VISIBLE AREA IN WORLD UNITS (ZOOM INCLUDED)
+        size: Vec2,
+    }, 
+    ServerStats{ // ONCE PER SECOND, TIME SPENT ON SERVER FRAMES
+        avg_tick_ms: f32,
+        max_tick_ms: f32,
+        objects: u32,
+        clients: u32,
+    }, 
+    Inputs{ // CLIENT INPUTS
+        inputs: InputKeys,
+    }, 
+    ChatMessage{ // SENDER, MESSAGE
+        sender_id: u64,
+        message: String,
+    }, 
+    NewConnection{ // CLIENT DATA
+        client_data: ClientData
+    }, 
+    NewDisconnection{ // CLIENT ID
+        id: u64
+    }, 
+    Kick{ // REASON
+        reason: String
+    }, 
+    SafeZone{ // CURRENT ZONE, ONLY WITH SHRINKING ZONE TOPOLOGY
+        zone: SafeZone
+    },
+    MapChange{ // SERVER RESTARTS OR LOADS ANOTHER MAP, CLIENTS RECONNECT BY THEMSELVES
+        reason: String
+    },
+    ProfileRequest{ // CLIENT ASKS FOR ITS LIFETIME STATS
+    },
+    Profile{ // LIFETIME STATS, ONLY TO ITS OWNER
+        profile: PlayerProfile
+    },
+    Credentials{ // NEW IDENTITY FROM SERVER, CLIENT SENDS IT BACK ON NEXT CONNECT
+        credentials: Credentials
+    },
+    ERR,
+}
+
+/// Names of message types, same order as in Message (net stats columns)
+pub const MESSAGE_KINDS: [&str; 17] = [
+    "greeting", "register_client", "on_connect", "update", "viewport", "server_stats", "inputs",
+    "chat_message", "new_connection", "new_disconnection", "kick", "safe_zone", "map_change",
+    "profile_request", "profile", "credentials", "err",
+];
+
+impl Message{
+    pub fn kind(&self) -> usize{ // index in MESSAGE_KINDS
+        match self {
+            Message::Greeteng{..} => 0,
+            Message::RegisterClient{..} => 1,
+            Message::OnConnect{..} => 2,
+            Message::Update{..} => 3,
+            Message::Viewport{..} => 4,
+            Message::ServerStats{..} => 5,
+            Message::Inputs{..} => 6,
+            Message::ChatMessage{..} => 7,
+            Message::NewConnection{..} => 8,
+            Message::NewDisconnection{..} => 9,
+            Message::Kick{..} => 10,
+            Message::SafeZone{..} => 11,
+            Message::MapChange{..} => 12,
+            Message::ProfileRequest{..} => 13,
+            Message::Profile{..} => 14,
+            Message::Credentials{..} => 15,
+            Message::ERR => 16,
+        }
+    }
+}
+
+/// Profile key and secret proving it, server makes them, client only stores them
+#[derive(Serialize, Deserialize)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Credentials{
+    pub identity: u64,
+    pub secret: u64,
+}
+
+/// Lifetime stats of one client identity, kept by server between sessions
+#[derive(Serialize, Deserialize)]
+#[derive(Clone, Default, Debug)]
+pub struct PlayerProfile{
+    pub name: String, // last used
+    pub kills: u32,
+    pub deaths: u32,
+    pub shots: u32,
+    pub hits: u32, // shots that hit other ship, asteroids don't count
+    pub playtime_secs: f32,
+    pub style_secs: HashMap<u8, f32>, // time played with every ship style
+    pub last_seen: u64, // unix seconds
+}
+
+impl PlayerProfile{
+    pub fn accuracy(&self) -> f32{
+        if self.shots == 0 {return 0.;}
+        self.hits as f32 / self.shots as f32
+    }
+    /// Profiles without kills, deaths or shots are not saved and not on leaderboard
+    pub fn has_stats(&self) -> bool{
+        self.kills > 0 || self.deaths > 0 || self.shots > 0
+    }
+    pub fn favorite_style(&self) -> Option<u8>{
+        self.style_secs.iter().max_by(|a, b| a.1.total_cmp(b.1)).map(|(style, _)| *style)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[derive(Clone)]
+pub struct ObjectData{
+    pub object: Object,
+    pub states_and_statuses: Option<(ShipState, ShipStatuses)>,
+    pub weapon: Option<ShipWeapon>,
+    pub angular_velocity: f32,
+    pub linear_velocity: Vec2,
+    pub translation: Vec3,
+    pub rotation: Quat,
+}
+
+
+#[derive(Serialize, Deserialize)]
+pub struct MyData{ 
+    pub color: [f32; 3],
+    pub style: u8,
+    pub name: String,
+}
+
+#[derive(Serialize, Deserialize)]
+#[derive(Clone)]
+#[derive(Resource)]
+pub struct ClientsData{
+    binds: HashMap<u64, u64>, // object_id -> client_id
+    data: HashMap<u64, ClientData> // client_id -> data
+}
+
+
+#[derive(Component)]
+pub struct LastDamageTaken{pub time: f32} 
+
+#[derive(Event)]
+pub struct ShipDestroyed{ // object ids
+    pub victim: u64,
+    pub killer: Option<u64>,
+}
+
+#[derive(Event)]
+pub struct ShotFired{ // every bullet and every tick of firing beam, object id of shooter
+    pub owner: u64,
+}
+
+#[derive(Event, Clone, Copy)]
+pub struct HitscanFired{ // resolved in the same tick by resolve_hitscan_shots, object id of shooter
+    pub owner: u64,
+    pub origin: Vec2,
+    pub dir: Vec2,
+    pub range: f32,
+    pub damage: f32, // extra damage included
+    pub weapon: WeaponType,
+    pub rewind_ticks: u64, // ships are hit where shooter saw them
+}
+
+#[derive(Event)]
+pub struct ShotHit{ // once per bullet or beam tick that hit other ship, object id of shooter
+    pub owner: u64,
+}
+
+
+#[derive(Serialize, Deserialize)]
+#[derive(Clone)]
+#[derive(Component)]
+pub struct ClientData{ 
+    pub client_id: u64,
+    pub object_id: u64,
+    pub entity: Entity,
+    pub style: u8,
+    pub color: Color,
+    pub name: String,
+    pub kills: u32,
+    pub deaths: u32,
+}
+impl Default for ClientsData {
+    fn default() -> Self {
+        ClientsData{
+            binds: HashMap::new(),
+            data: HashMap::new()
+        }
+    }
+}
+
+impl ClientData{
+    pub fn for_spawn(
+        style: u8,
+        color: Color,
+        object_id: u64,
+    ) -> Self{
+        ClientData {
+            client_id: 0,
+            object_id: object_id,
+            style: style,
+            entity: Entity::PLACEHOLDER,
+            color: color,
+            name: "PLACEHOLDER".into(),
+            kills: 0,
+            deaths: 0,
+        }
+    }
+}
+
+
+impl ClientsData{
+    pub fn clean_exclude_me(&mut self){
+        let to_save = self.get_by_client_id(0).clone();
+        self.binds = HashMap::new();
+        self.data = HashMap::new();
+        self.add(to_save);
+    }
+    pub fn get_option_by_object_id(&self, key: u64) -> Option<&ClientData>{
+        let key = self.binds.get(&key);
+        if key.is_some(){
+            return self.data.get(key.unwrap());
+        }
+        return None;
+    }
+    pub fn get_by_client_id(&self, key: u64) -> &ClientData{
+        self.data.get(&key).unwrap()
+    }
+    pub fn get_option_by_client_id(&self, key: &u64) -> Option<&ClientData>{
+        self.data.get(key)
+    }
+    pub fn iter(&self) -> impl Iterator<Item = &ClientData>{
+        self.data.values()
+    }
+    pub fn get_mut_by_client_id(&mut self, key: u64) -> &mut ClientData{
+        self.data.get_mut(&key).unwrap()
+    }
+    pub fn add(&mut self, data: ClientData){
+        self.binds.insert(data.object_id, data.client_id);
+        self.data.insert(data.client_id, data);
+    }
+    pub fn remove_by_object_id(&mut self, key: u64){
+        let k = self.binds.get(&key).unwrap();
+        self.data.remove(k);
+        self.binds.remove(&key);
+    }
+    pub fn remove_by_client_id(&mut self, key: u64){
+        self.data.remove(&key);
+        self.binds.remove(&key);
+    }
+}
+
+
+#[derive(Component)]
+pub struct CameraCanvas;
+
+#[derive(Component)]
+pub struct PixelCamera;
+
+#[derive(Event)]
+pub enum ApplyCameraSettings{
+    Tonemapping,
+    BloomCompositeMode,
+    Intensity,
+    LowFrequencyBoost,
+    LowFrequencyBoostCurvature,
+    HighPassFrequency,
+    Threshold,
+    ThresholdSoftness,
+    DebandDither,
+}
+
+#[derive(Serialize, Deserialize)]
+#[derive (Clone)]
+#[derive (Resource)]
+pub struct GlobalConfig{
+    // MAP
+    pub last_id: u64,
+    pub map_size_chunks: Vec2, //   !!!MUST BE INTEGER!!!
+    pub single_chunk_size: Vec2, // !!!MUST BE INTEGER!!!
+    pub asteroids_per_chunk: f32,
+    pub topology: MapTopology,
+
+    pub debug_render: bool, // todo: move to ?
+    pub world_seed: u64, // same seed and inputs give same match
+    pub tick_rate: f32, // server simulation ticks per second
+    pub snapshot_rate: f32, // updates sent to clients per second, rounded to whole number of ticks
+    pub max_rewind_ms: f32, // lag compensation limit, players with bigger ping have to lead targets
+    // OBJECTS
+    pub asteroid_hp: Vec<i8>, // why i8? oh... number of hits. per size class, last one is used for bigger sizes
+    pub asteroid_generator: AsteroidGeneratorConfig,
+    pub asteroid_motion: AsteroidMotion,
+    pub gravity_wells: Vec<GravityWell>,
+    pub fill_asteroids: bool, // asteroids_refiller keeps map filled with random asteroids
+    pub obstacles: Vec<MapObstacle>,
+    pub spawn_points: Vec<Vec2>, // ships spawn at random one of them if not empty
+    pub background: MapBackground,
+    pub interest: InterestConfig,
+    pub player_hp: f32,
+    pub player_shields: f32,
+    pub shield_recharge_per_sec: f32,
+    pub collision_damage_threshold: f32, // contact force below that is ignored
+    pub collision_damage_multiplier: f32, // damage per unit of force above threshold
+    pub powerup_drop_chances: f32,
+    pub powerup_lifetime_secs: f32,
+    pub powerup_blink_secs: f32, // pickups blink on clients before despawn
+    pub max_powerups: usize, // oldest dropped pickups are removed above that
+    pub powerup_spawn_points: Vec<PowerUPSpawnPoint>,
+    // TIMERS
+    pub dash_cd_secs: f32,
+    pub dash_time: f32,
+    pub dash_impulse: f32,
+    pub shield_recharge_delay: f32,
+    pub respawn_time_secs: f32,
+
+    // POWERUPS
+    pub powerups: Vec<PowerUPDefinition>, // index is PowerUPType
+
+    // WEAPONS
+    pub weapons: HashMap<WeaponType, WeaponStats>,
+}
+
+impl Default for GlobalConfig {
+    fn default() -> Self {
+        GlobalConfig{
+            last_id: 0,
+            map_size_chunks: Vec2{x: 5., y: 5.},
+            single_chunk_size: Vec2{x: 500., y: 500.},
+            asteroids_per_chunk: 1.,
+            topology: MapTopology::Torus,
+            debug_render: false,
+            world_seed: rand::random(),
+            tick_rate: 60.,
+            snapshot_rate: 30.,
+            max_rewind_ms: 200.,
+            asteroid_hp: vec![1, 1, 1, 2, 2],
+            asteroid_generator: AsteroidGeneratorConfig::default(),
+            asteroid_motion: AsteroidMotion::Drift{max_speed: 150.},
+            gravity_wells: vec![],
+            fill_asteroids: true,
+            obstacles: vec![],
+            spawn_points: vec![],
+            background: MapBackground::default(),
+            interest: InterestConfig::default(),
+            player_hp: 100.,
+            player_shields: 100.,
+            shield_recharge_per_sec: 10.,
+            shield_recharge_delay: 5.,
+            collision_damage_threshold: 1500.,
+            collision_damage_multiplier: 0.02,
+            powerup_drop_chances: 1.0,
+            powerup_lifetime_secs: 30.,
+            powerup_blink_secs: 5.,
+            max_powerups: 30,
+            powerup_spawn_points: vec![],
+            dash_cd_secs: 0.5, // todo: gui cd
+            dash_time: 0.12,
+            dash_impulse: 2000.,
+            respawn_time_secs: 5.,
+
+            powerups: parse_power_up_definitions(DEFAULT_POWERUPS).unwrap(),
+
+            weapons: HashMap::from_iter([
+                (WeaponType::Blaster, WeaponStats{
+                    fire_cd_secs: 0.5,
+                    projectile_speed: 1000.,
+                    spread: 0.,
+                    pellets: 1,
+                    damage: 50.,
+                    lifetime_secs: 10.,
+                    ammo: None,
+                    ..default()
+                }),
+                (WeaponType::SpreadShot, WeaponStats{
+                    fire_cd_secs: 0.7,
+                    projectile_speed: 900.,
+                    spread: 0.6,
+                    pellets: 5,
+                    damage: 20.,
+                    lifetime_secs: 1.,
+                    ammo: Some(15),
+                    ..default()
+                }),
+                (WeaponType::RapidFire, WeaponStats{
+                    fire_cd_secs: 0.1,
+                    projectile_speed: 1200.,
+                    spread: 0.08,
+                    pellets: 1,
+                    damage: 15.,
+                    lifetime_secs: 2.,
+                    ammo: Some(60),
+                    ..default()
+                }),
+                (WeaponType::Railgun, WeaponStats{
+                    fire_cd_secs: 1.5,
+                    projectile_speed: 0., // hitscan, nothing flies
+                    spread: 0.,
+                    pellets: 1,
+                    damage: 120.,
+                    lifetime_secs: 0.,
+                    ammo: Some(5),
+                    charge_secs: 0.8,
+                    hitscan_range: Some(2000.),
+                    ..default()
+                }),
+                (WeaponType::HomingMissiles, WeaponStats{
+                    fire_cd_secs: 0.8,
+                    projectile_speed: 600.,
+                    spread: 0.,
+                    pellets: 1,
+                    damage: 40.,
+                    lifetime_secs: 5.,
+                    ammo: Some(6),
+                    homing_turn_rate: 3.,
+                    homing_range: 800.,
+                    ..default()
+                }),
+                (WeaponType::Laser, WeaponStats{
+                    fire_cd_secs: 0.,
+                    projectile_speed: 0.,
+                    spread: 0.,
+                    pellets: 0,
+                    damage: 90.,
+                    lifetime_secs: 0.,
+                    ammo: None, // limited by heat
+                    beam_range: Some(700.),
+                    heat_per_sec: 0.35,
+                    cooling_per_sec: 0.5,
+                    ..default()
+                }),
+            ]),
+        }
+    }
+}
+
+impl GlobalConfig {
+    pub fn get_power_up_def(&self, powerup_type: PowerUPType) -> &PowerUPDefinition {
+        self.powerups.get(powerup_type.0 as usize).unwrap()
+    }
+    pub fn get_power_up_effect(&self, powerup_type: PowerUPType) -> PowerUPEffect {
+        let def = self.get_power_up_def(powerup_type);
+        PowerUPEffect{seconds: def.duration_secs, value: def.magnitude, kind: def.effect}
+    }
+    pub fn find_power_up_by_id(&self, id: &str) -> Option<PowerUPType> {
+        self.powerups.iter().position(|def| def.id == id).map(|i| PowerUPType(i as u16))
+    }
+    pub fn find_power_up(&self, kind: PowerUPEffectKind) -> Option<PowerUPType> { // first definition with that effect
+        self.powerups.iter().position(|def| def.effect == kind).map(|i| PowerUPType(i as u16))
+    }
+    pub fn roll_power_up_drop(&self, asteroid_size: u8, rng: &mut ChaCha8Rng) -> Option<PowerUPType> {
+        let weights = self.powerups.iter()
+            .map(|def| *def.drop_weights.get(asteroid_size as usize - 1).or(def.drop_weights.last()).unwrap_or(&0))
+            .collect::<Vec<u32>>();
+        if weights.iter().sum::<u32>() == 0 {return None;}
+        let table = WalkerTableBuilder::new(&weights).build();
+        Some(PowerUPType(table.next_rng(rng) as u16))
+    }
+    pub fn get_weapon_stats(&self, weapon_type: WeaponType) -> &WeaponStats {
+        self.weapons.get(&weapon_type).unwrap()
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum AsteroidGeneratorType{
+    Classic, // 16-sided jittered polygon, 3 sizes, rock only
+    Procedural,
+}
+
+#[derive(Serialize, Deserialize)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum AsteroidShape{
+    Round,
+    Elongated,
+    Cratered,
+    Clustered,
+}
+
+#[derive(Serialize, Deserialize)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum AsteroidMaterial{
+    Rock,
+    Ice,
+    Metal,
+}
+
+#[derive(Serialize, Deserialize)]
+#[derive(Clone, Debug)]
+pub struct AsteroidMaterialStats{
+    pub material: AsteroidMaterial,
+    pub weight: u32, // chance to be picked
+    pub hp_multiplier: f32,
+    pub density: f32, // collider density, so mass
+    pub drop_rolls: u32, // how many times drop is rolled when destroyed
+    pub color: Color,
+}
+
+#[derive(Serialize, Deserialize)]
+#[derive(Clone, Debug)]
+pub struct AsteroidGeneratorConfig{
+    pub generator: AsteroidGeneratorType,
+    pub size_weights: Vec<u32>, // per size class, from the smallest
+    pub shape_weights: Vec<(AsteroidShape, u32)>,
+    pub materials: Vec<AsteroidMaterialStats>,
+}
+
+impl Default for AsteroidGeneratorConfig{
+    fn default() -> Self {
+        AsteroidGeneratorConfig{
+            generator: AsteroidGeneratorType::Procedural,
+            size_weights: vec![6, 5, 3, 2, 1],
+            shape_weights: vec![
+                (AsteroidShape::Round, 4),
+                (AsteroidShape::Elongated, 2),
+                (AsteroidShape::Cratered, 2),
+                (AsteroidShape::Clustered, 1),
+            ],
+            materials: vec![
+                AsteroidMaterialStats{material: AsteroidMaterial::Rock, weight: 6, hp_multiplier: 1., density: 1., drop_rolls: 1, color: Color::WHITE * 2.},
+                AsteroidMaterialStats{material: AsteroidMaterial::Ice, weight: 3, hp_multiplier: 0.5, density: 0.6, drop_rolls: 1, color: Color::rgb(0.6, 1.2, 2.)},
+                AsteroidMaterialStats{material: AsteroidMaterial::Metal, weight: 1, hp_multiplier: 2., density: 2.5, drop_rolls: 2, color: Color::rgb(2., 1.3, 0.6)},
+            ],
+        }
+    }
+}
+
+/// Ambient movement of asteroids
+#[derive(Serialize, Deserialize)]
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum AsteroidMotion{
+    Still,
+    Drift{max_speed: f32}, // random velocity on spawn
+    FlowField{speed: f32, waves: u32, steering: f32}, // asteroids are pushed along field, waves is number of periods across map
+}
+
+#[derive(Serialize, Deserialize)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum BoundaryWalls{
+    Solid,
+    Bouncy,
+}
+
+/// How map borders work
+#[derive(Serialize, Deserialize)]
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub enum MapTopology{
+    #[default]
+    Torus, // objects fly through border to the other side
+    Bounded{walls: BoundaryWalls},
+    ShrinkingZone{start_delay_secs: f32, shrink_secs: f32, min_radius: f32, damage_per_sec: f32}, // bounded with solid walls
+}
+
+impl MapTopology{
+    pub fn is_wrapped(&self) -> bool{
+        *self == MapTopology::Torus
+    }
+    pub fn walls(&self) -> Option<BoundaryWalls>{
+        match *self {
+            MapTopology::Torus => None,
+            MapTopology::Bounded { walls } => Some(walls),
+            MapTopology::ShrinkingZone { .. } => Some(BoundaryWalls::Solid),
+        }
+    }
+}
+
+/// Number of server simulation tick, increased first in every FixedUpdate
+#[derive(Resource, Default)]
+pub struct ServerTick(pub u64);
+
+/// Bullet of a player with latency, it hits ships where they were that many ticks ago
+#[derive(Component, Clone, Copy)]
+pub struct LagCompensation{
+    pub rewind_ticks: u64,
+}
+
+/// Ship positions and rotations for last ticks, newest first
+#[derive(Resource, Default)]
+pub struct ShipHistory{
+    ticks: VecDeque<(u64, Vec<(Entity, Vec2, f32)>)>, // tick, ships
+}
+
+impl ShipHistory{
+    pub fn record(&mut self, tick: u64, ships: Vec<(Entity, Vec2, f32)>, max_ticks: u64){
+        self.ticks.push_front((tick, ships));
+        self.ticks.truncate(max_ticks as usize + 1);
+    }
+    /// Ships at tick or the oldest state if it is too old
+    pub fn at(&self, tick: u64) -> Option<&Vec<(Entity, Vec2, f32)>>{
+        self.ticks.iter().find(|(t, _)| *t <= tick).or(self.ticks.back()).map(|(_, ships)| ships)
+    }
+}
+
+/// What objects are sent to every client and how often
+#[derive(Serialize, Deserialize)]
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct InterestConfig{
+    pub margin: f32, // added to half of viewport diagonal
+    pub min_radius: f32,
+    pub max_radius: f32,
+    pub bandwidth_bytes_per_sec: f32, // per client, own ship is always sent
+    pub distance_priority: f32, // extra priority per sec for objects close to the player
+    pub speed_priority: f32, // extra priority per sec for every 100 units/s of speed
+}
+
+impl Default for InterestConfig{
+    fn default() -> Self {
+        InterestConfig{
+            margin: 200.,
+            min_radius: 400.,
+            max_radius: 2000.,
+            bandwidth_bytes_per_sec: 64000.,
+            distance_priority: 4.,
+            speed_priority: 1.,
+        }
+    }
+}
+
+pub const DEFAULT_VIEWPORT: Vec2 = Vec2{x: 910., y: 512.}; // 16:9 at TARGET_HEIGHT, used for bots and before client reports
+
+impl InterestConfig{
+    pub fn radius(&self, viewport: Vec2) -> f32{
+        (viewport.length() / 2. + self.margin).clamp(self.min_radius, self.max_radius)
+    }
+
+    /// Priority gained per second, base 1 for everything in radius
+    pub fn priority(&self, distance: f32, radius: f32, speed: f32) -> f32{
+        1. + (1. - distance / radius).max(0.) * self.distance_priority + speed / 100. * self.speed_priority
+    }
+}
+
+/// Ships outside of it are damaged, infinite radius when there is no zone
+#[derive(Resource)]
+#[derive(Serialize, Deserialize)]
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct SafeZone{
+    pub center: Vec2,
+    pub radius: f32,
+    #[serde(skip)]
+    pub started_at: Option<f32>, // server only, elapsed seconds when match began, new zone starts new match
+}
+
+impl Default for SafeZone{
+    fn default() -> Self {
+        SafeZone{center: Vec2::ZERO, radius: f32::INFINITY, started_at: None}
+    }
+}
+
+impl SafeZone{
+    pub fn contains(&self, pos: Vec2) -> bool{
+        pos.distance_squared(self.center) <= self.radius.powi(2)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum GravityWellKind{
+    Star,
+    BlackHole,
+}
+
+#[derive(Component)]
+pub struct GravityWellSprite;
+
+/// Wall on map border of bounded topology
+#[derive(Component)]
+pub struct MapBound;
+
+/// Fixed asteroid or wall from map
+#[derive(Component)]
+pub struct Obstacle;
+
+/// Pulls ships, bullets and asteroids, acceleration falls off with squared distance
+#[derive(Serialize, Deserialize)]
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct GravityWell{
+    pub kind: GravityWellKind,
+    pub position: Vec2,
+    pub strength: f32, // acceleration at distance of 100
+    pub radius: f32, // no pull outside
+    pub core_radius: f32, // visual size, also pull is not growing inside
+}
+
+impl GravityWell{
+    pub fn acceleration(&self, cfg: &GlobalConfig, pos: Vec2) -> Vec2{
+        let to_well = cfg.world_wrapped_vec(pos, self.position);
+        let distance = to_well.length();
+        if distance > self.radius || distance < f32::EPSILON {return Vec2::ZERO;}
+        to_well / distance * self.strength * (100. / distance.max(self.core_radius)).powi(2)
+    }
+}
+
+/// Everything about asteroid that is derived from its seed
+#[derive(Clone, Copy, Debug)]
+pub struct AsteroidParams{
+    pub size: u8, // from 1
+    pub shape: AsteroidShape,
+    pub material: AsteroidMaterial,
+}
+
+impl AsteroidGeneratorConfig{
+    pub fn get_params(&self, seed: u64) -> AsteroidParams{
+        let mut rng = ChaCha8Rng::seed_from_u64(seed);
+        match self.generator {
+            AsteroidGeneratorType::Classic => {
+                let size = match rng.gen_range(0..16) {
+                    0..=6 => 1,
+                    7..=14 => 2,
+                    15..=16 => 3,
+                    e => {println!("{}", e); 1}
+                };
+                AsteroidParams{size, shape: AsteroidShape::Round, material: AsteroidMaterial::Rock}
+            }
+            AsteroidGeneratorType::Procedural => {
+                let pick = |weights: &Vec<u32>, rng: &mut ChaCha8Rng| -> usize {
+                    let total: u32 = weights.iter().sum();
+                    let mut roll = rng.gen_range(0..total.max(1));
+                    for (i, weight) in weights.iter().enumerate(){
+                        if roll < *weight {return i;}
+                        roll -= weight;
+                    }
+                    0
+                };
+                let size = pick(&self.size_weights, &mut rng) as u8 + 1;
+                let shape = self.shape_weights.get(pick(&self.shape_weights.iter().map(|(_, w)| *w).collect(), &mut rng)).map(|(shape, _)| *shape).unwrap_or(AsteroidShape::Round);
+                let material = self.materials.get(pick(&self.materials.iter().map(|m| m.weight).collect(), &mut rng)).map(|m| m.material).unwrap_or(AsteroidMaterial::Rock);
+                AsteroidParams{size, shape, material}
+            }
+        }
+    }
+    pub fn get_material_stats(&self, material: AsteroidMaterial) -> AsteroidMaterialStats{
+        self.materials.iter().find(|m| m.material == material).cloned().unwrap_or(
+            AsteroidMaterialStats{material, weight: 0, hp_multiplier: 1., density: 1., drop_rolls: 1, color: Color::WHITE * 2.}
+        )
+    }
+}
+
+impl GlobalConfig{
+    pub fn get_asteroid_params(&self, seed: u64) -> AsteroidParams{
+        self.asteroid_generator.get_params(seed)
+    }
+    pub fn get_asteroid_size(&self, seed: u64) -> u8{
+        self.get_asteroid_params(seed).size
+    }
+    pub fn get_asteroid_hp(&self, seed: u64) -> u8{
+        let params = self.get_asteroid_params(seed);
+        let hits = *self.asteroid_hp.get(params.size as usize - 1).or(self.asteroid_hp.last()).unwrap_or(&1) as f32;
+        (hits * self.asteroid_generator.get_material_stats(params.material).hp_multiplier).round().clamp(1., 255.) as u8
+    }
+    pub fn ticks_per_snapshot(&self) -> u64{
+        (self.tick_rate / self.snapshot_rate.max(0.001)).round().max(1.) as u64
+    }
+    pub fn snapshot_interval(&self) -> f32{ // seconds
+        self.ticks_per_snapshot() as f32 / self.tick_rate
+    }
+    pub fn max_rewind_ticks(&self) -> u64{
+        (self.max_rewind_ms / 1000. * self.tick_rate).round().max(0.) as u64
+    }
+    pub fn new_id(&mut self) -> u64{ // ID 0 IS EMPTY!!!!
+        self.last_id += 1;
+        return self.last_id;
+    }
+    pub fn pos_to_chunk(&self, pos: &Vec3) -> Vec2{
+        Vec2{x: (pos.x / self.single_chunk_size.x).floor(), y: (pos.y / self.single_chunk_size.y).floor()}
+    }
+    
+    pub fn pos_to_real_chunk(&self, pos: &Vec3) -> Vec2{
+        let chunk = self.pos_to_chunk(pos);
+        Vec2{x: chunk.x.rem_euclid(self.map_size_chunks.x), y: chunk.y.rem_euclid(self.map_size_chunks.y)}
+    }
+    pub fn pos_to_chunk_v2(&self, pos: &Vec2) -> Vec2{
+        Vec2{x: (pos.x / self.single_chunk_size.x).floor(), y: (pos.y / self.single_chunk_size.y).floor()}
+    }
+    
+    pub fn pos_to_real_chunk_v2(&self, pos: &Vec2) -> Vec2{
+        let chunk = self.pos_to_chunk_v2(pos);
+        Vec2{x: chunk.x.rem_euclid(self.map_size_chunks.x), y: chunk.y.rem_euclid(self.map_size_chunks.y)}
+    }
+
+    pub fn chunk_to_real_chunk_v2(&self, chunk: &Vec2) -> Vec2{
+        Vec2{x: chunk.x.rem_euclid(self.map_size_chunks.x), y: chunk.y.rem_euclid(self.map_size_chunks.y)}
+    }
+
+    pub fn chunk_to_offset(&self, chunk: &Vec2) -> Vec2{
+        Vec2{x: chunk.x * self.single_chunk_size.x, y: chunk.y * self.single_chunk_size.y}
+    }
+
+    pub fn wrap_offsets(&self) -> Vec<Vec2> { // where copies of static things are drawn, shadow chunks exist only on torus
+        if !self.topology.is_wrapped() {return vec![Vec2::ZERO];}
+        let world_size = self.map_size_chunks * self.single_chunk_size;
+        let mut offsets = vec![];
+        for x in -1..=1{
+            for y in -1..=1{
+                offsets.push(Vec2::new(x as f32, y as f32) * world_size);
+            }
+        }
+        offsets
+    }
+    pub fn flow_at(&self, pos: Vec2) -> Vec2 {
+        match self.asteroid_motion {
+            AsteroidMotion::FlowField { speed, waves, steering: _ } => {
+                let world_size = self.map_size_chunks * self.single_chunk_size;
+                let phase = |shift: u32| (self.world_seed >> shift & 0xffff) as f32 / 65535. * PI * 2.;
+                let waves = waves.max(1) as f32; // whole number of periods keeps field continuous through map borders
+                let angle = PI * (
+                    (pos.x / world_size.x * waves * PI * 2. + phase(0)).sin() +
+                    (pos.y / world_size.y * waves * PI * 2. + phase(16)).sin() +
+                    ((pos.x / world_size.x + pos.y / world_size.y) * PI * 2. + phase(32)).sin() * 0.5
+                );
+                Vec2::from_angle(angle) * speed
+            }
+            _ => Vec2::ZERO
+        }
+    }
+    pub fn world_wrapped_vec(&self, obj1: Vec2, obj2: Vec2) -> Vec2 { // shortest vector from obj1 to obj2 through map borders. todo: use in stars/dust layers
+        if !self.topology.is_wrapped() {return obj2 - obj1;}
+        let world_size = self.map_size_chunks * self.single_chunk_size;
+        let vector_without_looping = obj2 - obj1;
+        return Vec2::from((
+            [vector_without_looping.x, vector_without_looping.x - world_size.x, vector_without_looping.x + world_size.x]
+                .iter()
+                .min_by(|&x, &y| {
+                    x.abs().partial_cmp(&y.abs()).unwrap_or(Ordering::Equal)
+                })
+                .unwrap()
+                .clone(),
+            [vector_without_looping.y, vector_without_looping.y - world_size.y, vector_without_looping.y + world_size.y]
+                .iter()
+                .min_by(|&x, &y| {
+                    x.abs().partial_cmp(&y.abs()).unwrap_or(Ordering::Equal)
+                })
+                .unwrap()
+                .clone(),
+        ));
+    }
+}
+
+#[derive(Resource)]
+pub struct LoadedChunks{ pub chunks: Vec<Chunk> } // todo: fow what? (debug maybe)
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SpatialKind{
+    Asteroid,
+    Bullet,
+    Ship,
+    PickUP,
+}
+
+impl From<&ObjectType> for SpatialKind{
+    fn from(object_type: &ObjectType) -> Self {
+        match object_type {
+            ObjectType::Asteroid { .. } => SpatialKind::Asteroid,
+            ObjectType::Bullet { .. } => SpatialKind::Bullet,
+            ObjectType::Ship { .. } => SpatialKind::Ship,
+            ObjectType::PickUP { .. } => SpatialKind::PickUP,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct SpatialEntry{
+    pub entity: Entity,
+    pub object_id: u64,
+    pub kind: SpatialKind,
+    pub position: Vec2,
+    pub chunk: (u32, u32), // real chunk
+}
+
+/// Real (not puppet) objects by real chunks, updated by update_spatial_index only for moved objects
+#[derive(Resource, Default)]
+pub struct SpatialIndex{
+    map_size_chunks: Vec2, // grid it was built for, rebuilt when map changes
+    single_chunk_size: Vec2,
+    chunks: HashMap<(u32, u32), HashSet<Entity>>,
+    entries: HashMap<Entity, SpatialEntry>,
+    objects: HashMap<u64, Entity>, // object_id -> entity
+}
+
+impl SpatialIndex{
+    pub fn is_built_for(&self, cfg: &GlobalConfig) -> bool{
+        self.map_size_chunks == cfg.map_size_chunks && self.single_chunk_size == cfg.single_chunk_size
+    }
+    pub fn clear_for(&mut self, cfg: &GlobalConfig){
+        *self = SpatialIndex{
+            map_size_chunks: cfg.map_size_chunks,
+            single_chunk_size: cfg.single_chunk_size,
+            ..default()
+        };
+    }
+    pub fn contains(&self, entity: Entity) -> bool{
+        self.entries.contains_key(&entity)
+    }
+    pub fn update(&mut self, entity: Entity, object: &Object, position: Vec2, cfg: &GlobalConfig){
+        let real_chunk = cfg.pos_to_real_chunk_v2(&position);
+        let chunk = (real_chunk.x as u32, real_chunk.y as u32);
+        if let Some(old) = self.entries.get(&entity){
+            if old.chunk != chunk{
+                if let Some(set) = self.chunks.get_mut(&old.chunk) {set.remove(&entity);}
+            }
+        }
+        self.chunks.entry(chunk).or_default().insert(entity);
+        self.objects.insert(object.id, entity);
+        self.entries.insert(entity, SpatialEntry{entity, object_id: object.id, kind: SpatialKind::from(&object.object_type), position, chunk});
+    }
+    pub fn remove(&mut self, entity: Entity){
+        let Some(old) = self.entries.remove(&entity) else {return;};
+        if let Some(set) = self.chunks.get_mut(&old.chunk) {set.remove(&entity);}
+        if self.objects.get(&old.object_id) == Some(&entity) {self.objects.remove(&old.object_id);}
+    }
+    pub fn get(&self, entity: Entity) -> Option<&SpatialEntry>{
+        self.entries.get(&entity)
+    }
+    pub fn get_by_object_id(&self, object_id: u64) -> Option<&SpatialEntry>{
+        self.objects.get(&object_id).and_then(|e| self.entries.get(e))
+    }
+    pub fn len(&self) -> usize{
+        self.entries.len()
+    }
+    /// Objects in real chunk
+    pub fn chunk(&self, chunk: (u32, u32)) -> impl Iterator<Item = &SpatialEntry>{
+        self.chunks.get(&chunk).into_iter().flat_map(|set| set.iter()).filter_map(|e| self.entries.get(e))
+    }
+    /// Real chunks in square of chunks around, every chunk once even if map is smaller than square
+    pub fn neighborhood_chunks(&self, cfg: &GlobalConfig, chunk: Vec2, range: IVec2) -> Vec<(u32, u32)>{
+        let range = range.min(IVec2::new((cfg.map_size_chunks.x / 2.).ceil() as i32, (cfg.map_size_chunks.y / 2.).ceil() as i32)); // whole map at most
+        let mut included = HashSet::new();
+        let mut chunks = vec![];
+        for x in chunk.x as i32 - range.x ..= chunk.x as i32 + range.x{
+            for y in chunk.y as i32 - range.y ..= chunk.y as i32 + range.y{
+                if !cfg.topology.is_wrapped() && (x < 0 || y < 0 || x >= cfg.map_size_chunks.x as i32 || y >= cfg.map_size_chunks.y as i32){
+                    continue; // nothing behind walls
+                }
+                let real_chunk = cfg.chunk_to_real_chunk_v2(&Vec2{x: x as f32, y: y as f32});
+                let key = (real_chunk.x as u32, real_chunk.y as u32);
+                if included.insert(key){
+                    chunks.push(key);
+                }
+            }
+        }
+        chunks
+    }
+    /// Objects in square of chunks around chunk
+    pub fn neighborhood(&self, cfg: &GlobalConfig, chunk: Vec2, range: IVec2) -> Vec<&SpatialEntry>{
+        self.neighborhood_chunks(cfg, chunk, range).into_iter().flat_map(|key| self.chunk(key)).collect()
+    }
+    /// Objects with distance through map borders not bigger than radius, with distances
+    pub fn in_radius(&self, cfg: &GlobalConfig, pos: Vec2, radius: f32) -> Vec<(&SpatialEntry, f32)>{
+        let range = IVec2::new((radius / cfg.single_chunk_size.x).ceil() as i32, (radius / cfg.single_chunk_size.y).ceil() as i32);
+        self.neighborhood(cfg, cfg.pos_to_chunk_v2(&pos), range).into_iter()
+            .map(|entry| (entry, cfg.world_wrapped_vec(pos, entry.position).length()))
+            .filter(|(_, distance)| *distance <= radius)
+            .collect()
+    }
+    pub fn nearest(&self, cfg: &GlobalConfig, pos: Vec2, max_radius: f32, filter: impl Fn(&SpatialEntry) -> bool) -> Option<(&SpatialEntry, f32)>{
+        self.in_radius(cfg, pos, max_radius).into_iter()
+            .filter(|(entry, _)| filter(entry))
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+    }
+}
+
+pub enum GameRenderLayers{
+    _Main,
+    PixelCamera,
+    PreviewCamera,
+}
+
+
+
+#[derive(Serialize, Deserialize)]
+#[derive (Component, Clone, Copy)]
+pub struct Object{
+    pub id: u64,
+    pub object_type: ObjectType
+}
+
+#[derive (Component, Clone)]
+pub struct Puppet{pub id: u64, pub binded_chunk: Chunk}
+/*
+impl Puppet{
+    pub fn empty() -> Self{
+        return Puppet{id:0, binded_chunk: Chunk { pos: Vec2::ZERO }}
+    }
+}
+*/
+
+#[derive (Component, Clone)]
+pub struct Chunk{pub pos: Vec2} // todo: do smt
+
+#[derive (Component)]
+pub struct Bullet;
+
+#[derive (Component)]
+pub struct Asteroid;
+
+#[derive (Component)]
+pub struct Ship;
+
+#[derive(Component)]
+pub struct PowerUP;
+
+#[derive(Component)]
+pub struct PowerUPImage;
+
+#[derive(Component)]
+pub struct PowerUPCube;
+
+
+#[derive (Component)]
+pub struct Debug;
+
+#[derive (Component)]
+pub struct PuppetPlayer;
+
+
+#[derive(Serialize, Deserialize)]
+#[derive (Clone, Copy)]
+
+pub enum ObjectType{
+    Asteroid{seed: u64, hp: u8},
+    Bullet{previous_position: Transform, spawn_time: f32, owner: u64, extra_damage: f32, weapon: WeaponType},
+    Ship{style: u8, color: Color, shields: f32, hp: f32},
+    PickUP{pickup_type: PowerUPType, lifetime: f32}, // seconds left
+}
+/*
+NEVER DO LIKE THAT ^^^^^ (ENUM COMPONENT STRUCTS WITH BEVY) WITH MUT PARAMETERS. LOOK AT MY CODE AND DONT DO LIKE THAT.
+
+/ for example, changing one parameter (hp) looks like that:
+
+fn heal(
+    mut ship_q: Query<&mut Object>,
+    cfg: Res<GlobalConfig>,
+){
+    let to_heal_object = ship_q.last().unwrap(); 
+
+    let mut object_clone = object.clone();
+    match object.object_type {
+        ObjectType::Ship { style, color, shields, hp } => {
+            object_clone.object_type = ObjectType::Ship { style, color, shields, hp: (hp + cfg.effects_repair_amount).clamp(0., cfg.player_hp)};
+            *object = object_clone;
+        }
+        _ => {}
+    }
+}
+*/
+
+
+/// Index of definition in GlobalConfig::powerups
+#[derive(Component)]
+#[derive(Serialize, Deserialize)]
+#[derive(Clone, Copy)]
+#[derive(Hash, PartialEq, Eq)]
+pub struct PowerUPType(pub u16);
+
+impl PowerUPType {
+    pub fn texture_path(&self, cfg: &GlobalConfig) -> String {
+        cfg.get_power_up_def(*self).texture.clone()
+    }
+}
+
+/// What power-up does with ship. Every definition uses one of them
+#[derive(Serialize, Deserialize)]
+#[derive(Clone, Copy)]
+#[derive(Hash, PartialEq, Eq)]
+pub enum PowerUPEffectKind{
+    Repair, // instant, magnitude is hp
+    ExtraDamage, // magnitude is additional damage multiplier
+    Haste, // magnitude is acceleration multiplier
+    SuperShield, // magnitude is absorbed damage
+    Invisibility,
+    SpawnImmunity, // not a pickup, given on spawn and respawn
+    Weapon(WeaponType), // never goes to statuses, replaces ship weapon
+    Magnet, // magnitude is pull speed
+    TimeSlow, // magnitude is part of speed lost by enemies per second
+    Multishot, // magnitude is bullets per pellet
+    Emp, // instant pulse
+    RevealPulse, // instant pulse, gives Revealed to others
+    Revealed, // visible to everyone even if invisible
+}
+
+#[derive(Serialize, Deserialize)]
+#[derive(Clone, Copy)]
+#[derive(PartialEq)]
+pub enum StackingRule{
+    Refresh, // reset to definition values
+    Stack, // add duration and magnitude to current
+    Max, // keep the biggest duration and magnitude
+}
+
+#[derive(Serialize, Deserialize)]
+#[derive(Clone)]
+pub struct PowerUPDefinition{
+    pub id: String,
+    pub texture: String,
+    pub duration_secs: f32,
+    pub magnitude: f32,
+    pub stacking: StackingRule,
+    pub drop_weights: Vec<u32>, // per asteroid size, from the smallest
+    pub effect: PowerUPEffectKind,
+    #[serde(default)]
+    pub radius: f32, // for area effects
+}
+
+/// Source of every gameplay random choice on server, seeded with GlobalConfig::world_seed
+#[derive(Resource)]
+pub struct GameRng(pub ChaCha8Rng);
+
+impl GameRng {
+    pub fn from_seed(seed: u64) -> Self {
+        GameRng(ChaCha8Rng::seed_from_u64(seed))
+    }
+}
+
+/// Arena pickup which is respawned at the same place after being collected
+#[derive(Serialize, Deserialize)]
+#[derive(Clone, PartialEq, Debug)]
+pub struct PowerUPSpawnPoint{
+    pub position: Vec2,
+    pub powerup: String, // id of definition
+    pub respawn_secs: f32,
+}
+
+#[derive(Serialize, Deserialize)]
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum MapObstacle{
+    Asteroid{seed: u64, position: Vec2, rotation: f32}, // indestructible and not moving
+    Wall{from: Vec2, to: Vec2, thickness: f32},
+}
+
+#[derive(Serialize, Deserialize)]
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct MapBackground{
+    pub color: Color,
+    pub dust: usize, // number of dust particles around player
+}
+
+impl Default for MapBackground{
+    fn default() -> Self {
+        MapBackground{color: Color::BLACK, dust: 1000}
+    }
+}
+
+/// Map saved by editor and loaded by server with `--map <path>`
+#[derive(Serialize, Deserialize)]
+#[derive(Clone, Debug)]
+pub struct MapFile{
+    pub name: String,
+    pub map_size_chunks: Vec2, //   !!!MUST BE INTEGER!!!
+    pub single_chunk_size: Vec2, // !!!MUST BE INTEGER!!!
+    #[serde(default)]
+    pub topology: MapTopology,
+    #[serde(default)]
+    pub fill_asteroids: bool,
+    #[serde(default)]
+    pub obstacles: Vec<MapObstacle>,
+    #[serde(default)]
+    pub spawn_points: Vec<Vec2>,
+    #[serde(default)]
+    pub powerup_spawn_points: Vec<PowerUPSpawnPoint>,
+    #[serde(default)]
+    pub gravity_wells: Vec<GravityWell>,
+    #[serde(default)]
+    pub background: MapBackground,
+}
+
+impl Default for MapFile{
+    fn default() -> Self {
+        let cfg = GlobalConfig::default();
+        MapFile{
+            name: "new map".into(),
+            map_size_chunks: cfg.map_size_chunks,
+            single_chunk_size: cfg.single_chunk_size,
+            topology: MapTopology::Torus,
+            fill_asteroids: false,
+            obstacles: vec![],
+            spawn_points: vec![],
+            powerup_spawn_points: vec![],
+            gravity_wells: vec![],
+            background: MapBackground::default(),
+        }
+    }
+}
+
+pub fn load_map(path: &str) -> Result<MapFile, String> {
+    let data = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+    ron::from_str::<MapFile>(&data).map_err(|e| e.to_string())
+}
+
+pub fn save_map(path: &str, map: &MapFile) -> Result<(), String> {
+    let data = ron::ser::to_string_pretty(map, ron::ser::PrettyConfig::default()).map_err(|e| e.to_string())?;
+    std::fs::write(path, data).map_err(|e| e.to_string())
+}
+
+impl GlobalConfig{
+    pub fn apply_map(&mut self, map: &MapFile){
+        self.map_size_chunks = map.map_size_chunks;
+        self.single_chunk_size = map.single_chunk_size;
+        self.topology = map.topology;
+        self.fill_asteroids = map.fill_asteroids;
+        self.obstacles = map.obstacles.clone();
+        self.spawn_points = map.spawn_points.clone();
+        self.powerup_spawn_points = map.powerup_spawn_points.clone();
+        self.gravity_wells = map.gravity_wells.clone();
+        self.background = map.background;
+    }
+}
+
+pub const DEFAULT_POWERUPS: &str = include_str!("../../assets/powerups.ron");
+
+pub fn parse_power_up_definitions(data: &str) -> Result<Vec<PowerUPDefinition>, String> {
+    ron::from_str::<Vec<PowerUPDefinition>>(data).map_err(|e| e.to_string())
+}
+
+pub fn load_power_up_definitions(path: &str) -> Result<Vec<PowerUPDefinition>, String> {
+    let data = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+    parse_power_up_definitions(&data)
+}
+
+#[derive (Component)]
+#[derive(Serialize, Deserialize)]
+#[derive (Clone, Copy)]
+pub enum ShipState{
+    Regular,
+    Dash{start_time: f32, init_velocity: Vec2},
+    Dead{time: f32},
+}
+#[derive (Component)]
+#[derive(Serialize, Deserialize)]
+#[derive (Clone)]
+pub struct ShipStatuses{
+    pub current: HashMap<PowerUPType, PowerUPEffect>
+}
+
+impl ShipStatuses {
+    pub fn has(&self, kind: PowerUPEffectKind) -> bool{
+        self.current.values().any(|effect| effect.kind == kind)
+    }
+    pub fn get_value(&self, kind: PowerUPEffectKind) -> Option<f32>{ // biggest magnitude of that kind
+        self.current.values().filter(|effect| effect.kind == kind).map(|effect| effect.value).reduce(f32::max)
+    }
+    pub fn get_mut(&mut self, kind: PowerUPEffectKind) -> Option<&mut PowerUPEffect>{
+        self.current.values_mut().find(|effect| effect.kind == kind)
+    }
+    pub fn remove(&mut self, kind: PowerUPEffectKind){
+        self.current.retain(|_, effect| effect.kind != kind);
+    }
+    pub fn has_extra_damage(&self) -> bool{
+        self.has(PowerUPEffectKind::ExtraDamage)
+    }
+    pub fn has_haste(&self) -> bool{
+        self.has(PowerUPEffectKind::Haste)
+    }
+    pub fn has_super_shield(&self) -> bool{
+        self.has(PowerUPEffectKind::SuperShield)
+    }
+    pub fn has_invisibility(&self) -> bool{
+        self.has(PowerUPEffectKind::Invisibility)
+    }
+    pub fn has_spawn_immunity(&self) -> bool{
+        self.has(PowerUPEffectKind::SpawnImmunity)
+    }
+    pub fn is_hidden(&self) -> bool{ // invisible and not revealed
+        self.has_invisibility() && !self.has(PowerUPEffectKind::Revealed)
+    }
+    /// Applies picked up effect with its stacking rule
+    pub fn apply(&mut self, powerup_type: PowerUPType, cfg: &GlobalConfig){
+        let new_effect = cfg.get_power_up_effect(powerup_type);
+        let current = self.current.get_mut(&powerup_type);
+        if current.is_none(){
+            self.current.insert(powerup_type, new_effect);
+            return;
+        }
+        let current = current.unwrap();
+        match cfg.get_power_up_def(powerup_type).stacking {
+            StackingRule::Refresh => {*current = new_effect}
+            StackingRule::Stack => {
+                current.seconds += new_effect.seconds;
+                current.value += new_effect.value;
+            }
+            StackingRule::Max => {
+                current.seconds = current.seconds.max(new_effect.seconds);
+                current.value = current.value.max(new_effect.value);
+            }
+        }
+    }
+    pub fn on_spawn(cfg: &GlobalConfig) -> Self{
+        let mut current = HashMap::new();
+        let immunity = cfg.find_power_up(PowerUPEffectKind::SpawnImmunity);
+        if immunity.is_some(){
+            current.insert(immunity.unwrap(), cfg.get_power_up_effect(immunity.unwrap()));
+        }
+        ShipStatuses{current}
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[derive (Clone)]
+pub struct PowerUPEffect{
+    pub seconds: f32,
+    pub value: f32,
+    pub kind: PowerUPEffectKind,
+}
+impl PowerUPEffect{
+    pub fn get_val_to_show(&self) -> f32{ // timed effects show seconds, others their magnitude
+        if self.seconds.is_finite() && self.seconds > 0. {self.seconds} else {self.value}
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[derive(Clone, Copy)]
+#[derive(Hash, PartialEq, Eq)]
+pub enum WeaponType{
+    Blaster, // default one
+    SpreadShot,
+    RapidFire,
+    Railgun,
+    HomingMissiles,
+    Laser,
+}
+
+impl WeaponType {
+    pub fn texture_path(&self) -> String {
+        match self {
+            WeaponType::Blaster => { return "bullet.png".to_owned()},
+            WeaponType::SpreadShot => { return "powerups/spreadshot.png".to_owned()},
+            WeaponType::RapidFire => { return "powerups/rapidfire.png".to_owned()},
+            WeaponType::Railgun => { return "powerups/railgun.png".to_owned()},
+            WeaponType::HomingMissiles => { return "powerups/missiles.png".to_owned()},
+            WeaponType::Laser => { return "powerups/laser.png".to_owned()},
+        };
+    }
+    pub fn name(&self) -> String {
+        match self {
+            WeaponType::Blaster => { return "BLASTER".to_owned()},
+            WeaponType::SpreadShot => { return "SPREAD".to_owned()},
+            WeaponType::RapidFire => { return "RAPID".to_owned()},
+            WeaponType::Railgun => { return "RAILGUN".to_owned()},
+            WeaponType::HomingMissiles => { return "MISSILES".to_owned()},
+            WeaponType::Laser => { return "LASER".to_owned()},
+        };
+    }
+    pub fn projectile_color(&self) -> Color {
+        match self {
+            WeaponType::Blaster => { return Color::WHITE},
+            WeaponType::SpreadShot => { return Color::ORANGE * 2.},
+            WeaponType::RapidFire => { return Color::WHITE * 2.},
+            WeaponType::Railgun => { return Color::AZURE * 4.},
+            WeaponType::HomingMissiles => { return Color::FUCHSIA * 2.},
+            WeaponType::Laser => { return Color::RED * 4.},
+        };
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[derive(Clone)]
+pub struct WeaponStats{
+    pub fire_cd_secs: f32,
+    pub projectile_speed: f32,
+    pub spread: f32, // radians between outer pellets; random deviation if there is only one pellet
+    pub pellets: u32, // bullets per shot
+    pub damage: f32, // per pellet
+    pub lifetime_secs: f32,
+    pub ammo: Option<u32>, // None is infinite
+    pub charge_secs: f32, // if > 0, shoot must be held that long and weapon fires on release
+    pub hitscan_range: Option<f32>, // instant ray through ships and asteroids up to the first wall instead of flying projectile
+    pub homing_turn_rate: f32, // radians per sec
+    pub homing_range: f32,
+    pub beam_range: Option<f32>, // continuous beam, damage is per second
+    pub heat_per_sec: f32, // beam overheats at 1.0 of heat
+    pub cooling_per_sec: f32,
+}
+
+impl Default for WeaponStats {
+    fn default() -> Self {
+        WeaponStats{
+            fire_cd_secs: 0.5,
+            projectile_speed: 1000.,
+            spread: 0.,
+            pellets: 1,
+            damage: 50.,
+            lifetime_secs: 10.,
+            ammo: None,
+            charge_secs: 0.,
+            hitscan_range: None,
+            homing_turn_rate: 0.,
+            homing_range: 0.,
+            beam_range: None,
+            heat_per_sec: 0.,
+            cooling_per_sec: 0.,
+        }
+    }
+}
+
+#[derive(Component)]
+#[derive(Serialize, Deserialize)]
+#[derive(Clone, Copy)]
+pub struct ShipWeapon{
+    pub weapon_type: WeaponType,
+    pub ammo: Option<u32>,
+    pub charge: f32, // seconds of holding shoot
+    pub heat: f32, // 0..1, beam can't fire while overheated until it cools down to 0
+    pub overheated: bool,
+    pub firing: bool, // beam is held
+    pub beam: Option<(Vec2, Vec2)>, // start and end points of beam, end may be outside of map
+    pub tracer: Option<(Vec2, Vec2, WeaponType)>, // last hitscan ray in world, start and end, shown for TRACER_SECS
+    #[serde(skip)]
+    pub tracer_until: f32, // elapsed seconds, server only
+    #[serde(skip)]
+    pub rewind_ticks: u64, // how old world shooter sees, beam hits ships there, server only
+}
+
+impl ShipWeapon {
+    pub fn new(weapon_type: WeaponType, cfg: &GlobalConfig) -> Self {
+        ShipWeapon{
+            weapon_type,
+            ammo: cfg.get_weapon_stats(weapon_type).ammo,
+            charge: 0.,
+            heat: 0.,
+            overheated: false,
+            firing: false,
+            beam: None,
+            tracer: None,
+            tracer_until: 0.,
+            rewind_ticks: 0,
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct ShipPreview;
+
+
+// ids and properties of fast and garanteed same for both client and server are the same. maybe use just one enum Channel?
+pub enum ClientChannel { 
+    Fast,
+    Garanteed,
+}
+
+impl From<ClientChannel> for u8 {
+    fn from(channel_id: ClientChannel) -> Self {
+        match channel_id {
+            ClientChannel::Fast => 0,
+            ClientChannel::Garanteed => 1,
+        }
+    }
+}
+
+impl ClientChannel {
+    pub fn channels_config() -> Vec<ChannelConfig> {
+        vec![
+            ChannelConfig {
+                channel_id: Self::Fast.into(),
+                max_memory_usage_bytes: 5 * 1024 * 1024,
+                send_type: SendType::ReliableOrdered {
+                    resend_time: Duration::ZERO,
+                },
+            },
+            ChannelConfig {
+                channel_id: Self::Garanteed.into(),
+                max_memory_usage_bytes: 5 * 1024 * 1024,
+                send_type: SendType::ReliableOrdered {
+                    resend_time: Duration::from_millis(200),
+                },
+            },
+        ]
+    }
+}
+
+pub enum ServerChannel {
+    Fast,
+    Garanteed,
+}
+
+impl From<ServerChannel> for u8 {
+    fn from(channel_id: ServerChannel) -> Self {
+        match channel_id {
+            ServerChannel::Fast => 0,
+            ServerChannel::Garanteed => 1,
+        }
+    }
+}
+
+impl ServerChannel {
+    pub fn channels_config() -> Vec<ChannelConfig> {
+        vec![
+            ChannelConfig {
+                channel_id: Self::Fast.into(),
+                max_memory_usage_bytes: 10 * 1024 * 1024,
+                send_type: SendType::Unreliable,
+            },
+            ChannelConfig {
+                channel_id: Self::Garanteed.into(),
+                max_memory_usage_bytes: 10 * 1024 * 1024,
+                send_type: SendType::ReliableOrdered {
+                    resend_time: Duration::from_millis(200),
+                },
+            },
+        ]
+    }
+}
+
+pub fn parse_arg(name: &str) -> Option<String> { // value after `name` in command line
+    let args: Vec<String> = std::env::args().collect();
+    let i = args.iter().position(|arg| arg == name)?;
+    args.get(i + 1).cloned()
+}
+
+pub fn connection_config() -> ConnectionConfig {
+    ConnectionConfig {
+        available_bytes_per_tick: 1024 * 1024,
+        client_channels_config: ClientChannel::channels_config(),
+        server_channels_config: ServerChannel::channels_config(),
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[derive(Resource, PartialEq, Eq, Clone, Copy)]
+pub enum InputType{
+    Keyboard,
+    Mouse
+}
+#[derive(Serialize, Deserialize)]
+#[derive(Resource, Clone)]
+pub struct InputKeys{
+    pub input_vector: Vec2,
+    pub rotate_left: bool,
+    pub rotate_right: bool,
+    pub rotation_target: Vec2,
+    pub stabilize: bool,
+    pub shoot: bool,
+    pub dash: bool,
+    pub fixed_camera_z: bool,
+    pub input_type: InputType,
+    pub acked_tick: u64, // last snapshot client got, 0 if none
+}
+
+impl Default for InputKeys{
+    fn default() -> Self {
+        InputKeys {
+            input_vector: Vec2::ZERO,
+            rotate_left: false,
+            rotate_right: false,
+            rotation_target: Vec2::ZERO,
+            stabilize: false,
+            shoot: false,
+            dash: false,
+            fixed_camera_z: false,
+            input_type: InputType::Mouse,
+            acked_tick: 0,
+        }
+    }
+}
\ No newline at end of file
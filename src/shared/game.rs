@@ -0,0 +1,2459 @@
+use std::f32::consts::PI;
+
+use bevy::{
+    prelude::*,
+    sprite::{MaterialMesh2dBundle, Mesh2dHandle}, render::{render_resource::{PrimitiveTopology, Extent3d, TextureDescriptor, TextureDimension, TextureFormat, TextureUsages}, mesh::Indices, camera::RenderTarget, view::RenderLayers}, utils::hashbrown::{HashMap, HashSet}, core_pipeline::{tonemapping::{Tonemapping, DebandDither}, bloom::{BloomSettings, BloomCompositeMode}, clear_color::ClearColorConfig}, window::WindowResized,
+};
+
+use bevy_rapier2d::{prelude::*, rapier::geometry::CollisionEventFlags};
+use rand::prelude::*;
+use rand_chacha::ChaCha8Rng;
+
+use super::{settings::*, components::*};
+
+
+pub fn pixel_camera_event_listener(
+    settings: Res<GameSettings>,
+    mut listener: EventReader<ApplyCameraSettings>,
+    mut camera: Query<(&mut Tonemapping, &mut BloomSettings, &mut DebandDither), With<PixelCamera>>
+){
+    for e in listener.read(){
+        for (mut tonemapping, mut bloom, mut deband_dither) in camera.iter_mut(){
+            match e{
+                ApplyCameraSettings::DebandDither => {
+                    *deband_dither = settings.deband_dither;
+                },
+                ApplyCameraSettings::Tonemapping => {
+                    *tonemapping = settings.tonemapping;
+                },
+                ApplyCameraSettings::BloomCompositeMode => {
+                    bloom.composite_mode = settings.composite_mode;
+                },
+                ApplyCameraSettings::Intensity => {
+                    bloom.intensity = settings.bloom_intensity;
+                },
+                ApplyCameraSettings::LowFrequencyBoost => {
+                    bloom.low_frequency_boost = settings.low_frequency_boost;
+                },
+                ApplyCameraSettings::LowFrequencyBoostCurvature => {
+                    bloom.low_frequency_boost_curvature = settings.low_frequency_boost_curvature;
+                },
+                ApplyCameraSettings::HighPassFrequency => {
+                    bloom.high_pass_frequency = settings.high_pass_frequency;
+                },
+                ApplyCameraSettings::Threshold => {
+                    bloom.prefilter_settings.threshold = settings.threshold;
+                },
+                ApplyCameraSettings::ThresholdSoftness => {
+                    bloom.prefilter_settings.threshold_softness = settings.threshold_softness;
+                },
+            }
+        }
+    }
+}
+
+#[allow(dead_code)]
+pub fn init_pixel_camera(app: &mut App){
+    app.add_event::<ApplyCameraSettings>();
+    app.add_systems(Startup, setup_pixel_camera);
+    app.add_systems(Update, (update_pixel_camera, pixel_camera_event_listener));
+}
+
+fn setup_pixel_camera(
+    mut commands: Commands,
+    mut images: ResMut<Assets<Image>>,
+) {
+    let size = Extent3d {
+        width: 256,
+        height: 180,
+        ..default()
+    };
+
+    // This is the texture that will be rendered to.
+    let mut image: Image = Image {
+        texture_descriptor: TextureDescriptor {
+            label: None,
+            size,
+            dimension: TextureDimension::D2,
+            format: TextureFormat::Bgra8UnormSrgb,
+            mip_level_count: 1,
+            sample_count: 1,
+            usage: TextureUsages::TEXTURE_BINDING
+                | TextureUsages::COPY_DST
+                | TextureUsages::RENDER_ATTACHMENT,
+            view_formats: &[],
+        },
+        ..default()
+    };
+
+    // fill image.data with zeroes
+    image.resize(size);
+
+    let image_handle = images.add(image);
+
+    // This specifies the layer used for the first pass, which will be attached to the first pass camera and cube.
+    let second_pass_layer = RenderLayers::layer(GameRenderLayers::PixelCamera as u8);
+
+
+    // MAIN CAMERA
+    commands.spawn((
+        Camera2dBundle {
+            camera_2d: Camera2d {
+                clear_color: ClearColorConfig::Custom(Color::Rgba { red: 0., green: 0., blue: 0., alpha: 1. }),
+                ..default()
+            },
+            camera: Camera {
+                // render before the "main pass" camera
+                order: 1,
+                hdr: true,
+                target: RenderTarget::Image(image_handle.clone()),
+                msaa_writeback: false,
+                ..default()
+            },
+            tonemapping: Tonemapping::TonyMcMapface,
+            deband_dither: DebandDither::Enabled,
+            transform: Transform::from_translation(Vec3::new(0.0, 0.0, 0.0)),
+            ..default()
+        },
+        BloomSettings{ // 3. Enable bloom for the camera
+            composite_mode: BloomCompositeMode::Additive,
+            intensity: 0.1,
+            ..default()
+        },
+        PixelCamera,
+    )).insert(TransformBundle::default());
+
+
+    // FAKE MAIN CAMERA
+    commands.spawn((
+        Camera2dBundle {
+            camera_2d: Camera2d {
+                clear_color: ClearColorConfig::Custom(Color::Rgba { red: 0., green: 0., blue: 0., alpha: 1. }),
+                ..default()
+            },
+            camera: Camera {
+                order: 2,
+                ..default()
+            },
+            transform: Transform::from_xyz(0.0, 0.0, 15.0).looking_at(Vec3::ZERO, Vec3::Y),
+            ..default()
+        },
+        second_pass_layer,
+    ));
+    commands.spawn((
+        SpriteBundle {
+            texture: image_handle,
+            transform: Transform::from_scale(Vec3::splat(5.)),
+            ..default()
+        },
+        CameraCanvas,
+        Name::from("PixelCameraCanvas"),
+        second_pass_layer,
+    ));
+}
+
+const TARGET_HEIGHT: f32 = 512.; // todo: add setting
+
+#[allow(dead_code)]
+pub fn update_pixel_camera(
+    resize_event: Res<Events<WindowResized>>,
+    mut canvas_q: Query<(&Handle<Image>, &mut Transform), With<CameraCanvas>>,
+    mut images: ResMut<Assets<Image>>,
+){
+    let mut reader = resize_event.get_reader();
+    let (image_handle, mut transform) = canvas_q.single_mut();
+    for e in reader.read(&resize_event) {
+        if e.height == 0.{continue;}
+        let raito = TARGET_HEIGHT / e.height;
+        let size = Extent3d {
+            width: (e.width * raito) as u32,
+            height: (e.height * raito) as u32,
+            ..default()
+        };
+        let target_size = e.width / size.width as f32;
+        let img = images.get_mut(image_handle).unwrap();
+        img.resize(size);
+        transform.scale = Vec3::splat(target_size);
+    }
+}
+
+
+pub fn get_ship_vertices(style: u8) -> (Vec<Vec<(f32, f32)>>, Vec<Vec<u32>>){
+    //let mut style: u8 = 0b_10001110_u8;
+    let mut style2 = style.clone();
+    let mut bits: [bool; 8] = [false; 8];
+    
+    for n in 0..=7{
+        let m = 2_u8.pow(7 - n);
+        //print!("{:?}", m);
+        let i = style2 / m;
+        //println!(" {:?}", i);
+        if i == 1{
+            bits[n as usize] = i != 0;
+        }
+        style2 = style2 % m;
+    }
+    
+    
+    let is_lined = bits[2];
+    let is_spear = bits[3];
+    let is_spikes = bits[4];
+    let is_gem = bits[5];
+    let is_shards = bits[6];
+    let _is_shield = bits[7];
+
+
+    let mut vertices: Vec<Vec<(f32, f32)>> = vec![];
+    let mut indices = vec![];
+    let mut z_indexes: Vec<i8> = vec![]; 
+    /*
+    -1 shards, spikes
+    0 standard
+    1 gem
+    */
+
+
+    if is_shards{
+        vertices.push(vec![(-1., -3.), (0., -6.), (1., -3.)]);
+        vertices.push(vec![(1., -3.), (3., -5.5), (3., -2.5)]);
+        vertices.push(vec![(-1., -3.), (-3., -5.5), (-3., -2.5)]);
+        z_indexes.push(-1);
+        z_indexes.push(-1);
+        z_indexes.push(-1);
+    }
+    
+    // base
+    match bits[0] as i32 * 2 + bits[1] as i32{
+        0 => {
+            vertices.push(vec![(0., 5.), (3., -2.5), (0., -5.), (-3., -2.5)]);
+            z_indexes.push(0);
+        },
+        1 => {
+            vertices.push(vec![(0., 5.), (4., -5.), (1., -3.), (0., -5.), (-1., -3.), (-4., -5.)]);
+            z_indexes.push(0);
+        },
+        2 => {
+            vertices.push(vec![(0., 5.), (4., -5.), (-4., -5.)]);
+            z_indexes.push(0);
+        },
+        3 => {
+            vertices.push(vec![(0., 5.), (4., -5.), (0., -3.), (-4., -5.)]);
+            z_indexes.push(0);
+        },
+        _ => {},
+    };
+    
+    // deco
+    if is_spear{
+        vertices.push(vec![(0., 6.), (-1., 4.), (0., 3.5), (1., 4.)]);
+        z_indexes.push(1);
+    }
+    if is_spikes{
+        vertices.push(vec![(0.5, 3.75), (3., 0.75), (2.5, -1.25)]);//vertices.push(vec![(0.5, 4.), (3., 1.), (2.5, -1.)]);
+        vertices.push(vec![(-0.5, 3.75), (-3., 0.75), (-2.5, -1.25)]);//vertices.push(vec![(-0.5, 4.), (-3., 1.), (-2.5, -1.)]);
+        z_indexes.push(-1);
+        z_indexes.push(-1);
+    }
+    if is_gem{
+        vertices.push(vec![(0., 3.), (-2., -2.), (0., -3.), (2., -2.)]);
+        z_indexes.push(1);
+    }
+
+    if is_lined {
+        let mut last_i: u32 = 0;
+        for i in 0..vertices.len(){
+            let mut d: Vec<u32> = vec![];
+            for ind in 0..(vertices[i].len() as u32){
+                d.push(last_i + ind);
+                d.push(last_i + ((ind + 1) % vertices[i].len() as u32));
+            }
+            last_i += vertices[i].len() as u32 + 1;
+
+            indices.push(d);
+            let l = vertices[i][0];
+            vertices[i].push(l);
+        }
+    } else {
+        let mut last_i: u32 = 0;
+        for i in 0..vertices.len(){
+            let mut d: Vec<u32> = vec![];
+            for i in 2..(vertices[i].len() as u32){
+                d.push(last_i + 0);
+                d.push(last_i + i-1);
+                d.push(last_i + i);
+            }
+            last_i += vertices[i].len() as u32;
+            indices.push(d);
+        }
+    }
+
+    //let permutation = permutation::sort(&z_indexes); // todo: exclude permutation from deps (if unused)
+    //let vertices = permutation.apply_slice(&vertices);
+    //let indices = permutation.apply_slice(&indices);
+    return (vertices, indices);
+}
+
+
+pub fn generate_asteroid_vertices(seed: u64, cfg: &GlobalConfig) -> (Vec<[f32; 3]>, Vec<u32>) {
+    match cfg.asteroid_generator.generator {
+        AsteroidGeneratorType::Classic => generate_asteroid_vertices_classic(seed, cfg),
+        AsteroidGeneratorType::Procedural => generate_asteroid_vertices_procedural(seed, cfg),
+    }
+}
+
+fn generate_asteroid_vertices_classic(seed: u64, cfg: &GlobalConfig) -> (Vec<[f32; 3]>, Vec<u32>) {
+    let mut rng = ChaCha8Rng::seed_from_u64(seed);
+    let size = cfg.get_asteroid_size(seed);
+
+    /*
+    GENERATING ROUND ASTEROID AND OFFSET EVERY VERTEX
+          10 09 08
+       11          07
+    12                06
+    13       *        05
+    14                04
+       15          03 
+          00 01 02
+    (* - is ZERO)
+    */
+    
+
+    let sides = 16; // number of verteces
+    let mut vec = Vec::new();
+    let mut ind = Vec::new();
+    for side in 0..sides{
+        let mut point: [f32; 3];
+        match side {
+            0..=2 => point = [-1.0 + side as f32, -3.0, 0.0],
+            3 => point = [2.0, -2.0, 0.0],
+            4..=6 => point = [3.0, -1.0 + (side as f32 - 4.), 0.0],
+            7 => point = [2.0, 2.0, 0.0],
+            8..=10 => point = [1.0 - (side as f32 - 8.), 3.0, 0.0],
+            11 => point = [-2.0, 2.0, 0.0],
+            12..=14 => point = [-3.0, 1.0 - (side as f32 - 12.), 0.0],
+            15 => point = [-2.0, -2.0, 0.0],
+            _ => point = [-10.0, -10.0, -10.0]
+        };
+        //                            pre-mul            rand             *    max off   | * size 
+        point = point.map(|v| (v * 2.0  + rng.gen_range(-100..101) as f32 * 0.01 ) * size as f32);  
+        point[2] = 0.0;
+        vec.push(point);
+        ind.push(side);
+        ind.push((side + 1) % sides); // for loop
+    }
+    (vec, ind)
+}
+
+
+/*
+    EVERY SHAPE IS A RADIUS FUNCTION OF ANGLE AROUND ZERO, SO POLYGON IS ALWAYS STAR-SHAPED
+    ROUND     - jittered circle
+    ELONGATED - jittered ellipse
+    CRATERED  - circle with 1..3 dents
+    CLUSTERED - outline of 2..3 overlapping blobs, every blob contains zero
+*/
+fn generate_asteroid_vertices_procedural(seed: u64, cfg: &GlobalConfig) -> (Vec<[f32; 3]>, Vec<u32>) {
+    let params = cfg.get_asteroid_params(seed);
+    let mut rng = ChaCha8Rng::seed_from_u64(seed.rotate_left(32)); // params already used stream of seed itself
+    let radius = 6. * params.size as f32 + 2.;
+    let sides = 12 + params.size as u32 * 2;
+    let jitter = 0.12;
+
+    let radius_at: Box<dyn Fn(f32) -> f32> = match params.shape {
+        AsteroidShape::Round => Box::new(move |_| radius),
+        AsteroidShape::Elongated => {
+            let stretch: f32 = rng.gen_range(1.5..2.2);
+            let rotation = rng.gen_range(0.0..PI);
+            let (a, b) = (radius * stretch.sqrt(), radius / stretch.sqrt()); // same area as round
+            Box::new(move |angle: f32| {
+                let angle = angle - rotation;
+                a * b / ((b * angle.cos()).powi(2) + (a * angle.sin()).powi(2)).sqrt()
+            })
+        }
+        AsteroidShape::Cratered => {
+            let craters: Vec<(f32, f32, f32)> = (0..rng.gen_range(1..=3)).map(|_| (
+                rng.gen_range(0.0..PI * 2.), // center
+                rng.gen_range(0.35..0.7), // angular half width
+                rng.gen_range(0.25..0.45), // depth
+            )).collect();
+            Box::new(move |angle: f32| {
+                let mut r = radius;
+                for (center, width, depth) in craters.iter(){
+                    let d = (angle - center + PI).rem_euclid(PI * 2.) - PI;
+                    if d.abs() < *width{
+                        r -= radius * depth * (d / width * PI / 2.).cos();
+                    }
+                }
+                r.max(radius * 0.4) // overlapping craters
+            })
+        }
+        AsteroidShape::Clustered => {
+            let blobs: Vec<(Vec2, f32)> = (0..rng.gen_range(2..=3)).map(|_| {
+                let r = radius * rng.gen_range(0.55..0.8);
+                (Vec2::from_angle(rng.gen_range(0.0..PI * 2.)) * r * rng.gen_range(0.3..0.6), r)
+            }).collect();
+            Box::new(move |angle: f32| {
+                let dir = Vec2::from_angle(angle);
+                blobs.iter().map(|(center, r)| { // far intersection of ray from zero with blob circle
+                    let projection = center.dot(dir);
+                    projection + (r * r - center.length_squared() + projection * projection).max(0.).sqrt()
+                }).fold(0., f32::max)
+            })
+        }
+    };
+
+    let mut vec = Vec::new();
+    let mut ind = Vec::new();
+    for side in 0..sides{
+        let angle = side as f32 / sides as f32 * PI * 2.;
+        let r = radius_at(angle) * (1. + rng.gen_range(-jitter..jitter));
+        let point = Vec2::from_angle(angle) * r;
+        vec.push([point.x, point.y, 0.]);
+        ind.push(side);
+        ind.push((side + 1) % sides); // for loop
+    }
+    (vec, ind)
+}
+
+
+fn prepate_for_polyline(vec: Vec<[f32; 3]>, ind: Vec<u32>) -> (Vec<Vect>, Vec<[u32; 2]>) {
+    let mut vertices: Vec<Vect> = Vec::new();
+    let mut indexes = Vec::new();
+    for i in 0..ind.len(){
+        if i * 2 + 1 >= ind.len() {break}
+
+        indexes.push([ind[i*2], ind[i*2 + 1]])
+    }
+    for v in vec.iter(){
+        vertices.push(Vect::from((v[0], v[1])));
+    }
+    
+    (vertices, indexes)
+}
+
+
+pub fn spawn_asteroid(
+    seed: u64,
+    velocity: Velocity,
+    transform: Transform,
+    meshes: &mut ResMut<Assets<Mesh>>,
+    materials: &mut ResMut<Assets<ColorMaterial>>,
+    commands: &mut Commands,
+    object_id: u64,
+    hp: u8,
+    cfg: &GlobalConfig,
+    //asset_server: Res<AssetServer>,
+) -> Entity{
+    
+    let mut mesh = Mesh::new(PrimitiveTopology::LineList);
+    let mut shadow_mesh = Mesh::new(PrimitiveTopology::TriangleList);
+
+    let seed = seed;
+    
+    let (vec, ind) = generate_asteroid_vertices(seed, cfg);
+    let material_stats = cfg.asteroid_generator.get_material_stats(cfg.get_asteroid_params(seed).material);
+    let mut shadow_vec = vec.clone();
+    let mut shadow_ind = vec![];
+    shadow_vec.push([0., 0., 0.,]);
+    for i in 0..ind.len(){
+        shadow_ind.push(*ind.get(i).unwrap());
+        if (i + 1) % 2 == 0{
+            shadow_ind.push(shadow_vec.len() as u32 - 1);
+        }
+    }
+    mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, vec.clone());
+    shadow_mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, shadow_vec.clone());
+    mesh.set_indices(Some(Indices::U32(ind.clone())));
+    shadow_mesh.set_indices(Some(Indices::U32(shadow_ind.clone())));
+    mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, vec![material_stats.color.as_rgba_f32(); vec.len()]);
+    shadow_mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, vec![Color::BLACK.as_rgba_f32(); shadow_vec.len()]);
+    
+    let (vertices, indices) = prepate_for_polyline(vec, ind);
+    //let (vertices, indices) = prepate_for_trimesh(vec, ind);
+    let mut summ = Vec2::ZERO;
+    let mut count = 0;
+    for vert in vertices.iter(){
+        summ += *vert;
+        count += 1;
+    }
+    let center = summ / count as f32;
+
+    let shadow = commands.spawn((
+        MaterialMesh2dBundle { //MESH
+            mesh: Mesh2dHandle(meshes.add(shadow_mesh)),
+            transform: Transform { translation: (-center).extend(-1.), scale: Vec3::splat(1.), ..default()},
+            material: materials.add(ColorMaterial::default()), //ColorMaterial::from(texture_handle)
+            ..default()
+        },
+    )).id();
+
+    return commands.spawn((
+        RigidBody::Dynamic,
+        //TransformBundle::from(Transform::from_xyz(0.0, 5.0, 0.0)), // SPAWN POSITION
+        velocity,
+        Friction::default(),
+        GravityScale(0.0),
+        Sleeping::disabled(),
+        Ccd::enabled(),
+        Object{
+            id: object_id,
+            object_type: ObjectType::Asteroid{seed: seed, hp: hp}
+        },
+        Collider::convex_decomposition(&vertices, &indices),
+        ColliderMassProperties::Density(material_stats.density),
+        //Collider::trimesh(vertices, indices), // trimesh is shit for dynamic bodies
+        
+        //Collider::ball(get_asteroid_size(seed) as f32 * 10.0),
+        Restitution {
+            coefficient: 0.1,
+            combine_rule: CoefficientCombineRule::Average,
+        },
+        Name::new("ASTEROID"),
+        Asteroid, // TAG get_asteroid_size(seed) * 2 - 1 
+        
+    )).insert(MaterialMesh2dBundle { //MESH
+        mesh: Mesh2dHandle(meshes.add(mesh)),
+        transform: transform.with_scale(Vec3::splat(1.)),
+        material: materials.add(ColorMaterial::default()), //ColorMaterial::from(texture_handle)
+        ..default()
+    }).add_child(shadow).id();
+}
+
+pub fn spawn_ship(
+    mesh_only: bool,
+    meshes: &mut ResMut<Assets<Mesh>>,
+    materials: &mut ResMut<Assets<ColorMaterial>>,
+    commands: &mut Commands,
+    player_data: &ClientData,
+    cfg: &mut ResMut<GlobalConfig>,
+    time: &Time,
+) -> Entity {
+    let color = Color::from(player_data.color).as_rgba_f32();
+    let target_style = player_data.style;
+    
+    let (triangle_vertices, mut triangle_indices) = get_ship_vertices(target_style);
+
+    
+
+    let mut vertices = vec![];
+    let mut vertex_colors = vec![];
+    let mut indices = vec![];
+
+
+    let mut style2 = target_style.clone();
+    let mut bits: [bool; 8] = [false; 8];
+        
+    for n in 0..=7{
+        let m = 2_u8.pow(7 - n);
+        //print!("{:?}", m);
+        let i = style2 / m;
+        //println!(" {:?}", i);
+        if i == 1{
+            bits[n as usize] = i != 0;
+        }
+        style2 = style2 % m;
+    }
+        
+        
+    //let is_lined = bits[2];
+    //let is_spear = bits[3];
+    //let is_spikes = bits[4];
+    //let is_gem = bits[5];
+    //let is_shards = bits[6];
+    
+    let offset: f32 = -(1. / 3.); // for fixing center of body. // todo: maybe it outdated
+
+    let is_aspects = bits[7];
+    let is_lined = bits[2];
+    for i in 0..triangle_vertices.len(){
+        let mut v = triangle_vertices[i].iter().map(|&p| Vec3::from((p.0, p.1 - 2.*offset, 0.))).collect::<Vec<_>>();
+
+        let is_body = triangle_vertices[i].contains(&(0., 5.));
+        let color = if is_body && is_aspects {Color::WHITE.as_rgba_f32()} else {color};
+
+        vertex_colors.append(&mut vec![color; v.len()]);
+        vertices.append(&mut v);
+        indices.append(&mut triangle_indices[i]);
+
+        //let ind = indices[i].clone();
+        //
+        //if ship_style.2{
+        //    shapes.push(epaint::Shape::line(v, egui::Stroke{width:1., color: color}))
+        //} else {
+        //    shapes.push(epaint::Shape::mesh(
+        //        epaint::Mesh{
+        //            indices: ind,
+        //            vertices: v.iter().map(|&p| Vertex { pos: p, uv: Pos2{x: 0., y:0.}, color: color }).collect::<Vec<_>>(),
+        //            ..default()
+        //        }
+        //    ));
+        //}
+    }
+    let mut mesh = if is_lined{
+        Mesh::new(PrimitiveTopology::LineList)
+    } else {
+        Mesh::new(PrimitiveTopology::TriangleList)
+    };
+    vertices = vertices.iter().map(|x| *x * 3.).collect();
+    mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, vertices);        
+    mesh.set_indices(Some(Indices::U32(indices)));
+    mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, vertex_colors);
+
+    let entity = if !mesh_only{
+        commands.spawn((
+            RigidBody::Dynamic,
+            Velocity {              // VELOCITY
+                linvel: Vec2::new(0.0, 0.0),
+                angvel: 0.0
+            },
+            Friction::default(),
+            ColliderMassProperties::Density(2.),
+            GravityScale(0.0),
+            Sleeping::disabled(),
+            Ccd::enabled(),
+            Collider::trimesh(vec![Vec2::new(0., 17.), Vec2::new(-9., -4.), Vec2::new(9., -4.), Vec2::new(0., -13.)], vec![[0, 1, 3], [3, 2, 0]]),
+            Restitution {
+                coefficient: 0.1,
+                combine_rule: CoefficientCombineRule::Average,
+            },
+            Name::new("Player"),
+            ActiveEvents::CONTACT_FORCE_EVENTS,
+            Ship,
+            Object{
+                id: player_data.object_id,
+                object_type: ObjectType::Ship { style: target_style, color: player_data.color, shields: cfg.player_shields, hp: cfg.player_hp }
+            },
+            ShipStatuses::on_spawn(cfg),
+
+            ShipState::Regular
+        )).insert((
+            LastDamageTaken{time: 0.},
+            ShipWeapon::new(WeaponType::Blaster, cfg),
+            ContactForceEventThreshold(cfg.collision_damage_threshold),
+            Damping { linear_damping: 0.1, angular_damping: 1.0 },
+        )).insert(MaterialMesh2dBundle { //MESH
+            
+                mesh: Mesh2dHandle(meshes.add(mesh)),
+                transform: Transform::from_translation(Vec3::ZERO),
+                material: materials.add(ColorMaterial::default()), //ColorMaterial::from(texture_handle)
+                ..default()
+            },
+        ).id()
+    } else {
+        commands.spawn(
+            MaterialMesh2dBundle { //MESH
+                mesh: Mesh2dHandle(meshes.add(mesh)),
+                transform: Transform::from_translation(Vec3::ZERO),
+                    //,
+                material: materials.add(ColorMaterial::default()), //ColorMaterial::from(texture_handle)
+                ..default()
+            },
+        ).id()
+    };
+    return entity
+}
+
+
+pub fn spawn_powerup(
+    powerup_type: PowerUPType,
+    lifetime: f32,
+    pos: Vec3,
+    commands: &mut Commands,
+    meshes: &mut ResMut<Assets<Mesh>>,
+    materials: &mut ResMut<Assets<ColorMaterial>>,
+    asset_server: &Res<AssetServer>,
+    cfg: &GlobalConfig,
+    object_id: u64,
+) -> Entity{
+    let mut mesh = Mesh::new(PrimitiveTopology::LineList);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, vec![
+        Vec3{x: 1., y: 1., z: 1.},
+        Vec3{x: 1., y: 1., z:-1.},
+        Vec3{x: 1., y:-1., z: 1.},
+        Vec3{x: 1., y:-1., z:-1.},
+        Vec3{x:-1., y: 1., z: 1.},
+        Vec3{x:-1., y: 1., z:-1.},
+        Vec3{x:-1., y:-1., z: 1.},
+        Vec3{x:-1., y:-1., z:-1.},
+    ]);
+    mesh.set_indices(Some(Indices::U32(vec![
+        0, 1,
+        0, 2,
+        1, 3,
+        2, 3,
+        4, 5,
+        4, 6,
+        5, 7,
+        6, 7,
+        0, 4,
+        1, 5,
+        2, 6,
+        3, 7
+        ])));
+    mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, vec![(Color::from([0.05, 0.05, 0.05, 1.])).as_rgba_f32(); 8]);
+
+    let powerup_box = commands.spawn((
+        MaterialMesh2dBundle { //MESH
+            mesh: Mesh2dHandle(meshes.add(mesh)),
+            transform: Transform::from_xyz(0., 0., 0.).with_scale(Vec3::splat(5.)),
+            material: materials.add(ColorMaterial::default()), //ColorMaterial::from(texture_handle)
+            ..default()
+        },
+        PowerUPCube
+    )).id();
+
+
+
+    let powerup_image = commands.spawn((
+        SpriteBundle {
+            texture: asset_server.load(powerup_type.texture_path(cfg)),
+            transform: Transform::from_xyz(0., 0., 0.01).with_scale(Vec3::splat(1.2)),
+            ..default()
+        },
+        PowerUPImage
+    )).id();
+    return commands.spawn((
+        Object{
+            id: object_id,
+            object_type: ObjectType::PickUP { pickup_type: powerup_type, lifetime },
+        },
+        PowerUP,
+        RigidBody::Fixed,
+        ActiveEvents::COLLISION_EVENTS,
+        Sensor,
+        Collider::ball(10.),
+        Velocity::zero(),
+        VisibilityBundle::default(),
+        TransformBundle::default()
+    )).insert(Transform::from_translation(pos).with_scale(Vec3::splat(1.))).add_child(powerup_image).add_child(powerup_box).id();
+}
+
+#[allow(dead_code)]
+pub fn update_powerups_animation(
+    mut images_q: Query<&mut Transform, (With<PowerUPImage>, Without<Object>, Without<PowerUPCube>)>,
+    mut cube_q: Query<&mut Transform, (With<PowerUPCube>, Without<Object>, Without<PowerUPImage>)>,
+    time: Res<Time>,
+){
+    for mut cube in cube_q.iter_mut(){
+        cube.rotate_local_x(time.delta_seconds() * 0.5);
+        cube.rotate_local_y(-time.delta_seconds() * 1.);
+        cube.rotate_local_z(time.delta_seconds() * 2.);
+        cube.scale = Vec3::splat(5. + ((time.elapsed_seconds() * 3.).sin() + 1.) * 0.5);
+    }
+    for mut image in images_q.iter_mut(){
+        image.rotate_local_y(time.delta_seconds() * 2.);
+    }
+}
+
+pub fn debug_chunk_render( // todo: do something :D
+    chunks_q: Query<(&Chunk, Entity)>,
+    mut cfg: ResMut<GlobalConfig>,
+    asset_server: Res<AssetServer>,
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    keys: Res<Input<KeyCode>>, // todo: add check previous value
+){
+    if keys.just_pressed(KeyCode::F3){
+        cfg.debug_render = !cfg.debug_render;
+    }
+
+    if !cfg.debug_render {
+        for (_, e) in chunks_q.iter(){
+            commands.entity(e).despawn();
+        }
+        return;
+    }
+
+    let font = asset_server.load("../assets/fonts/VecTerminus12Medium.otf");
+    let text_style = TextStyle {
+        font: font.clone(),
+        font_size: 60.0,
+        color: Color::GRAY,
+    };
+    let mut chunks_around: Vec<Vec2> = vec![];
+    for x in -1..(cfg.map_size_chunks.x as i32+1){
+        for y in -1..(cfg.map_size_chunks.y as i32+1){
+            chunks_around.push(Vec2{x: x as f32, y: y as f32})
+        }
+    }
+    let mut existing_debug_chunks: Vec<Vec2> = vec![];
+    for (c, _) in chunks_q.iter(){
+        existing_debug_chunks.push(c.pos);
+    }
+    for chunk in chunks_around.iter(){
+        let isreal = cfg.chunk_to_real_chunk_v2(chunk) == *chunk;
+        if cfg.debug_render {
+            if !existing_debug_chunks.contains(chunk){
+                let mut mesh = Mesh::new(PrimitiveTopology::LineList);
+                let chunk_size = cfg.single_chunk_size;
+                let vec = vec![
+                    [-chunk_size.x/2. + 1., -chunk_size.y/2. + 1., 0.],
+                    [ chunk_size.x/2. - 1., -chunk_size.y/2. + 1., 0.],
+                    [ chunk_size.x/2. - 1.,  chunk_size.y/2. - 1., 0.],
+                    [-chunk_size.x/2. + 1.,  chunk_size.y/2. - 1., 0.],
+                ];
+                let ind = vec![0, 1, 1, 2, 2, 3, 3, 0];
+                mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, vec);
+                mesh.set_indices(Some(Indices::U32(ind)));
+                if isreal{
+                    mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, vec![Color::DARK_GRAY.as_rgba_f32(); 4]);
+                } else {
+                    mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, vec![Color::ORANGE_RED.as_rgba_f32(); 4]);
+                }
+                let real_chunk_pos = cfg.chunk_to_real_chunk_v2(chunk);
+                commands.spawn((
+                    Text2dBundle {
+                        text: Text::from_section( format!("{} {}\n({} {})", chunk.x, chunk.y, real_chunk_pos.x, real_chunk_pos.y), text_style.clone())
+                            .with_alignment(TextAlignment::Center),
+                        ..default()
+                    },
+                    Chunk{pos: Vec2{x: chunk.x, y: chunk.y}},
+                )).insert(
+                    MaterialMesh2dBundle { //MESH
+                        mesh: Mesh2dHandle(meshes.add(mesh)),
+                        transform: Transform::from_scale(Vec3::splat(1.)),
+                        material: materials.add(ColorMaterial::default()), //ColorMaterial::from(texture_handle)
+                        ..default()
+                    }
+                )
+                .insert(Transform::from_xyz(
+                    chunk_size.x/2. + chunk.x * chunk_size.x, 
+                    chunk_size.y/2. + chunk.y * chunk_size.y, 
+                    -1.0));
+            } else {
+                existing_debug_chunks.remove(existing_debug_chunks.iter().position(|x| x == chunk).unwrap());
+            }
+        }   
+    }
+}
+
+#[allow(dead_code)]
+pub fn snap_objects(                                                     
+    cfg: ResMut<GlobalConfig>,
+    mut objects: Query<(&mut Transform, Option<&mut Velocity>, Option<&Bullet>, Entity), (With<Object>, Without<Puppet>)>, // ADD SNAPPING TO PUPPETS
+    mut commands: Commands,
+){
+    let xsize = cfg.map_size_chunks.x * cfg.single_chunk_size.x;
+    let ysize = cfg.map_size_chunks.y * cfg.single_chunk_size.y;
+    if let Some(walls) = cfg.topology.walls(){
+        // walls are colliders, this only catches bullets (no collider) and things that tunneled through
+        for (mut transform, velocity, bullet, entity) in objects.iter_mut(){
+            let pos = transform.translation.truncate();
+            let clamped = pos.clamp(Vec2::ZERO, Vec2::new(xsize, ysize));
+            if pos == clamped {continue;}
+            if bullet.is_some() && walls == BoundaryWalls::Solid{
+                commands.entity(entity).despawn_recursive();
+                continue;
+            }
+            transform.translation = clamped.extend(transform.translation.z);
+            if let Some(mut velocity) = velocity{
+                let factor = if walls == BoundaryWalls::Bouncy {-1.} else {0.};
+                if pos.x != clamped.x {velocity.linvel.x *= factor;}
+                if pos.y != clamped.y {velocity.linvel.y *= factor;}
+            }
+        }
+        return;
+    }
+    for (mut transform, _, _, _) in objects.iter_mut(){
+        if transform.translation.x < 0.{
+            transform.translation.x = (transform.translation.x + xsize) % xsize;
+        } else {
+            transform.translation.x = transform.translation.x % xsize;
+        }
+        if transform.translation.y < 0.{
+            transform.translation.y = (transform.translation.y + ysize) % ysize;
+        } else {
+            transform.translation.y = transform.translation.y % ysize;
+        }
+    }
+}
+
+
+/// Keeps SpatialIndex in sync, only moved, new and removed objects are touched
+pub fn update_spatial_index(
+    mut index: ResMut<SpatialIndex>,
+    objects_q: Query<(Entity, &Object, Ref<Transform>), Without<Puppet>>,
+    mut removed: RemovedComponents<Object>,
+    cfg: Res<GlobalConfig>,
+){
+    let rebuild = !index.is_built_for(&cfg);
+    if rebuild {
+        index.clear_for(&cfg);
+    }
+    for entity in removed.read(){
+        index.remove(entity);
+    }
+    for (entity, object, transform) in objects_q.iter(){
+        if !rebuild && !transform.is_changed() && index.contains(entity) {continue;}
+        index.update(entity, object, transform.translation.truncate(), &cfg);
+    }
+}
+
+/// Stores ship positions of this tick for lag compensation
+pub fn record_ship_history(
+    mut history: ResMut<ShipHistory>,
+    ships_q: Query<(Entity, &Transform), (With<Ship>, Without<Puppet>)>,
+    tick: Res<ServerTick>,
+    cfg: Res<GlobalConfig>,
+){
+    let ships = ships_q.iter().map(|(entity, transform)| {
+        (entity, transform.translation.truncate(), transform.rotation.to_euler(EulerRot::XYZ).2)
+    }).collect();
+    history.record(tick.0, ships, cfg.max_rewind_ticks());
+}
+
+pub fn update_chunks_around(
+    loaded_chunks: Res<LoadedChunks>,
+    mut commands: Commands,
+
+    time: Res<Time>,
+    asset_server: Res<AssetServer>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+
+    chunks_q: Query<(&Chunk, Entity)>, // todo: do smt with chunk
+    mut cfg: ResMut<GlobalConfig>,
+
+    mut puppet_objects: Query<(&mut Transform, &Object, &Puppet, &mut Velocity, Entity), (With<Object>, With<Puppet>)>,
+    objects: Query<(&Transform, &Object, &Velocity, Entity), (With<Object>, Without<Puppet>)>,
+    index: Res<SpatialIndex>,
+
+    asteroid_q: Query<(&Asteroid,  &Collider), (With<Object>, Without<Puppet>)>,
+    bullet_q: Query<&Bullet, (With<Object>, Without<Puppet>)>,
+
+    clients_data: Res<ClientsData>
+    //ship_q: Query<&Ship, (With<Object>, Without<Puppet>)>,
+){
+    if !cfg.topology.is_wrapped(){ // no shadow chunks
+        for (_, _, _, _, puppet_entity) in puppet_objects.iter(){
+            commands.entity(puppet_entity).despawn_recursive();
+        }
+        return;
+    }
+
+    let mut chunks_around: Vec<Vec2> = vec![];
+    for c in loaded_chunks.chunks.iter(){
+        chunks_around.push(c.pos);
+    }
+    
+    // GET REAL CHUNKS AND DRAW OTHER CHUNKS FOR DEBUG
+    let mut real_chunks: Vec<Vec2> = vec![];
+
+
+    for chunk in chunks_around.iter(){
+        let isreal = cfg.chunk_to_real_chunk_v2(chunk) == *chunk;
+        if isreal {real_chunks.push(*chunk);};
+    }
+
+
+    // REAL OBJECTS ARE IN SPATIAL INDEX
+    let real_object = |id: u64| index.get_by_object_id(id).and_then(|entry| objects.get(entry.entity).ok());
+    
+    // GET NEED-TO-SHADOW-CHUNKS (NOT NEED) AROUND_CHUNKS - REAL_CHUNKS = SHADOW CHUNKS
+    // COLLECT AND MOVE EXISTED PUPPETS AND DELETE NOT NEEDED 
+    //                            id  chunk pos
+    let mut existing_puppets: Vec<(u64, i64, i64)> = vec![];
+    
+    for (mut puppet_transform, puppet_object, puppet, mut puppet_velocity, puppet_entity) in puppet_objects.iter_mut(){
+        
+        let puppet_position_chunk = cfg.pos_to_chunk(&puppet_transform.translation);
+        let key = (puppet_object.id, puppet_position_chunk.x as i64, puppet_position_chunk.y as i64);
+        let real = real_object(puppet_object.id);
+        if real.is_some() &&                                // COND 1 ( EXISTING OF REAL OBJECT )
+        chunks_around.contains(&puppet_position_chunk) &&    // COND 2 ( EXISTING OF REAL CHUNK )
+        puppet_position_chunk == puppet.binded_chunk.pos && // COND 3 ( STILL IN THEIR SHADOW CHUNK? )
+        !existing_puppets.contains(&key) &&                // COND 4 ( DOES THAT PUPPET ALREADY EXISTS )
+                                                            // COND 5 ( DOES REAL OBJECT IN THEIR CHUNK )
+        cfg.pos_to_real_chunk(&puppet_transform.translation) == cfg.pos_to_chunk(&real.unwrap().0.translation)
+        { 
+            // APPLY REAL OBJECT's TRANSFORMS
+            existing_puppets.push(key);
+            let (transform, _, velocity, _) = real.unwrap();
+            let offset = cfg.chunk_to_offset(&puppet_position_chunk);
+            puppet_transform.translation = (transform.translation % Vec3 { x: cfg.single_chunk_size.x, y: cfg.single_chunk_size.y, z: 1. }) + Vec3{x: offset.x, y: offset.y, z:0.};
+            puppet_transform.rotation = transform.rotation;
+            puppet_velocity.angvel = velocity.angvel;
+            puppet_velocity.linvel = velocity.linvel;
+        } else {
+
+            //println!("{:?}",  bullet_q.contains(*e));
+            //println!("COND1 {}", real_objects.contains_key(&puppet_object.id));
+            //println!("COND2 {}", chunks_around.contains(&puppet_position_chunk));
+            //println!("COND3 {}", puppet_position_chunk == puppet.binded_chunk.pos);
+            //println!("COND4 {} {:?}", !existing_puppets.contains(&key), key);
+            //println!("COND5 {}", map.pos_to_real_chunk(&puppet_transform.translation) == map.pos_to_chunk(&real_objects.get(&puppet_object.id).unwrap().0.translation));
+            
+            // DESPAWN
+            commands.entity(puppet_entity).despawn_recursive();
+        }
+    }
+    //println!("{:?}", existing_puppets);
+
+    // SPAWN NEW
+    for chunk in chunks_around.iter(){
+        if !real_chunks.contains(&chunk){ // IF CHUNK IS SHADOW-CHUNK
+            let real_chunk = cfg.chunk_to_real_chunk_v2(chunk);
+            let key = (real_chunk.x as u32, real_chunk.y as u32);
+            // OBJECTS IN CHUNK
+            for (transform, object, velocity, entity) in index.chunk(key).filter_map(|entry| objects.get(entry.entity).ok()){
+                if !existing_puppets.contains(&(object.id, chunk.x as i64, chunk.y as i64)){ // IF NOT ALREADY EXISTS
+                    let pos = (transform.translation % Vec3 { x: cfg.single_chunk_size.x, y: cfg.single_chunk_size.y, z: 1. }) + // INCHUNK OFFSET
+                        Vec3{x: chunk.x * cfg.single_chunk_size.x, y: chunk.y * cfg.single_chunk_size.y, z: 0.};             // CHUNK OFFSET
+                    
+                    match object.object_type{
+                        ObjectType::Asteroid {seed, hp} => {
+                            let entity = spawn_asteroid(seed, *velocity, transform.with_translation(pos), &mut meshes, &mut materials, &mut commands, object.id, cfg.get_asteroid_hp(seed), &cfg);
+                            commands.entity(entity).insert(
+                                (
+                                    Puppet {
+                                        id: object.id,
+                                        binded_chunk: Chunk {
+                                            pos: *chunk
+                                        }
+                                    },//.with_scale(Vec3::splat(2.))
+                                    Name::new("ASTEROID PUPPET"),
+                                )
+                            );
+                        },
+                        ObjectType::Bullet { previous_position, spawn_time, owner, extra_damage, weapon } => {
+                            let entity = spawn_bullet(velocity.linvel, extra_damage, weapon, transform.with_translation(pos), object.id, owner, spawn_time, &asset_server, &mut commands);
+                            commands.entity(entity).insert(
+                                (
+                                    Puppet {
+                                        id: object.id,
+                                        binded_chunk: Chunk {
+                                            pos: *chunk
+                                    }
+                                },
+                                Name::new("BULLET PUPPET"),
+                                )
+                            );
+                        },
+                        ObjectType::Ship { style, color, shields, hp } => {
+                            let player_data = clients_data.get_option_by_object_id(object.id);
+                            if player_data.is_some(){
+                                let player_data = player_data.unwrap();
+                                let entity = spawn_ship(false, &mut meshes, &mut materials, &mut commands, player_data, &mut cfg, &time);
+                                commands.entity(entity).insert((
+                                    *velocity,
+                                    Transform::from_translation(pos),
+                                    Name::new(format!("Player Puppet of {}:{}", object.id, player_data.client_id)),
+                                    ActiveEvents::CONTACT_FORCE_EVENTS,
+                                    Puppet {
+                                        id: object.id,
+                                        binded_chunk: Chunk {
+                                            pos: *chunk
+                                        }
+                                    },
+                                    Object{
+                                        id: object.id,
+                                        object_type: ObjectType::Ship { style, color, shields, hp }
+                                    },
+                                ));
+                            }
+                        }
+                        ObjectType::PickUP{ pickup_type, lifetime } => {
+                            let entity = spawn_powerup(pickup_type, lifetime, pos, &mut commands, &mut meshes, &mut materials, &asset_server, &cfg, object.id);
+                            commands.entity(entity).insert((
+                                *velocity,
+                                Transform::from_translation(pos),
+                                ActiveEvents::CONTACT_FORCE_EVENTS,
+                                Puppet {
+                                    id: object.id,
+                                    binded_chunk: Chunk {
+                                        pos: *chunk
+                                    }
+                                },
+                            ));
+                        }
+                    }
+                }
+            }
+        }
+    }
+    
+    return ()
+}
+
+
+pub fn spawn_bullet(
+    target_velocity: Vec2,
+    extra_damage: f32, // additional damage multiplier
+    weapon: WeaponType,
+    transform: Transform,
+    object_id: u64,
+    owner: u64,
+    spawn_time: f32, // time.elapsed().as_secs_f32()
+    asset_server: &Res<AssetServer>,
+    commands: &mut Commands,
+) -> Entity{
+    commands.spawn((
+        RigidBody::Dynamic,
+        //TransformBundle::from(Transform::from_xyz(0.0, 5.0, 0.0)), // SPAWN POSITION
+        Velocity {              // VELOCITY
+            linvel: target_velocity,
+            angvel: 0.0
+        },
+        Friction{ // DISABLE ROTATING WHET COLLIDING TO ANYTHING ( MAYBE REPLACE IT ONLY FOR WALLS FOR FUN )
+            coefficient: 0.0,
+            combine_rule: CoefficientCombineRule::Min
+        },
+        GravityScale(0.0),
+        Sleeping::disabled(),
+        Ccd::enabled(),
+        //Collider::cuboid(2., 30.),
+        Restitution {
+            coefficient: 1.,
+            combine_rule: CoefficientCombineRule::Multiply,
+        },
+        Name::new("Bullet"),
+        //Sensor,
+        ActiveEvents::COLLISION_EVENTS,
+        Object{
+            id: object_id,
+            object_type: ObjectType::Bullet{
+                previous_position: Transform::from_translation(transform.translation),
+                spawn_time: spawn_time,
+                owner: owner,
+                extra_damage: extra_damage,
+                weapon: weapon,
+            }
+        },
+        Bullet
+    ))
+    .insert(
+        SpriteBundle {
+            transform: Transform::from_matrix(Mat4::from_rotation_translation(Quat::from_rotation_z(Vec2::X.angle_between(target_velocity) + PI / 2.), transform.translation)),
+            texture: asset_server.load("bullet.png"),
+            sprite: Sprite { color: weapon.projectile_color(), ..default() },
+            ..default()
+    }).id()
+    
+}
+
+
+pub fn asteroids_refiller(
+    index: Res<SpatialIndex>,
+    mut cfg: ResMut<GlobalConfig>,
+    asteroids_q: Query<&Asteroid, Without<Puppet>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut rng: ResMut<GameRng>,
+    mut commands: Commands,
+){
+    if !cfg.fill_asteroids {return;}
+    if asteroids_q.into_iter().len() < cfg.map_size_chunks.x as usize * cfg.map_size_chunks.y as usize{
+        let seed = rng.0.gen::<u64>();
+        let pos = get_pos_to_spawn(&index, &cfg, &mut rng.0).extend(0.);
+        let linvel = match cfg.asteroid_motion {
+            AsteroidMotion::Still => Vec2::ZERO,
+            AsteroidMotion::Drift { max_speed } => Vec2::from_angle(rng.0.gen::<f32>() * PI * 2.) * rng.0.gen::<f32>() * max_speed,
+            AsteroidMotion::FlowField { .. } => cfg.flow_at(pos.truncate()),
+        };
+        let velocity = Velocity{
+            linvel: linvel,
+            angvel: (rng.0.gen::<f32>() - 0.5) * 5. 
+        };
+        let object_id = cfg.new_id();
+        spawn_asteroid(seed, velocity, Transform::from_translation(pos), &mut meshes, &mut materials, &mut commands, object_id, cfg.get_asteroid_hp(seed), &cfg);
+    }
+}
+
+
+/// Flow field for asteroids and gravity wells for everything. Ships and asteroids get rapier external force,
+/// bullets have no collider (so no mass) and their velocity is changed directly.
+pub fn apply_ambient_forces(
+    mut bodies_q: Query<(Entity, &Object, &mut Velocity, &Transform, Option<&mut ExternalForce>, Option<&ReadMassProperties>), Without<Puppet>>,
+    cfg: Res<GlobalConfig>,
+    time: Res<Time>,
+    mut commands: Commands,
+){
+    let flow_steering = match cfg.asteroid_motion {
+        AsteroidMotion::FlowField { steering, .. } => Some(steering),
+        _ => None
+    };
+    for (entity, object, mut velocity, transform, external_force, mass_properties) in bodies_q.iter_mut(){
+        let pos = transform.translation.truncate();
+        let mut acceleration = Vec2::ZERO;
+        for well in cfg.gravity_wells.iter(){
+            acceleration += well.acceleration(&cfg, pos);
+        }
+        match object.object_type {
+            ObjectType::Asteroid { .. } => {
+                if let Some(steering) = flow_steering{
+                    acceleration += (cfg.flow_at(pos) - velocity.linvel) * steering;
+                }
+            }
+            ObjectType::Bullet { .. } => {
+                velocity.linvel += acceleration * time.delta_seconds();
+                continue;
+            }
+            ObjectType::Ship { .. } => {}
+            ObjectType::PickUP { .. } => {continue;}
+        }
+        let mass = mass_properties.map(|m| m.get().mass).unwrap_or(0.);
+        match external_force {
+            Some(mut external_force) if mass > 0. => {
+                external_force.force = acceleration * mass;
+            }
+            Some(_) => {
+                velocity.linvel += acceleration * time.delta_seconds();
+            }
+            None => {
+                commands.entity(entity).insert((ExternalForce::default(), ReadMassProperties::default()));
+            }
+        }
+    }
+}
+
+/// Spawns gravity well meshes in map and in shadow chunks around it (on torus), respawns them when wells in config are changed
+pub fn render_gravity_wells(
+    cfg: Res<GlobalConfig>,
+    sprites_q: Query<Entity, With<GravityWellSprite>>,
+    mut rendered: Local<Option<(Vec<GravityWell>, MapTopology)>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut commands: Commands,
+){
+    let despawned = sprites_q.is_empty() && !cfg.gravity_wells.is_empty(); // e.g. after leaving game
+    let key = (cfg.gravity_wells.clone(), cfg.topology);
+    if rendered.as_ref() == Some(&key) && !despawned {return;}
+    *rendered = Some(key);
+    for entity in sprites_q.iter(){
+        commands.entity(entity).despawn_recursive();
+    }
+    for well in cfg.gravity_wells.iter(){
+        let (core_color, halo_color) = match well.kind {
+            GravityWellKind::Star => (Color::rgb(4., 3., 1.2), Color::rgba(1., 0.8, 0.3, 0.04)),
+            GravityWellKind::BlackHole => (Color::BLACK, Color::rgba(0.6, 0.2, 1., 0.06)),
+        };
+        let core = meshes.add(Mesh::from(shape::Circle::new(well.core_radius)));
+        let ring = meshes.add(Mesh::from(shape::Circle::new(well.core_radius * 1.3)));
+        let halo = meshes.add(Mesh::from(shape::Circle::new(well.radius)));
+        for offset in cfg.wrap_offsets(){
+            let pos = well.position + offset;
+            commands.spawn((
+                MaterialMesh2dBundle {
+                    mesh: Mesh2dHandle(halo.clone()),
+                    transform: Transform::from_translation(pos.extend(-5.)),
+                    material: materials.add(ColorMaterial::from(halo_color)),
+                    ..default()
+                },
+                GravityWellSprite,
+                Name::new("GRAVITY WELL"),
+            )).with_children(|parent| {
+                if well.kind == GravityWellKind::BlackHole {
+                    parent.spawn(MaterialMesh2dBundle {
+                        mesh: Mesh2dHandle(ring.clone()),
+                        transform: Transform::from_xyz(0., 0., 0.5),
+                        material: materials.add(ColorMaterial::from(Color::rgb(2., 0.8, 3.))),
+                        ..default()
+                    });
+                }
+                parent.spawn(MaterialMesh2dBundle {
+                    mesh: Mesh2dHandle(core.clone()),
+                    transform: Transform::from_xyz(0., 0., 1.),
+                    material: materials.add(ColorMaterial::from(core_color)),
+                    ..default()
+                });
+            });
+        }
+    }
+}
+
+
+/// Spawns map obstacles in map and in shadow chunks around it (on torus), respawns them when obstacles in config are changed
+pub fn update_map_obstacles(
+    cfg: Res<GlobalConfig>,
+    obstacles_q: Query<Entity, With<Obstacle>>,
+    mut spawned: Local<Option<(Vec<MapObstacle>, MapTopology)>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut commands: Commands,
+){
+    let despawned = obstacles_q.is_empty() && !cfg.obstacles.is_empty(); // e.g. after leaving game
+    let key = (cfg.obstacles.clone(), cfg.topology);
+    if spawned.as_ref() == Some(&key) && !despawned {return;}
+    *spawned = Some(key);
+    for entity in obstacles_q.iter(){
+        commands.entity(entity).despawn_recursive();
+    }
+    for obstacle in cfg.obstacles.iter(){
+        for offset in cfg.wrap_offsets(){
+            spawn_obstacle(obstacle, offset, &cfg, &mut meshes, &mut materials, &mut commands);
+        }
+    }
+}
+
+#[cfg_attr(not(feature = "client"), allow(unused_variables, unused_mut))] // headless server needs only colliders
+pub fn spawn_obstacle(
+    obstacle: &MapObstacle,
+    offset: Vec2,
+    cfg: &GlobalConfig,
+    meshes: &mut ResMut<Assets<Mesh>>,
+    materials: &mut ResMut<Assets<ColorMaterial>>,
+    commands: &mut Commands,
+) -> Entity {
+    match *obstacle {
+        MapObstacle::Asteroid { seed, position, rotation } => {
+            let (vec, ind) = generate_asteroid_vertices(seed, cfg);
+            #[cfg(feature = "client")]
+            let (mesh, fill_mesh) = {
+                let mut mesh = Mesh::new(PrimitiveTopology::LineList);
+                mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, vec.clone());
+                mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, vec![(Color::GRAY * 2.).as_rgba_f32(); vec.len()]);
+                mesh.set_indices(Some(Indices::U32(ind.clone())));
+                let mut fill = vec.clone();
+                fill.push([0., 0., 0.]);
+                let mut fill_ind = vec![];
+                for pair in ind.chunks(2){
+                    fill_ind.extend([pair[0], pair[1], fill.len() as u32 - 1]);
+                }
+                let mut fill_mesh = Mesh::new(PrimitiveTopology::TriangleList);
+                fill_mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, fill.clone());
+                fill_mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, vec![Color::BLACK.as_rgba_f32(); fill.len()]);
+                fill_mesh.set_indices(Some(Indices::U32(fill_ind)));
+                (mesh, fill_mesh)
+            };
+            let (vertices, indices) = prepate_for_polyline(vec, ind);
+            let mut entity = commands.spawn((
+                RigidBody::Fixed,
+                Collider::convex_decomposition(&vertices, &indices),
+                SpatialBundle::from_transform(Transform::from_translation((position + offset).extend(0.)).with_rotation(Quat::from_rotation_z(rotation))),
+                Obstacle,
+                Name::new("OBSTACLE"),
+            ));
+            #[cfg(feature = "client")]
+            entity.insert((
+                Mesh2dHandle(meshes.add(mesh)),
+                materials.add(ColorMaterial::default()),
+            )).with_children(|parent| {
+                parent.spawn(MaterialMesh2dBundle {
+                    mesh: Mesh2dHandle(meshes.add(fill_mesh)),
+                    transform: Transform::from_xyz(0., 0., -1.),
+                    material: materials.add(ColorMaterial::default()),
+                    ..default()
+                });
+            });
+            entity.id()
+        }
+        MapObstacle::Wall { from, to, thickness } => {
+            let length = (to - from).length();
+            let angle = Vec2::X.angle_between(to - from);
+            let mut entity = commands.spawn((
+                RigidBody::Fixed,
+                Collider::cuboid(length / 2., thickness / 2.),
+                SpatialBundle::from_transform(Transform::from_translation(((from + to) / 2. + offset).extend(0.)).with_rotation(Quat::from_rotation_z(if angle.is_nan() {0.} else {angle}))),
+                Obstacle,
+                Name::new("OBSTACLE"),
+            ));
+            #[cfg(feature = "client")]
+            entity.insert((
+                Mesh2dHandle(meshes.add(Mesh::from(shape::Quad::new(Vec2::new(length, thickness))))),
+                materials.add(ColorMaterial::from(Color::GRAY * 2.)),
+            ));
+            entity.id()
+        }
+    }
+}
+
+/// Spawn point from map if there are any, otherwise free place like for asteroids. Always inside safe zone.
+pub fn get_ship_spawn_pos(
+    index: &SpatialIndex,
+    cfg: &ResMut<GlobalConfig>,
+    safe_zone: &SafeZone,
+    rng: &mut ChaCha8Rng,
+) -> Vec2 {
+    let pos = if cfg.spawn_points.is_empty(){
+        get_pos_to_spawn(index, cfg, rng)
+    } else {
+        cfg.spawn_points[rng.gen_range(0..cfg.spawn_points.len())]
+    };
+    if safe_zone.contains(pos) {
+        pos
+    } else {
+        safe_zone.center + (pos - safe_zone.center).clamp_length_max(safe_zone.radius * 0.8)
+    }
+}
+
+/// Spawns walls around map for bounded topologies
+#[cfg_attr(not(feature = "client"), allow(unused_variables, unused_mut))] // headless server needs only colliders
+pub fn update_map_bounds(
+    cfg: Res<GlobalConfig>,
+    bounds_q: Query<Entity, With<MapBound>>,
+    mut spawned: Local<Option<(MapTopology, Vec2)>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut commands: Commands,
+){
+    let world_size = cfg.map_size_chunks * cfg.single_chunk_size;
+    let despawned = bounds_q.is_empty() && cfg.topology.walls().is_some(); // e.g. after leaving game
+    if spawned.as_ref() == Some(&(cfg.topology, world_size)) && !despawned {return;}
+    *spawned = Some((cfg.topology, world_size));
+    for entity in bounds_q.iter(){
+        commands.entity(entity).despawn_recursive();
+    }
+    let Some(walls) = cfg.topology.walls() else {return;};
+    let restitution = match walls {
+        BoundaryWalls::Solid => Restitution{coefficient: 0., combine_rule: CoefficientCombineRule::Min},
+        BoundaryWalls::Bouncy => Restitution{coefficient: 1., combine_rule: CoefficientCombineRule::Max},
+    };
+    let thickness = 100.;
+    for (center, size) in [ // outside of map, so map size is not changed
+        (Vec2::new(world_size.x / 2., -thickness / 2.), Vec2::new(world_size.x + thickness * 2., thickness)),
+        (Vec2::new(world_size.x / 2., world_size.y + thickness / 2.), Vec2::new(world_size.x + thickness * 2., thickness)),
+        (Vec2::new(-thickness / 2., world_size.y / 2.), Vec2::new(thickness, world_size.y)),
+        (Vec2::new(world_size.x + thickness / 2., world_size.y / 2.), Vec2::new(thickness, world_size.y)),
+    ]{
+        let mut entity = commands.spawn((
+            RigidBody::Fixed,
+            Collider::cuboid(size.x / 2., size.y / 2.),
+            restitution,
+            SpatialBundle::from_transform(Transform::from_translation(center.extend(-2.))),
+            MapBound,
+            Name::new("MAP BOUND"),
+        ));
+        #[cfg(feature = "client")]
+        entity.insert((
+            Mesh2dHandle(meshes.add(Mesh::from(shape::Quad::new(size)))),
+            materials.add(ColorMaterial::from(Color::DARK_GRAY)),
+        ));
+    }
+}
+
+pub fn render_safe_zone(
+    safe_zone: Res<SafeZone>,
+    mut gizmos: Gizmos,
+){
+    if safe_zone.radius.is_finite(){
+        gizmos.circle_2d(safe_zone.center, safe_zone.radius, Color::rgb(3., 0.3, 0.3)).segments(128);
+    }
+}
+
+// todo: make fast version, time usage might be insane!
+pub fn get_pos_to_spawn( 
+    index: &SpatialIndex,
+    cfg: &ResMut<GlobalConfig>,
+    rng: &mut ChaCha8Rng,
+) -> Vec2 {
+    let size = cfg.map_size_chunks;
+    let size = (size.x as u32, size.y as u32);
+    // chunks with player overrides chunks around
+    let mut chunks_with_player_set: HashSet<(u32, u32)> = HashSet::new();
+    let mut chunks_around_player_set: HashSet<(u32, u32)> = HashSet::new();
+
+    
+    let mut all_chunks: HashMap<u32, Vec<((u32, u32), Vec<Vec2>)>> = HashMap::new();;
+    for x in 0..size.0{
+        for y in 0..size.1{
+            let key = (x, y);
+            let objects: Vec<Vec2> = index.chunk(key).map(|entry| entry.position).collect();
+            if !objects.is_empty(){
+                let number = objects.len() as u32;
+                let has_player = index.chunk(key).any(|entry| entry.kind == SpatialKind::Ship);
+
+                if all_chunks.contains_key(&number){
+                    all_chunks.get_mut(&number).unwrap().push((key, objects));
+                } else {
+                    all_chunks.insert(number,  vec![(key, objects)]);
+                }   
+
+                if has_player {
+                    let mut chunks: HashSet<(i32, i32)> = HashSet::new();
+                    for cx in x as i32 - 1 ..= x as i32 + 1 {
+                        for cy in y as i32 - 1 ..= y as i32 + 1 {
+                            chunks.insert((cx, cy));
+                        }
+                    }
+                    for chunk_pos in chunks.iter(){
+                        let real_chunk_pos = ((chunk_pos.0 + size.0 as i32) % size.0 as i32, (chunk_pos.1 + size.1 as i32) % size.1 as i32);
+                        let real_chunk_pos = (real_chunk_pos.0 as u32, real_chunk_pos.1 as u32); // after % it will never < 0 
+                        if real_chunk_pos != (x, y){
+                            chunks_around_player_set.insert(real_chunk_pos);
+                        }
+                    }
+                    chunks_with_player_set.insert((x, y));
+                }
+            } else {
+                if all_chunks.contains_key(&0){
+                    all_chunks.get_mut(&0).unwrap().push((key, vec![]));
+                } else {
+                    all_chunks.insert(0,  vec![(key, vec![])]);
+                }   
+            }
+        }
+    }
+    // other -> around -> player
+    let mut keys: Vec<u32> = all_chunks.keys().map(|x| *x).collect();
+    keys.sort();
+
+    let min_amount = keys.get(0).unwrap();
+    let chunks_objects = all_chunks.get(min_amount).unwrap(); // get chunk with minimal amount of objects
+    if *min_amount == 0 { // choose random location in chunk and return it
+        let chunk = chunks_objects.get(rng.gen_range(0..chunks_objects.len())).unwrap();
+        let chunk_pos = (chunk.0.0 * cfg.single_chunk_size.x as u32, chunk.0.1 * cfg.single_chunk_size.y as u32);
+        let offset = (cfg.single_chunk_size.x * rng.gen::<f32>(), cfg.single_chunk_size.y * rng.gen::<f32>());
+        return Vec2::from([chunk_pos.0 as f32 + offset.0, chunk_pos.1 as f32 + offset.1]);
+    } else { // > 0 objects in chunk
+        let mut without: HashMap<u32, Vec<((u32, u32), Vec<Vec2>)>> = HashMap::new();
+        let mut around: HashMap<u32, Vec<((u32, u32), Vec<Vec2>)>> = HashMap::new();
+        let mut with: HashMap<u32, Vec<((u32, u32), Vec<Vec2>)>> = HashMap::new();
+        for k in keys.iter(){
+            for tuple in all_chunks.get(k).unwrap().iter(){
+                if chunks_with_player_set.contains(&tuple.0){
+                    if with.contains_key(k){
+                        with.get_mut(k).unwrap().push(tuple.clone());
+                    } else {
+                        with.insert(*k, vec![tuple.clone()]);
+                    }
+                } else if chunks_around_player_set.contains(&tuple.0){
+                    if around.contains_key(k){
+                        around.get_mut(k).unwrap().push(tuple.clone());
+                    } else {
+                        around.insert(*k, vec![tuple.clone()]);
+                    }
+                } else {
+                    if without.contains_key(k){
+                        without.get_mut(k).unwrap().push(tuple.clone());
+                    } else {
+                        without.insert(*k, vec![tuple.clone()]);
+                    }
+                }
+            }
+        }
+
+        let find_dot = |chunk_data: &((u32, u32), Vec<Vec2>), rng: &mut ChaCha8Rng| -> Vec2{
+            let chunk_start = cfg.single_chunk_size * Vec2::from([chunk_data.0.0 as f32, chunk_data.0.1 as f32]); // left bottom corner
+            let margins = cfg.single_chunk_size * 0.05; // 5%
+            let data = chunk_data.1.clone();
+            
+            let dots = (0 .. data.len() + 1).map(|_|
+                Vec2::from((
+                    chunk_start.x + margins.x + ((cfg.single_chunk_size.x - margins.x * 2.) * rng.gen::<f32>()),
+                    chunk_start.y + margins.y + ((cfg.single_chunk_size.y - margins.y * 2.) * rng.gen::<f32>())
+                ))
+            ).collect::<Vec<Vec2>>();
+            
+            let mut max_min_dist_dot = dots.get(0).unwrap(); // find dot with maxiaml of minimal distances to objects and "walls"
+            let mut max_min_sq_dist = 0.;
+            for dot_pos in dots.iter(){
+                let mut min_sq_dist = dot_pos.distance_squared(*data.get(0).unwrap()); // data.len != 0 !!!
+
+                min_sq_dist = min_sq_dist.min((dot_pos.x - chunk_start.x).powi(2)); // distance to left
+                min_sq_dist = min_sq_dist.min((dot_pos.y - chunk_start.y).powi(2)); // distance to bottom
+                min_sq_dist = min_sq_dist.min((dot_pos.x - (chunk_start.x + cfg.single_chunk_size.x)).powi(2)); // distance to right
+                min_sq_dist = min_sq_dist.min((dot_pos.y - (chunk_start.y + cfg.single_chunk_size.y)).powi(2)); // distance to top
+
+                for object_pos in data.iter(){
+                    min_sq_dist = min_sq_dist.min(dot_pos.distance_squared(*object_pos));
+                }
+                if max_min_sq_dist < min_sq_dist {
+                    max_min_dist_dot = dot_pos;
+                    max_min_sq_dist = min_sq_dist;
+                }
+            }
+            return *max_min_dist_dot;
+        };
+
+        if !without.is_empty(){
+            let minimal = without.keys().min().unwrap();
+            let chunks_data = without.get(minimal).unwrap();
+            let chunk_data: &((u32, u32), Vec<Vec2>) = chunks_data.get(rng.gen_range(0..chunks_data.len())).unwrap();
+            let dot = find_dot(chunk_data, rng);
+            return dot;
+        } else if !around.is_empty(){
+            let minimal = around.keys().min().unwrap();
+            let chunks_data = around.get(minimal).unwrap();
+            let chunk_data: &((u32, u32), Vec<Vec2>) = chunks_data.get(rng.gen_range(0..chunks_data.len())).unwrap();
+            let dot = find_dot(chunk_data, rng);
+            return dot;
+        } else if !with.is_empty(){
+            let minimal = with.keys().min().unwrap();
+            let chunks_data = with.get(minimal).unwrap();
+            let chunk_data: &((u32, u32), Vec<Vec2>) = chunks_data.get(rng.gen_range(0..chunks_data.len())).unwrap();
+            let dot = find_dot(chunk_data, rng);
+            return dot;
+        }
+    }
+    return Vec2::ZERO;
+}
+
+
+pub fn check_bullet_collisions_and_lifetime(
+    mut bullets_data: Query<(Entity, &Transform, &mut Object, Option<&LagCompensation>), (With<Bullet>, Without<Puppet>)>,
+    rapier_context: Res<RapierContext>,
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    /*mut query_asteroid: Query<(&mut Asteroid, &Velocity, &mut Object), Without<Puppet>>,
+    mut query_ship: Query<&mut Object, With<Ship>>,*/
+    states_q: Query<&ShipState, Without<Puppet>>,
+    mut statuses_q: Query<&mut ShipStatuses, Without<Puppet>>,
+    mut query_object: Query<(&mut Object, &mut Velocity), (Without<Puppet>, Without<Bullet>)>,
+    mut cfg: ResMut<GlobalConfig>,
+    mut destroyed_writer: EventWriter<ShipDestroyed>,
+    mut rng: ResMut<GameRng>,
+    obstacles_q: Query<(), With<Obstacle>>,
+    (ship_colliders_q, all_ships_q): (Query<&Collider, (With<Ship>, Without<Puppet>)>, Query<(), With<Ship>>), // history has only real ships, puppets are skipped too
+    (history, tick, mut hit_writer): (Res<ShipHistory>, Res<ServerTick>, EventWriter<ShotHit>),
+    asset_server: Res<AssetServer>,
+    time: Res<Time>
+){
+    let mut to_despawn = HashSet::new();
+    let mut asteroids_to_split = HashMap::new();
+    let mut hit_bullets = HashMap::new(); // bullet -> owner, for accuracy, only hits on ships
+    for (bullet_entity, transform, mut object, lag_compensation) in bullets_data.iter_mut() { // todo: may crash when two bullets "touches" same asteroid at the same tick. fix!
+        match object.object_type{
+            ObjectType::Bullet { previous_position, spawn_time, owner, extra_damage, weapon} => {
+                let weapon_stats = cfg.get_weapon_stats(weapon).clone();
+                // HANDLE COLLISIONS
+                let previous_pos = previous_position.translation;
+                let previous_pos = Vec2::new(previous_pos.x, previous_pos.y);
+                let point = Vec2::new(transform.translation.x, transform.translation.y);
+                let dir = point - previous_pos;
+                let len = dir.length();
+                let filter = QueryFilter::default();
+                let mut stopped = false;
+                
+                // check collisions
+                rapier_context.intersections_with_ray(
+                    point, dir, len, true, filter,
+                    |entity, intersection| {
+                        let hit_point = intersection.point;
+                        
+                        let ray_len = (hit_point - point).length(); // check 
+                        if ray_len > len {
+                            return true
+                        }
+
+                        if lag_compensation.is_some() && all_ships_q.contains(entity){ // checked below against the past
+                            return true
+                        }
+
+                        //let hit_normal = intersection.normal; // USE FOR PARTILCES
+
+                        if obstacles_q.contains(entity){ // bullets stop at walls
+                            to_despawn.insert(bullet_entity);
+                            stopped = true;
+                            return false
+                        }
+
+                        // Check if entity is asteroid
+                        if let Ok(tuple) = query_object.get_mut(entity){
+                            let (mut object, velocity) = tuple;
+                            match object.object_type{
+                                ObjectType::Asteroid { seed, mut hp } => {
+                                    if hp != 0{ // inserting everything
+                                        hp = hp - 1;
+                                    }
+                                    object.object_type = ObjectType::Asteroid { seed: seed, hp: hp };
+                                    commands.entity(entity).insert(object.clone());
+                                    
+                                    if hp <= 0{
+                                        asteroids_to_split.insert(entity, (seed, hit_point, dir, velocity.clone(), transform));
+                                    };
+                                    commands.entity(bullet_entity).despawn_recursive();
+                                    stopped = true;
+                                    return false
+                                }
+                                ObjectType::Ship { .. } => {
+                                    if !(object.id == owner && time.elapsed().as_secs_f32() - spawn_time < 0.3){ // check ownership, after some time bullet will damage owner
+                                        match states_q.get(entity).unwrap() {
+                                            ShipState::Dead { time: _ } => {
+                                                to_despawn.insert(bullet_entity);
+                                                return true
+                                            },
+                                            _ => {}
+                                        }
+                                        
+                                        let mut statuses = statuses_q.get_mut(entity).unwrap();
+                                        let damage = weapon_stats.damage * (1. + extra_damage);
+                                        if damage_ship(entity, &object, &mut statuses, damage, &time, &mut commands){
+                                            destroyed_writer.send(ShipDestroyed{victim: object.id, killer: if owner != object.id {Some(owner)} else {None}});
+                                        }
+                                        if owner != object.id {hit_bullets.insert(bullet_entity, owner);}
+                                        to_despawn.insert(bullet_entity);
+                                        stopped = true;
+                                        return false
+                                    }
+                                }
+                                _ => {}
+                            }
+                        }
+                        return true // Return `false` instead if we want to stop searching for other hits.
+                });
+                // LAG COMPENSATION
+                // ships are checked where they were on the tick shooter saw
+                if let (Some(lag_compensation), false) = (lag_compensation, stopped) {
+                    let mut hits = vec![];
+                    if let Some(ships) = history.at(tick.0.saturating_sub(lag_compensation.rewind_ticks)){
+                        for (entity, position, rotation) in ships.iter(){
+                            let Ok(collider) = ship_colliders_q.get(*entity) else {continue}; // already despawned
+                            let position = point + cfg.world_wrapped_vec(point, *position);
+                            if let Some(toi) = collider.cast_ray(position, *rotation, point, dir.normalize_or_zero(), len, true){
+                                hits.push((toi, *entity));
+                            }
+                        }
+                    }
+                    hits.sort_by(|a, b| a.0.total_cmp(&b.0));
+                    for (_, entity) in hits{
+                        let Ok((object, _)) = query_object.get(entity) else {continue};
+                        if object.id == owner && time.elapsed().as_secs_f32() - spawn_time < 0.3 {continue}
+                        if let Ok(ShipState::Dead { .. }) = states_q.get(entity){
+                            to_despawn.insert(bullet_entity);
+                            continue
+                        }
+                        let Ok(mut statuses) = statuses_q.get_mut(entity) else {continue};
+                        let damage = weapon_stats.damage * (1. + extra_damage);
+                        if damage_ship(entity, object, &mut statuses, damage, &time, &mut commands){
+                            destroyed_writer.send(ShipDestroyed{victim: object.id, killer: if owner != object.id {Some(owner)} else {None}});
+                        }
+                        if owner != object.id {hit_bullets.insert(bullet_entity, owner);}
+                        to_despawn.insert(bullet_entity);
+                        break
+                    }
+                }
+                // UPDATE
+                //println!("pos {} -> {}", previous_position.translation.truncate(), transform.translation.truncate());
+                object.object_type = ObjectType::Bullet {
+                    previous_position: *transform,
+                    spawn_time,
+                    owner,
+                    extra_damage,
+                    weapon
+                };
+                //LIFETIME
+                if time.elapsed().as_secs_f32() - spawn_time > weapon_stats.lifetime_secs{
+                    to_despawn.insert(bullet_entity);
+                }
+            }
+            _ => {}
+        }
+    }
+    
+    for (e, data) in asteroids_to_split.iter(){
+        let (seed, hit_point, dir, velocity, transform) = data;
+        split_asteroid(*e, *seed, *hit_point, *dir, velocity, transform.translation, &mut meshes, &mut materials, &asset_server, &mut cfg, &mut rng.0, &mut commands);
+    }
+
+    for owner in hit_bullets.values(){
+        hit_writer.send(ShotHit{owner: *owner});
+    }
+    
+    for e in to_despawn.iter(){
+        commands.entity(*e).despawn();
+    }
+}
+
+const SPLIT_SEED_ATTEMPTS: u32 = 256;
+
+/// Despawns destroyed asteroid, spawns two smaller ones (if it is not the smallest) and maybe drops powerup.
+pub fn split_asteroid(
+    entity: Entity,
+    seed: u64,
+    hit_point: Vec2,
+    dir: Vec2,
+    velocity: &Velocity,
+    position: Vec3,
+    meshes: &mut ResMut<Assets<Mesh>>,
+    materials: &mut ResMut<Assets<ColorMaterial>>,
+    asset_server: &Res<AssetServer>,
+    cfg: &mut ResMut<GlobalConfig>,
+    rng: &mut ChaCha8Rng,
+    commands: &mut Commands,
+){
+    commands.entity(entity).despawn_recursive();
+    /*
+        |
+        v
+    o <- O -> o
+    SPLIT ASTEROID
+    todo: fix velocity
+    todo: fix client lag when asteroid splits
+    */
+
+    let params = cfg.get_asteroid_params(seed);
+    let current_size = params.size;
+    if current_size != 1{
+        let dir = (hit_point - dir).normalize().perp();
+        let dir = Vec3{x: dir.x, y: dir.y, z:0.0};
+        let dir1 = dir;
+        let dir2 = -dir;
+        let vel1 = Velocity{linvel: Vec2{x: velocity.linvel.x + dir1.x * (1. - rng.gen::<f32>()) * 50., y: velocity.linvel.y + dir1.x * (1. - rng.gen::<f32>()) * 50.}, angvel: velocity.angvel + (1. - rng.gen::<f32>() * 4.)};
+        let vel2 = Velocity{linvel: Vec2{x: velocity.linvel.x + dir2.x * (1. - rng.gen::<f32>()) * 50., y: velocity.linvel.y + dir2.x * (1. - rng.gen::<f32>()) * 50.}, angvel: velocity.angvel + (1. - rng.gen::<f32>() * 4.)};
+
+        // pieces are one size smaller and of the same material
+        // with zero weight for that size or material no seed matches, so attempts are limited
+        let mut find_seed = || {
+            let mut same_size = None;
+            for _ in 0..SPLIT_SEED_ATTEMPTS{
+                let new_seed = rng.gen::<u64>();
+                let new_params = cfg.get_asteroid_params(new_seed);
+                if new_params.size != current_size - 1 {continue;}
+                if new_params.material == params.material {return Some(new_seed);}
+                same_size = same_size.or(Some(new_seed));
+            }
+            same_size // other material, or None and asteroid breaks without pieces
+        };
+        if let (Some(new_seed_1), Some(new_seed_2)) = (find_seed(), find_seed()){
+            let object_id = cfg.new_id();
+            spawn_asteroid(
+                new_seed_1,
+                vel1,
+                Transform::from_translation(position + dir1 * current_size as f32 * 5.),
+                meshes,
+                materials,
+                commands,
+                object_id,
+                cfg.get_asteroid_hp(new_seed_1),
+                cfg,
+            );
+            let object_id = cfg.new_id();
+            spawn_asteroid(
+                new_seed_2,
+                vel2,
+                Transform::from_translation(position + dir2 * current_size as f32 * 5.),
+                meshes,
+                materials,
+                commands,
+                object_id,
+                cfg.get_asteroid_hp(new_seed_2),
+                cfg,
+            );
+        }
+    }
+    // spawn powerups, metal asteroids roll more
+    for _ in 0..cfg.asteroid_generator.get_material_stats(params.material).drop_rolls{
+        if rng.gen::<f32>() < cfg.powerup_drop_chances{
+            let powerup_type = cfg.roll_power_up_drop(current_size, rng);
+            if powerup_type.is_some(){
+                let object_id = cfg.new_id();
+                spawn_powerup(powerup_type.unwrap(), cfg.powerup_lifetime_secs, position, commands, meshes, materials, asset_server, cfg, object_id);
+            }
+        }
+    }
+}
+
+/// Damages ship through super shield -> shields -> hp and kills it if hp is over. Returns true if ship died.
+/// Ships with spawn immunity are not damaged at all.
+pub fn damage_ship(
+    entity: Entity,
+    object: &Object,
+    statuses: &mut ShipStatuses,
+    damage: f32,
+    time: &Time,
+    commands: &mut Commands,
+) -> bool {
+    if statuses.has_spawn_immunity(){
+        return false;
+    }
+    match object.object_type {
+        ObjectType::Ship { style, color, mut shields, mut hp } => {
+            if statuses.has_super_shield(){
+                let overshields = statuses.get_mut(PowerUPEffectKind::SuperShield).unwrap();
+                overshields.value -= damage;
+                overshields.value = if overshields.value > 0. {overshields.value} else {0.};
+            } else if shields > 0.{
+                shields -= damage;
+                shields = if shields > 0. {shields} else {0.};
+                commands.entity(entity).insert(LastDamageTaken{time: time.elapsed_seconds()});
+            } else {
+                hp -= damage;
+                hp = if hp > 0. {hp} else {0.};
+                commands.entity(entity).insert(LastDamageTaken{time: time.elapsed_seconds()});
+            }
+
+            let mut object_copy = object.clone();
+            object_copy.object_type = ObjectType::Ship { style , color,  shields, hp };
+            if hp <= 0. {
+                commands.entity(entity).insert((
+                    ShipState::Dead { time: 0. },
+                    ShipStatuses{current: HashMap::new()},
+                    //Visibility::Hidden,
+                    ColliderDisabled,
+                    Velocity::zero(),
+                    object_copy
+                ));
+                return true;
+            } else {
+                commands.entity(entity).insert(object_copy);
+                //println!("-> {} hp {} sh {}", object.id, hp, shields);
+            }
+        }
+        _ => {}
+    }
+    return false;
+}
+
+pub fn check_ship_force_events(
+    mut commands: Commands,
+    mut ships_q: Query<(&Object, &ShipState, &mut ShipStatuses), (With<Ship>, Without<Puppet>)>,
+    objects_q: Query<&Object, Without<Bullet>>, // includes puppets: ship can hit shadow copy of asteroid or ship
+    cfg: Res<GlobalConfig>,
+    mut contact_force_events: EventReader<ContactForceEvent>,
+    mut destroyed_writer: EventWriter<ShipDestroyed>,
+    time: Res<Time>
+){
+    // collect damage first, because ship can hit several things at the same tick
+    let mut damage_to_ships: HashMap<Entity, (f32, Option<u64>)> = HashMap::new(); // ship -> (damage, id of rammer ship)
+    for contact_force_event in contact_force_events.read() {
+        let force = contact_force_event.max_force_magnitude;
+        if force < cfg.collision_damage_threshold {continue;}
+        let damage = (force - cfg.collision_damage_threshold) * cfg.collision_damage_multiplier;
+
+        let pairs = [
+            (contact_force_event.collider1, contact_force_event.collider2),
+            (contact_force_event.collider2, contact_force_event.collider1)
+        ];
+        for (ship_e, other_e) in pairs{
+            if !ships_q.contains(ship_e) {continue;}
+            let rammer = match objects_q.get(other_e) {
+                Ok(Object { id, object_type: ObjectType::Ship { .. } }) => Some(*id),
+                _ => None
+            };
+            let entry = damage_to_ships.entry(ship_e).or_insert((0., None));
+            entry.0 += damage;
+            if rammer.is_some(){
+                entry.1 = rammer;
+            }
+        }
+    }
+
+    for (ship_e, (damage, rammer)) in damage_to_ships.iter(){
+        let (object, state, mut statuses) = ships_q.get_mut(*ship_e).unwrap();
+        match state {
+            ShipState::Dead { time: _ } => {continue;},
+            _ => {}
+        }
+        if damage_ship(*ship_e, object, &mut statuses, *damage, &time, &mut commands){
+            destroyed_writer.send(ShipDestroyed{victim: object.id, killer: *rammer});
+        }
+    }
+}
+
+
+
+/// Collected pickup runs out of lifetime, update_powerups_lifetime is the only place that despawns pickups
+fn expire_pickup(object: &mut Object){
+    if let ObjectType::PickUP { pickup_type, .. } = object.object_type {
+        object.object_type = ObjectType::PickUP { pickup_type, lifetime: 0. };
+    }
+}
+
+pub fn check_pickups_collisions(
+    mut collision_events: EventReader<CollisionEvent>,
+    mut ship_q: Query<(&mut ShipStatuses, &mut Object, &mut ShipWeapon, &Transform, Entity), (With<Ship>, Without<PowerUP>, Without<Puppet>)>,
+    mut powerup_q: Query<(Entity, &mut Object), (With<PowerUP>, Without<Ship>, Without<Puppet>)>,
+    mut commands: Commands,
+    cfg: Res<GlobalConfig>,
+    time: Res<Time>,
+){
+    let mut pulses = vec![]; // (source object id, position, effect, radius)
+    for collision_event in collision_events.read() {
+        match *collision_event { // todo: may crash when two ships "touches" same pup at the same tick. fix!
+            CollisionEvent::Started(e0, e1, flags) => {
+                match flags {
+                    CollisionEventFlags::SENSOR => {}
+                    _ => {continue;}
+                }
+                let e0_ship = ship_q.get(e0);
+                let e1_ship = ship_q.get(e1);
+                let e0_powerup = powerup_q.get(e0);
+                let e1_powerup = powerup_q.get(e1);
+
+                let (ship, powerup) = 
+                    if e0_ship.is_ok() && e1_powerup.is_ok() {(ship_q.get_mut(e0).unwrap(), e1_powerup.unwrap())}
+                else
+                    if e0_powerup.is_ok() && e1_ship.is_ok() {(ship_q.get_mut(e1).unwrap(), e0_powerup.unwrap())}
+                else
+                    {continue;};
+                let (powerup_e, powerup_object) = powerup;
+                match powerup_object.object_type {
+                    ObjectType::PickUP { pickup_type, lifetime } => {
+                        if lifetime <= 0. {continue;} // other ship took it this tick
+                        let (mut ship_effects, mut object, mut weapon, transform, _) = ship;
+                        let def = cfg.get_power_up_def(pickup_type);
+                        match def.effect {
+                            PowerUPEffectKind::Weapon(weapon_type) => {
+                                *weapon = ShipWeapon::new(weapon_type, &cfg);
+                                if let Ok((_, mut pickup)) = powerup_q.get_mut(powerup_e) {expire_pickup(&mut pickup);}
+                                continue;
+                            }
+                            _ => {}
+                        }
+                        ship_effects.apply(pickup_type, &cfg);
+                        match def.effect {
+                            PowerUPEffectKind::Repair => {
+                                let mut object_clone = object.clone();
+                                match object.object_type {
+                                    ObjectType::Ship { style, color, shields, hp } => {
+                                        object_clone.object_type = ObjectType::Ship { style, color, shields, hp: (hp + def.magnitude).clamp(0., cfg.player_hp)};
+                                        *object = object_clone;
+                                    }
+                                    _ => {}
+                                }
+                            }
+                            PowerUPEffectKind::Emp | PowerUPEffectKind::RevealPulse => {
+                                pulses.push((object.id, transform.translation.truncate(), def.effect, def.radius));
+                            }
+                            _ => {}
+                        }
+                    }
+                    _ => {}
+                }
+                if let Ok((_, mut pickup)) = powerup_q.get_mut(powerup_e) {expire_pickup(&mut pickup);}
+            },
+            _ => {},
+        }
+    }
+
+    // PULSES
+    let revealed = cfg.find_power_up(PowerUPEffectKind::Revealed);
+    for (source_id, position, effect, radius) in pulses {
+        for (mut statuses, mut object, _, transform, entity) in ship_q.iter_mut(){
+            if object.id == source_id || cfg.world_wrapped_vec(position, transform.translation.truncate()).length() > radius {continue;}
+            match effect {
+                PowerUPEffectKind::Emp => {
+                    statuses.current.retain(|_, effect| effect.kind == PowerUPEffectKind::SpawnImmunity);
+                    let mut object_clone = object.clone();
+                    match object.object_type {
+                        ObjectType::Ship { style, color, shields: _, hp } => {
+                            object_clone.object_type = ObjectType::Ship { style, color, shields: 0., hp };
+                            *object = object_clone;
+                            commands.entity(entity).insert(LastDamageTaken{time: time.elapsed_seconds()}); // delay recharge
+                        }
+                        _ => {}
+                    }
+                }
+                PowerUPEffectKind::RevealPulse => {
+                    if revealed.is_some(){
+                        statuses.apply(revealed.unwrap(), &cfg);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+
+pub fn update_homing_missiles(
+    mut bullets_q: Query<(&Object, &mut Velocity, &mut Transform), (With<Bullet>, Without<Puppet>)>,
+    ships_q: Query<(&Object, &Transform, &ShipState, &ShipStatuses), (With<Ship>, Without<Bullet>, Without<Puppet>)>,
+    cfg: Res<GlobalConfig>,
+    time: Res<Time>,
+){
+    for (object, mut velocity, mut transform) in bullets_q.iter_mut(){
+        let (owner, weapon) = match object.object_type {
+            ObjectType::Bullet { owner, weapon, .. } => {(owner, weapon)}
+            _ => {continue;}
+        };
+        let weapon_stats = cfg.get_weapon_stats(weapon);
+        if weapon_stats.homing_turn_rate <= 0. {continue;}
+
+        // FIND NEAREST TARGET
+        let pos = transform.translation.truncate();
+        let mut target_vec: Option<Vec2> = None;
+        for (ship_object, ship_transform, state, statuses) in ships_q.iter(){
+            if ship_object.id == owner || statuses.is_hidden(){continue;}
+            match state {
+                ShipState::Dead { .. } => {continue;}
+                _ => {}
+            }
+            let vec = cfg.world_wrapped_vec(pos, ship_transform.translation.truncate());
+            if vec.length() > weapon_stats.homing_range {continue;}
+            if target_vec.is_none() || vec.length_squared() < target_vec.unwrap().length_squared(){
+                target_vec = Some(vec);
+            }
+        }
+        if target_vec.is_none(){continue;}
+
+        // TURN
+        let speed = velocity.linvel.length();
+        let angle = velocity.linvel.angle_between(target_vec.unwrap());
+        if angle.is_nan(){continue;}
+        let max_turn = weapon_stats.homing_turn_rate * time.delta_seconds();
+        velocity.linvel = Vec2::from_angle(angle.clamp(-max_turn, max_turn)).rotate(velocity.linvel).normalize_or_zero() * speed;
+        transform.rotation = Quat::from_rotation_z(Vec2::X.angle_between(velocity.linvel) + PI / 2.);
+    }
+}
+
+
+/// How long clients see the ray of a hitscan shot
+pub const TRACER_SECS: f32 = 0.15;
+
+/// Hitscan shots are resolved in the tick they are fired: everything on the ray until the first wall is hit, nearest first.
+/// Shooter gets a tracer from muzzle to the wall or max range, clients draw it instead of a projectile
+pub fn resolve_hitscan_shots(
+    mut shots: EventReader<HitscanFired>,
+    rapier_context: Res<RapierContext>,
+    mut objects_q: Query<(Entity, &mut Object, &Velocity, &Transform), Without<Puppet>>,
+    mut weapons_q: Query<&mut ShipWeapon, Without<Puppet>>,
+    mut statuses_q: Query<&mut ShipStatuses, Without<Puppet>>,
+    states_q: Query<&ShipState, Without<Puppet>>,
+    puppets_q: Query<&Puppet>,
+    walls_q: Query<(), Or<(With<Obstacle>, With<MapBound>)>>,
+    (history, tick, ship_colliders_q, all_ships_q): (Res<ShipHistory>, Res<ServerTick>, Query<&Collider, (With<Ship>, Without<Puppet>)>, Query<(), With<Ship>>),
+    (mut meshes, mut materials): (ResMut<Assets<Mesh>>, ResMut<Assets<ColorMaterial>>),
+    (mut destroyed_writer, mut hit_writer): (EventWriter<ShipDestroyed>, EventWriter<ShotHit>),
+    mut cfg: ResMut<GlobalConfig>,
+    mut rng: ResMut<GameRng>,
+    asset_server: Res<AssetServer>,
+    mut commands: Commands,
+    time: Res<Time>,
+){
+    let now = time.elapsed_seconds();
+    for mut weapon in weapons_q.iter_mut(){
+        if weapon.tracer.is_some() && now >= weapon.tracer_until {
+            weapon.tracer = None;
+        }
+    }
+    let shots: Vec<HitscanFired> = shots.read().copied().collect();
+    if shots.is_empty() {return;}
+    let real_entities: HashMap<u64, Entity> = objects_q.iter().map(|(e, object, _, _)| (object.id, e)).collect();
+    let mut killed_ships = HashSet::new();
+    let mut split_asteroids = HashSet::new();
+    for shot in shots{
+        let shooter = real_entities.get(&shot.owner).copied();
+        let mut hits = vec![]; // (toi, entity)
+        let filter = QueryFilter::default().exclude_sensors();
+        rapier_context.intersections_with_ray(shot.origin, shot.dir, shot.range, true, filter, |entity, intersection| {
+            if shot.rewind_ticks == 0 || !all_ships_q.contains(entity) { // lag compensated ships, puppets too, are taken from history
+                hits.push((intersection.toi, entity));
+            }
+            true
+        });
+        // LAG COMPENSATION
+        if shot.rewind_ticks > 0 {
+            if let Some(ships) = history.at(tick.0.saturating_sub(shot.rewind_ticks)){
+                for (entity, position, rotation) in ships.iter(){
+                    if Some(*entity) == shooter {continue;}
+                    let Ok(collider) = ship_colliders_q.get(*entity) else {continue}; // already despawned
+                    let position = shot.origin + cfg.world_wrapped_vec(shot.origin, *position);
+                    if let Some(toi) = collider.cast_ray(position, *rotation, shot.origin, shot.dir, shot.range, true){
+                        hits.push((toi, *entity));
+                    }
+                }
+            }
+        }
+        hits.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        let mut end = shot.origin + shot.dir * shot.range;
+        let mut hit_objects = HashSet::new(); // object and its puppet are hit once
+        for (toi, entity) in hits{
+            if walls_q.contains(entity) {
+                end = shot.origin + shot.dir * toi;
+                break;
+            }
+            let object_id = match puppets_q.get(entity) {
+                Ok(puppet) => puppet.id,
+                Err(_) => match objects_q.get(entity) {
+                    Ok((_, object, _, _)) => object.id,
+                    Err(_) => continue,
+                },
+            };
+            if object_id == shot.owner || !hit_objects.insert(object_id) {continue;}
+            let Some(real_entity) = real_entities.get(&object_id).copied() else {continue;}; // puppets are damaged through their real objects
+            let Ok((_, mut object, velocity, transform)) = objects_q.get_mut(real_entity) else {continue;};
+            match object.object_type {
+                ObjectType::Asteroid { seed, hp } => {
+                    if split_asteroids.contains(&object_id) {continue;}
+                    let hp = hp.saturating_sub(1);
+                    object.object_type = ObjectType::Asteroid { seed, hp };
+                    if hp == 0 {
+                        split_asteroids.insert(object_id);
+                        let (velocity, position) = (*velocity, transform.translation);
+                        split_asteroid(real_entity, seed, shot.origin + shot.dir * toi, shot.dir, &velocity, position, &mut meshes, &mut materials, &asset_server, &mut cfg, &mut rng.0, &mut commands);
+                    }
+                }
+                ObjectType::Ship { .. } => {
+                    if killed_ships.contains(&object_id) || matches!(states_q.get(real_entity), Ok(ShipState::Dead { .. })) {continue;}
+                    let Ok(mut statuses) = statuses_q.get_mut(real_entity) else {continue;};
+                    hit_writer.send(ShotHit{owner: shot.owner});
+                    if damage_ship(real_entity, &object, &mut statuses, shot.damage, &time, &mut commands){
+                        killed_ships.insert(object_id);
+                        destroyed_writer.send(ShipDestroyed{victim: object_id, killer: Some(shot.owner)});
+                    }
+                }
+                _ => {}
+            }
+        }
+        if let Some(mut weapon) = shooter.and_then(|shooter| weapons_q.get_mut(shooter).ok()){
+            weapon.tracer = Some((shot.origin, end, shot.weapon));
+            weapon.tracer_until = now + TRACER_SECS;
+        }
+    }
+}
+
+/// Raycasts beams of firing ships every tick, damages first hit asteroid or ship and updates heat.
+/// Map walls and bounds stop beams without taking damage.
+pub fn update_beam_weapons(
+    rapier_context: Res<RapierContext>,
+    mut ships_q: Query<(Entity, &Object, &Transform, &ShipState, &mut ShipWeapon), (With<Ship>, Without<Puppet>)>,
+    targets_q: Query<(Entity, &Object, &Velocity, &Transform, Option<&Puppet>, Option<&ShipState>)>,
+    walls_q: Query<(), Or<(With<Obstacle>, With<MapBound>)>>,
+    real_objects_q: Query<(Entity, &Object), Without<Puppet>>,
+    mut statuses_q: Query<&mut ShipStatuses, Without<Puppet>>,
+    mut cfg: ResMut<GlobalConfig>,
+    (mut meshes, mut materials): (ResMut<Assets<Mesh>>, ResMut<Assets<ColorMaterial>>),
+    (history, tick, ship_colliders_q, all_ships_q): (Res<ShipHistory>, Res<ServerTick>, Query<&Collider, (With<Ship>, Without<Puppet>)>, Query<(), With<Ship>>),
+    mut destroyed_writer: EventWriter<ShipDestroyed>,
+    (mut shot_writer, mut hit_writer): (EventWriter<ShotFired>, EventWriter<ShotHit>), // every firing tick is a shot, for accuracy
+    mut asteroids_damage: Local<HashMap<u64, f32>>, // asteroid hp is in hits, so damage is accumulated
+    mut rng: ResMut<GameRng>,
+    asset_server: Res<AssetServer>,
+    mut commands: Commands,
+    time: Res<Time>,
+){
+    let mut hits = vec![]; // (shooter id, hit entity, damage, hit point, dir)
+    for (entity, object, transform, state, mut weapon) in ships_q.iter_mut(){
+        let stats = cfg.get_weapon_stats(weapon.weapon_type).clone();
+        let is_dead = match state {ShipState::Dead { .. } => {true} _ => {false}};
+        if stats.beam_range.is_none() || is_dead {
+            weapon.firing = false;
+            weapon.beam = None;
+            continue;
+        }
+        // HEAT
+        if weapon.firing && !weapon.overheated {
+            weapon.heat += stats.heat_per_sec * time.delta_seconds();
+            if weapon.heat >= 1. {
+                weapon.heat = 1.;
+                weapon.overheated = true;
+            }
+        } else {
+            weapon.heat = (weapon.heat - stats.cooling_per_sec * time.delta_seconds()).max(0.);
+            if weapon.heat == 0. {
+                weapon.overheated = false;
+            }
+        }
+        if !weapon.firing || weapon.overheated {
+            weapon.beam = None;
+            continue;
+        }
+        // RAYCAST
+        let origin = transform.translation.truncate();
+        let dir = transform.up().truncate();
+        let range = stats.beam_range.unwrap();
+        let lag_compensated = weapon.rewind_ticks > 0;
+        let predicate = |e: Entity| {
+            if walls_q.contains(e) {return true;}
+            if lag_compensated && all_ships_q.contains(e) {return false;} // checked below against the past, puppets too
+            match targets_q.get(e) {
+                Ok((_, target, _, _, _, _)) => {
+                    match target.object_type {
+                        ObjectType::Asteroid { .. } => {true}
+                        ObjectType::Ship { .. } => {target.id != object.id} // ignore self and own puppets
+                        _ => {false}
+                    }
+                }
+                Err(_) => {false}
+            }
+        };
+        let filter = QueryFilter::default().exclude_sensors().predicate(&predicate);
+        shot_writer.send(ShotFired{owner: object.id});
+        let mut hit = rapier_context.cast_ray(origin, dir, range, true, filter);
+        // LAG COMPENSATION
+        // ships are checked where they were on the tick shooter saw, beam stops at the closest hit
+        if lag_compensated {
+            if let Some(ships) = history.at(tick.0.saturating_sub(weapon.rewind_ticks)){
+                for (ship_entity, position, rotation) in ships.iter(){
+                    if *ship_entity == entity {continue;}
+                    let Ok(collider) = ship_colliders_q.get(*ship_entity) else {continue}; // already despawned
+                    let position = origin + cfg.world_wrapped_vec(origin, *position);
+                    let max_toi = hit.map(|(_, toi)| toi).unwrap_or(range);
+                    if let Some(toi) = collider.cast_ray(position, *rotation, origin, dir, max_toi, true){
+                        if toi < max_toi {hit = Some((*ship_entity, toi));}
+                    }
+                }
+            }
+        }
+        let end = match hit {
+            Some((hit_entity, toi)) if walls_q.contains(hit_entity) => {origin + dir * toi}
+            Some((hit_entity, toi)) => {
+                let extra_damage = statuses_q.get(entity).ok().and_then(|statuses| statuses.get_value(PowerUPEffectKind::ExtraDamage)).unwrap_or(0.);
+                let damage = stats.damage * (1. + extra_damage) * time.delta_seconds();
+                hits.push((object.id, hit_entity, damage, origin + dir * toi, dir));
+                origin + dir * toi
+            }
+            None => {origin + dir * range}
+        };
+        weapon.beam = Some((origin, end));
+    }
+
+    // APPLY DAMAGE
+    let real_entities: HashMap<u64, Entity> = real_objects_q.iter().map(|(e, object)| (object.id, e)).collect();
+    let mut damaged_asteroids = HashSet::new();
+    let mut split_asteroids = HashSet::new();
+    let mut killed_ships = HashSet::new(); // several beams on one ship kill it once
+    for (shooter_id, hit_entity, damage, hit_point, dir) in hits {
+        let (_, hit_object, _, _, _, _) = targets_q.get(hit_entity).unwrap();
+        let real_entity = real_entities.get(&hit_object.id); // puppets are damaged through their real objects
+        if real_entity.is_none() {continue;}
+        let real_entity = *real_entity.unwrap();
+        let (_, target, velocity, transform, _, state) = targets_q.get(real_entity).unwrap();
+        match target.object_type {
+            ObjectType::Asteroid { seed, hp } => {
+                if split_asteroids.contains(&target.id) {continue;}
+                damaged_asteroids.insert(target.id);
+                let accumulated = asteroids_damage.entry(target.id).or_insert(0.);
+                *accumulated += damage;
+                let hit_damage = cfg.get_weapon_stats(WeaponType::Blaster).damage; // damage of one regular hit
+                if *accumulated < hit_damage {continue;}
+                *accumulated -= hit_damage;
+                let hp = hp.saturating_sub(1);
+                commands.entity(real_entity).insert(Object{id: target.id, object_type: ObjectType::Asteroid { seed, hp }});
+                if hp == 0 {
+                    split_asteroids.insert(target.id);
+                    split_asteroid(real_entity, seed, hit_point, dir, velocity, transform.translation, &mut meshes, &mut materials, &asset_server, &mut cfg, &mut rng.0, &mut commands);
+                }
+            }
+            ObjectType::Ship { .. } => {
+                if killed_ships.contains(&target.id) || matches!(state, Some(ShipState::Dead { .. })) {continue;}
+                let statuses = statuses_q.get_mut(real_entity);
+                if statuses.is_err() {continue;}
+                let mut statuses = statuses.unwrap();
+                hit_writer.send(ShotHit{owner: shooter_id});
+                if damage_ship(real_entity, target, &mut statuses, damage, &time, &mut commands){
+                    killed_ships.insert(target.id);
+                    destroyed_writer.send(ShipDestroyed{victim: target.id, killer: Some(shooter_id)});
+                }
+            }
+            _ => {}
+        }
+    }
+    asteroids_damage.retain(|id, _| damaged_asteroids.contains(id) && !split_asteroids.contains(id)); // progress is lost when beam moves away
+}
+
+
+/// Area effects of statuses: magnet pulls pickups, time slow brakes enemy ships and bullets.
+pub fn apply_area_powerups(
+    ships_q: Query<(&Object, &Transform, &ShipStatuses), (With<Ship>, Without<Puppet>)>,
+    mut pickups_q: Query<&mut Transform, (With<PowerUP>, Without<Ship>, Without<Puppet>)>,
+    mut movables_q: Query<(&Object, &Transform, &mut Velocity), (Or<(With<Ship>, With<Bullet>)>, Without<PowerUP>, Without<Puppet>)>,
+    cfg: Res<GlobalConfig>,
+    time: Res<Time>,
+){
+    for (object, transform, statuses) in ships_q.iter(){
+        let position = transform.translation.truncate();
+        for (powerup_type, effect) in statuses.current.iter(){
+            let radius = cfg.get_power_up_def(*powerup_type).radius;
+            match effect.kind {
+                PowerUPEffectKind::Magnet => {
+                    for mut pickup_transform in pickups_q.iter_mut(){
+                        let vec = cfg.world_wrapped_vec(pickup_transform.translation.truncate(), position);
+                        if vec.length() > radius {continue;}
+                        let step = (effect.value * time.delta_seconds()).min(vec.length());
+                        pickup_transform.translation += (vec.normalize_or_zero() * step).extend(0.);
+                    }
+                }
+                PowerUPEffectKind::TimeSlow => {
+                    let keep = (1. - effect.value.clamp(0., 1.)).powf(time.delta_seconds()); // frame rate independent
+                    for (other, other_transform, mut velocity) in movables_q.iter_mut(){
+                        let owner = match other.object_type {
+                            ObjectType::Bullet { owner, .. } => {owner}
+                            _ => {other.id}
+                        };
+                        if owner == object.id {continue;}
+                        if cfg.world_wrapped_vec(position, other_transform.translation.truncate()).length() > radius {continue;}
+                        velocity.linvel *= keep;
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+
+/// Arena pickups of current match, server resets it with the world
+#[derive(Resource, Default)]
+pub struct SpawnPointsState{
+    pickups: HashMap<usize, u64>, // spawn point index -> object id of its pickup
+    respawn_at: HashMap<usize, f32>,
+}
+
+/// Counts down pickups lifetime, removes oldest dropped pickups above the cap and respawns arena pickups.
+pub fn update_powerups_lifetime(
+    mut pickups_q: Query<(Entity, &mut Object), (With<PowerUP>, Without<Puppet>)>,
+    mut cfg: ResMut<GlobalConfig>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut spawn_points: ResMut<SpawnPointsState>,
+    asset_server: Res<AssetServer>,
+    mut commands: Commands,
+    time: Res<Time>,
+){
+    let mut dropped = vec![]; // (object id, entity)
+    let mut existing = HashSet::new();
+    for (e, mut object) in pickups_q.iter_mut(){
+        match object.object_type {
+            ObjectType::PickUP { pickup_type, lifetime } => {
+                existing.insert(object.id);
+                let lifetime = lifetime - time.delta_seconds();
+                if lifetime <= 0. {
+                    commands.entity(e).despawn_recursive();
+                    continue;
+                }
+                object.object_type = ObjectType::PickUP { pickup_type, lifetime };
+                if !spawn_points.pickups.values().any(|id| *id == object.id){
+                    dropped.push((object.id, e));
+                }
+            }
+            _ => {}
+        }
+    }
+    // CAP
+    if dropped.len() > cfg.max_powerups {
+        dropped.sort_by_key(|(id, _)| *id); // ids are increasing, so the first are the oldest
+        for (_, e) in dropped.iter().take(dropped.len() - cfg.max_powerups){
+            commands.entity(*e).despawn_recursive();
+        }
+    }
+    // SPAWN POINTS
+    for (i, point) in cfg.powerup_spawn_points.clone().iter().enumerate(){
+        let current = spawn_points.pickups.get(&i);
+        if current.is_some() && existing.contains(current.unwrap()) {continue;}
+        if current.is_some(){ // collected, start timer
+            spawn_points.pickups.remove(&i);
+            spawn_points.respawn_at.insert(i, time.elapsed_seconds() + point.respawn_secs);
+        }
+        if *spawn_points.respawn_at.get(&i).unwrap_or(&0.) > time.elapsed_seconds() {continue;}
+        let powerup_type = cfg.find_power_up_by_id(&point.powerup);
+        if powerup_type.is_none(){
+            warn!("Unknown powerup \"{}\" at spawn point {}", point.powerup, i);
+            continue;
+        }
+        let object_id = cfg.new_id();
+        spawn_powerup(powerup_type.unwrap(), f32::INFINITY, point.position.extend(0.), &mut commands, &mut meshes, &mut materials, &asset_server, &cfg, object_id);
+        spawn_points.pickups.insert(i, object_id);
+        spawn_points.respawn_at.remove(&i);
+    }
+}
+
+/// Client side, pickups blink before despawn. Puppets take lifetime from real pickups
+pub fn blink_powerups(
+    real_q: Query<&Object, (With<PowerUP>, Without<Puppet>)>,
+    mut all_q: Query<(&Object, &mut Visibility), With<PowerUP>>,
+    cfg: Res<GlobalConfig>,
+    time: Res<Time>,
+){
+    let mut lifetimes = HashMap::new();
+    for object in real_q.iter(){
+        match object.object_type {
+            ObjectType::PickUP { lifetime, .. } => {lifetimes.insert(object.id, lifetime);}
+            _ => {}
+        }
+    }
+    for (object, mut visibility) in all_q.iter_mut(){
+        let lifetime = lifetimes.get(&object.id);
+        if lifetime.is_none() {continue;}
+        let lifetime = *lifetime.unwrap();
+        *visibility = if lifetime < cfg.powerup_blink_secs && (time.elapsed_seconds() * if lifetime < 1.5 {16.} else {8.}).sin() < 0. {
+            Visibility::Hidden
+        } else {
+            Visibility::Inherited
+        };
+    }
+}
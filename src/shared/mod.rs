@@ -0,0 +1,7 @@
+pub mod components;
+pub mod settings;
+pub mod game;
+
+pub use components::*;
+pub use settings::*;
+pub use game::*;
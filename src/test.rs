@@ -3,12 +3,12 @@ use bevy::render::render_resource::PrimitiveTopology;
 use bevy::sprite::{Mesh2dHandle, MaterialMesh2dBundle};
 use bevy::{prelude::*, core_pipeline::clear_color::ClearColorConfig};
 use bevy_egui::*;
-#[path = "console.rs"] mod console;
-#[path = "client_menu.rs"] mod client_menu;
+use rusteroids::server::console;
+use rusteroids::client::menu as client_menu;
 use bevy_egui::egui::RawInput;
 use bevy_inspector_egui::quick::WorldInspectorPlugin;
 use console::*;
-#[path = "game.rs"] mod game;
+use rusteroids::shared as game;
 use game::*;
 use game::components::*;
 
@@ -43,10 +43,9 @@ fn setup(
     mut materials: ResMut<Assets<ColorMaterial>>,
     mut images: ResMut<Assets<Image>>,
 ){
-    spawn_powerup( PowerUPType::Repair, Vec3::X * -80., &mut commands,&mut meshes, &mut materials, &asset_server, 0);
-    spawn_powerup( PowerUPType::ExtraDamage, Vec3::X * -40., &mut commands,&mut meshes, &mut materials, &asset_server, 0);
-    spawn_powerup( PowerUPType::Haste, Vec3::X * 0., &mut commands,&mut meshes, &mut materials, &asset_server, 0);
-    spawn_powerup( PowerUPType::SuperShield, Vec3::X * 40., &mut commands,&mut meshes, &mut materials, &asset_server, 0);
-    spawn_powerup( PowerUPType::Invisibility, Vec3::X * 80., &mut commands,&mut meshes, &mut materials, &asset_server, 0);
+    let cfg = GlobalConfig::default();
+    for (i, _) in cfg.powerups.iter().enumerate(){
+        spawn_powerup( PowerUPType(i as u16), f32::INFINITY, Vec3::X * (i as f32 * 40. - 80.), &mut commands,&mut meshes, &mut materials, &asset_server, &cfg, 0);
+    }
 }
 